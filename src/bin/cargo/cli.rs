@@ -31,11 +31,14 @@ Available unstable (nightly-only) flags:
 
     -Z avoid-dev-deps   -- Avoid installing dev-dependencies if possible
     -Z minimal-versions -- Install minimal dependency versions instead of maximum
+    -Z direct-minimal-versions -- Install minimal versions of direct dependencies only
     -Z no-index-update  -- Do not update the registry, avoids a network request for benchmarking
     -Z offline          -- Offline mode that does not perform network requests
     -Z unstable-options -- Allow the usage of unstable options such as --registry
     -Z config-profile   -- Read profiles from .cargo/config files
     -Z install-upgrade  -- `cargo install` will upgrade instead of failing
+    -Z msrv-policy      -- Prefer dependency versions compatible with the `rust-version`
+    -Z lockfile-v2      -- Write Cargo.lock with inline checksums and shorter dependencies
 
 Run with 'cargo -Z [FLAG] [SUBCOMMAND]'"
         );
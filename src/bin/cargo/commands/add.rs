@@ -0,0 +1,79 @@
+use crate::command_prelude::*;
+
+use cargo::core::dependency::Kind;
+use cargo::ops::{self, AddOptions};
+
+pub fn cli() -> App {
+    subcommand("add")
+        .about("Add dependencies to a Cargo.toml manifest file")
+        .arg(Arg::with_name("crate").multiple(true))
+        .arg(opt(
+            "dev",
+            "Add the dependencies as development dependencies",
+        ))
+        .arg(opt("build", "Add the dependencies as build dependencies").conflicts_with("dev"))
+        .arg_target_triple("Add the dependencies for the given target platform")
+        .arg(opt("features", "Space-separated list of features to enable").value_name("FEATURES"))
+        .arg(opt("optional", "Mark the dependencies as optional"))
+        .arg(opt("rename", "Rename the dependency").value_name("NAME"))
+        .arg(opt("path", "Filesystem path to the local crate to add").value_name("PATH"))
+        .arg(opt("git", "Git repository location of the crate to add").value_name("URI"))
+        .arg(opt("registry", "Registry to use").value_name("REGISTRY"))
+        .arg_manifest_path()
+        .after_help(
+            "\
+Each dependency is given as either `NAME` or `NAME@VERSION_REQ`. Without a
+version requirement, a new dependency from a registry requires its newest
+version, preferring versions which support the package's `rust-version`. With
+`-Z offline` the cached copy of the index is used.
+
+If the dependency is already listed, it is modified instead: its version is
+replaced if one is given and the features are added to the existing ones.
+The formatting and comments of the manifest are kept.
+",
+        )
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
+    if !config.cli_unstable().unstable_options {
+        return Err(failure::format_err!(
+            "`cargo add` is unstable, pass `-Z unstable-options` to enable it"
+        )
+        .into());
+    }
+
+    let ws = args.workspace(config)?;
+
+    let kind = if args.is_present("dev") {
+        Kind::Development
+    } else if args.is_present("build") {
+        Kind::Build
+    } else {
+        Kind::Normal
+    };
+    let features = values(args, "features")
+        .iter()
+        .flat_map(|s| s.split(|c: char| c == ',' || c.is_whitespace()))
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect();
+    let registry = if args.is_present("registry") {
+        args.registry(config)?
+    } else {
+        None
+    };
+
+    let opts = AddOptions {
+        crates: values(args, "crate"),
+        kind,
+        target: args.target(),
+        features,
+        optional: args.is_present("optional"),
+        rename: args.value_of("rename").map(|s| s.to_string()),
+        path: args.value_of_path("path", config),
+        git: args.value_of("git").map(|s| s.to_string()),
+        registry,
+    };
+    ops::add(&ws, &opts)?;
+    Ok(())
+}
@@ -45,6 +45,15 @@ pub fn cli() -> App {
             "no-fail-fast",
             "Run all benchmarks regardless of failure",
         ))
+        .arg(opt("save-baseline", "Save the results as the baseline NAME").value_name("NAME"))
+        .arg(opt("baseline", "Compare the results with the baseline NAME").value_name("NAME"))
+        .arg(
+            opt(
+                "regression-threshold",
+                "Slowdown compared with the baseline counted as a regression [default: 5]",
+            )
+            .value_name("PERCENT"),
+        )
         .after_help(
             "\
 The benchmark filtering argument BENCHNAME and all the arguments following the
@@ -66,6 +75,16 @@ Note that `--exclude` has to be specified in conjunction with the `--all` flag.
 The `--jobs` argument affects the building of the benchmark executable but does
 not affect how many jobs are used when running the benchmarks.
 
+The results of the benchmarks can be saved with `--save-baseline NAME`, and
+compared with a saved baseline with `--baseline NAME`. Baselines are stored in
+the `bench-baselines` directory of the target directory. The comparison fails
+if a benchmark got slower by more than the `--regression-threshold`
+percentage. With `--message-format json` a `bench-comparison` message is
+emitted for every benchmark.
+
+    cargo bench --save-baseline main
+    cargo bench --baseline main
+
 Compilation can be customized with the `bench` profile in the manifest.
 ",
         )
@@ -77,10 +96,36 @@ pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
 
     compile_opts.build_config.release = true;
 
+    let regression_threshold = match args.value_of("regression-threshold") {
+        Some(threshold) => match threshold.trim_end_matches('%').parse::<f64>() {
+            Ok(threshold) if threshold >= 0.0 => Some(threshold),
+            _ => {
+                return Err(CliError::new(
+                    failure::format_err!(
+                        "invalid regression threshold `{}`, expected a percentage such as `5`",
+                        threshold
+                    ),
+                    101,
+                ))
+            }
+        },
+        None => None,
+    };
+
     let ops = TestOptions {
         no_run: args.is_present("no-run"),
         no_fail_fast: args.is_present("no-fail-fast"),
         compile_opts,
+        reports: Vec::new(),
+        shard: None,
+        retries: 0,
+        timeout: None,
+        summary: false,
+        baselines: ops::BaselineOptions {
+            save: args.value_of("save-baseline").map(|s| s.to_string()),
+            compare: args.value_of("baseline").map(|s| s.to_string()),
+            regression_threshold,
+        },
     };
 
     let bench_args = args.value_of("BENCHNAME").into_iter();
@@ -1,6 +1,8 @@
 use crate::command_prelude::*;
 
-use cargo::ops;
+use cargo::core::Workspace;
+use cargo::ops::{self, CompileOptions};
+use cargo::CargoResult;
 
 pub fn cli() -> App {
     subcommand("build")
@@ -39,6 +41,7 @@ pub fn cli() -> App {
         )
         .arg_manifest_path()
         .arg_message_format()
+        .arg_watch()
         .arg_build_plan()
         .after_help(
             "\
@@ -49,13 +52,41 @@ Note that `--exclude` has to be specified in conjunction with the `--all` flag.
 Compilation can be configured via the use of profiles which are configured in
 the manifest. The default profile for this command is `dev`, but passing
 the --release flag will use the `release` profile instead.
+
+With `--watch` Cargo keeps running, and builds again whenever a manifest,
+source file or other input of the build changes.
 ",
         )
 }
 
 pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
     let ws = args.workspace(config)?;
-    let mut compile_opts = args.compile_options(config, CompileMode::Build, Some(&ws))?;
+    let compile_opts = compile_options(config, args, &ws)?;
+
+    if args.is_present("watch") {
+        let config: &Config = config;
+        ops::watch(&ws, || {
+            // Re-read the options too, as they depend on the manifests.
+            let ws = args.workspace(config)?;
+            let compile_opts = compile_options(config, args, &ws)?;
+            let compilation = ops::compile(&ws, &compile_opts)?;
+            Ok(ops::WatchRun {
+                input_files: compilation.input_files,
+                child: None,
+            })
+        })?;
+        return Ok(());
+    }
+    ops::compile(&ws, &compile_opts)?;
+    Ok(())
+}
+
+fn compile_options<'a>(
+    config: &'a Config,
+    args: &ArgMatches<'_>,
+    ws: &Workspace<'a>,
+) -> CargoResult<CompileOptions<'a>> {
+    let mut compile_opts = args.compile_options(config, CompileMode::Build, Some(ws))?;
 
     compile_opts.export_dir = args.value_of_path("out-dir", config);
     if compile_opts.export_dir.is_some() {
@@ -63,6 +94,5 @@ pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
             .cli_unstable()
             .fail_if_stable_opt("--out-dir", 6790)?;
     }
-    ops::compile(&ws, &compile_opts)?;
-    Ok(())
+    Ok(compile_opts)
 }
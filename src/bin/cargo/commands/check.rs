@@ -1,6 +1,8 @@
 use crate::command_prelude::*;
 
-use cargo::ops;
+use cargo::core::Workspace;
+use cargo::ops::{self, CompileOptions};
+use cargo::CargoResult;
 
 pub fn cli() -> App {
     subcommand("check")
@@ -33,6 +35,7 @@ pub fn cli() -> App {
         .arg_target_dir()
         .arg_manifest_path()
         .arg_message_format()
+        .arg_watch()
         .after_help(
             "\
 If the `--package` argument is given, then SPEC is a package ID specification
@@ -50,27 +53,49 @@ the `--release` flag will use the `release` profile instead.
 
 The `--profile test` flag can be used to check unit tests with the
 `#[cfg(test)]` attribute.
+
+With `--watch` Cargo keeps running, and checks again whenever a manifest,
+source file or other input of the build changes.
 ",
         )
 }
 
 pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
     let ws = args.workspace(config)?;
+    let compile_opts = compile_options(config, args, &ws)?;
+
+    if args.is_present("watch") {
+        let config: &Config = config;
+        ops::watch(&ws, || {
+            // Re-read the options too, as they depend on the manifests.
+            let ws = args.workspace(config)?;
+            let compile_opts = compile_options(config, args, &ws)?;
+            let compilation = ops::compile(&ws, &compile_opts)?;
+            Ok(ops::WatchRun {
+                input_files: compilation.input_files,
+                child: None,
+            })
+        })?;
+        return Ok(());
+    }
+    ops::compile(&ws, &compile_opts)?;
+    Ok(())
+}
+
+fn compile_options<'a>(
+    config: &'a Config,
+    args: &ArgMatches<'_>,
+    ws: &Workspace<'a>,
+) -> CargoResult<CompileOptions<'a>> {
     let test = match args.value_of("profile") {
         Some("test") => true,
         None => false,
-        Some(profile) => {
-            let err = failure::format_err!(
-                "unknown profile: `{}`, only `test` is \
-                 currently supported",
-                profile
-            );
-            return Err(CliError::new(err, 101));
-        }
+        Some(profile) => failure::bail!(
+            "unknown profile: `{}`, only `test` is \
+             currently supported",
+            profile
+        ),
     };
     let mode = CompileMode::Check { test };
-    let compile_opts = args.compile_options(config, mode, Some(&ws))?;
-
-    ops::compile(&ws, &compile_opts)?;
-    Ok(())
+    args.compile_options(config, mode, Some(ws))
 }
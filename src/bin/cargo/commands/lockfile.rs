@@ -0,0 +1,65 @@
+use crate::command_prelude::*;
+
+use std::path::Path;
+
+use cargo::ops;
+
+pub fn cli() -> App {
+    subcommand("lockfile")
+        .about("Operate on the lockfile of a package")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            subcommand("merge")
+                .about("Merge two versions of the lockfile, as a git merge driver")
+                .arg(Arg::with_name("BASE").required(true))
+                .arg(Arg::with_name("OURS").required(true))
+                .arg(Arg::with_name("THEIRS").required(true))
+                .arg_manifest_path()
+                .after_help(
+                    "\
+Merges the lockfiles OURS and THEIRS, which both derive from BASE, and writes
+the result to OURS. Packages added on either side are kept, and packages
+removed on either side are dropped. The dependencies are then resolved again
+against the manifests of the workspace, so the merged lockfile is consistent
+with them.
+
+The merge only fails if the conflict can't be resolved automatically, for
+example if both sides locked a git dependency to different revisions.
+
+To use it as the merge driver of `Cargo.lock` files, add the following to
+`.git/config`:
+
+    [merge \"cargo-lock\"]
+        name = Cargo.lock merge driver
+        driver = cargo -Z unstable-options lockfile merge %O %A %B
+
+and the following to `.gitattributes`:
+
+    Cargo.lock merge=cargo-lock
+",
+                ),
+        )
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
+    if !config.cli_unstable().unstable_options {
+        return Err(failure::format_err!(
+            "`cargo lockfile` is unstable, pass `-Z unstable-options` to enable it"
+        )
+        .into());
+    }
+
+    match args.subcommand() {
+        ("merge", Some(args)) => {
+            let ws = args.workspace(config)?;
+            ops::merge_lockfiles(
+                &ws,
+                Path::new(args.value_of("BASE").unwrap()),
+                Path::new(args.value_of("OURS").unwrap()),
+                Path::new(args.value_of("THEIRS").unwrap()),
+            )?;
+            Ok(())
+        }
+        _ => unreachable!("clap requires a subcommand"),
+    }
+}
@@ -2,6 +2,7 @@ use crate::command_prelude::*;
 
 pub fn builtin() -> Vec<App> {
     vec![
+        add::cli(),
         bench::cli(),
         build::cli(),
         check::cli(),
@@ -15,6 +16,7 @@ pub fn builtin() -> Vec<App> {
         init::cli(),
         install::cli(),
         locate_project::cli(),
+        lockfile::cli(),
         login::cli(),
         metadata::cli(),
         new::cli(),
@@ -23,13 +25,16 @@ pub fn builtin() -> Vec<App> {
         pkgid::cli(),
         publish::cli(),
         read_manifest::cli(),
+        remove::cli(),
         run::cli(),
         rustc::cli(),
         rustdoc::cli(),
         search::cli(),
         test::cli(),
+        tree::cli(),
         uninstall::cli(),
         update::cli(),
+        upgrade::cli(),
         verify_project::cli(),
         version::cli(),
         yank::cli(),
@@ -38,6 +43,7 @@ pub fn builtin() -> Vec<App> {
 
 pub fn builtin_exec(cmd: &str) -> Option<fn(&mut Config, &ArgMatches<'_>) -> CliResult> {
     let f = match cmd {
+        "add" => add::exec,
         "bench" => bench::exec,
         "build" => build::exec,
         "check" => check::exec,
@@ -51,6 +57,7 @@ pub fn builtin_exec(cmd: &str) -> Option<fn(&mut Config, &ArgMatches<'_>) -> Cli
         "init" => init::exec,
         "install" => install::exec,
         "locate-project" => locate_project::exec,
+        "lockfile" => lockfile::exec,
         "login" => login::exec,
         "metadata" => metadata::exec,
         "new" => new::exec,
@@ -59,13 +66,16 @@ pub fn builtin_exec(cmd: &str) -> Option<fn(&mut Config, &ArgMatches<'_>) -> Cli
         "pkgid" => pkgid::exec,
         "publish" => publish::exec,
         "read-manifest" => read_manifest::exec,
+        "remove" => remove::exec,
         "run" => run::exec,
         "rustc" => rustc::exec,
         "rustdoc" => rustdoc::exec,
         "search" => search::exec,
         "test" => test::exec,
+        "tree" => tree::exec,
         "uninstall" => uninstall::exec,
         "update" => update::exec,
+        "upgrade" => upgrade::exec,
         "verify-project" => verify_project::exec,
         "version" => version::exec,
         "yank" => yank::exec,
@@ -74,6 +84,7 @@ pub fn builtin_exec(cmd: &str) -> Option<fn(&mut Config, &ArgMatches<'_>) -> Cli
     Some(f)
 }
 
+pub mod add;
 pub mod bench;
 pub mod build;
 pub mod check;
@@ -87,6 +98,7 @@ pub mod git_checkout;
 pub mod init;
 pub mod install;
 pub mod locate_project;
+pub mod lockfile;
 pub mod login;
 pub mod metadata;
 pub mod new;
@@ -95,13 +107,16 @@ pub mod package;
 pub mod pkgid;
 pub mod publish;
 pub mod read_manifest;
+pub mod remove;
 pub mod run;
 pub mod rustc;
 pub mod rustdoc;
 pub mod search;
 pub mod test;
+pub mod tree;
 pub mod uninstall;
 pub mod update;
+pub mod upgrade;
 pub mod verify_project;
 pub mod version;
 pub mod yank;
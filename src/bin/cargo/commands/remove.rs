@@ -0,0 +1,57 @@
+use crate::command_prelude::*;
+
+use cargo::core::dependency::Kind;
+use cargo::ops::{self, RemoveOptions};
+
+pub fn cli() -> App {
+    subcommand("remove")
+        .about("Remove dependencies from a Cargo.toml manifest file")
+        .arg(Arg::with_name("crate").multiple(true).required(true))
+        .arg(opt(
+            "dev",
+            "Remove the dependencies from the development dependencies",
+        ))
+        .arg(
+            opt(
+                "build",
+                "Remove the dependencies from the build dependencies",
+            )
+            .conflicts_with("dev"),
+        )
+        .arg_target_triple("Remove the dependencies from the given target platform")
+        .arg_manifest_path()
+        .after_help(
+            "\
+Each dependency is given by the name it has in the manifest. Features which
+refer to a removed optional dependency are updated as well. The formatting and
+comments of the manifest are kept.
+",
+        )
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
+    if !config.cli_unstable().unstable_options {
+        return Err(failure::format_err!(
+            "`cargo remove` is unstable, pass `-Z unstable-options` to enable it"
+        )
+        .into());
+    }
+
+    let ws = args.workspace(config)?;
+
+    let kind = if args.is_present("dev") {
+        Kind::Development
+    } else if args.is_present("build") {
+        Kind::Build
+    } else {
+        Kind::Normal
+    };
+
+    let opts = RemoveOptions {
+        crates: values(args, "crate"),
+        kind,
+        target: args.target(),
+    };
+    ops::remove(&ws, &opts)?;
+    Ok(())
+}
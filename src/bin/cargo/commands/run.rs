@@ -1,7 +1,8 @@
 use crate::command_prelude::*;
 
-use cargo::core::Verbosity;
-use cargo::ops::{self, CompileFilter};
+use cargo::core::{Verbosity, Workspace};
+use cargo::ops::{self, CompileFilter, CompileOptions};
+use cargo::CargoResult;
 
 pub fn cli() -> App {
     subcommand("run")
@@ -23,6 +24,7 @@ pub fn cli() -> App {
         .arg_target_dir()
         .arg_manifest_path()
         .arg_message_format()
+        .arg_watch()
         .after_help(
             "\
 If neither `--bin` nor `--example` are given, then if the package only has one
@@ -33,6 +35,10 @@ and `--example` specifies the example target to run. At most one of `--bin` or
 All the arguments following the two dashes (`--`) are passed to the binary to
 run. If you're passing arguments to both Cargo and the binary, the ones after
 `--` go to the binary, the ones before go to Cargo.
+
+With `--watch` Cargo keeps running, and whenever a manifest, source file or
+other input of the build changes it stops the binary, builds it again and
+restarts it.
 ",
         )
 }
@@ -40,12 +46,62 @@ run. If you're passing arguments to both Cargo and the binary, the ones after
 pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
     let ws = args.workspace(config)?;
 
-    let mut compile_opts = args.compile_options(config, CompileMode::Build, Some(&ws))?;
+    let compile_opts = compile_options(config, args, &ws)?;
+
+    if args.is_present("watch") {
+        let config: &Config = config;
+        let run_args = values(args, "args");
+        ops::watch(&ws, || {
+            // Re-read the options too, as they depend on the manifests.
+            let ws = args.workspace(config)?;
+            let compile_opts = compile_options(config, args, &ws)?;
+            let (compilation, process) = ops::compile_run(&ws, &compile_opts, &run_args)?;
+            let child = process.spawn_process_group()?;
+            Ok(ops::WatchRun {
+                input_files: compilation.input_files,
+                child: Some(child),
+            })
+        })?;
+        return Ok(());
+    }
+
+    match ops::run(&ws, &compile_opts, &values(args, "args"))? {
+        None => Ok(()),
+        Some(err) => {
+            // If we never actually spawned the process then that sounds pretty
+            // bad and we always want to forward that up.
+            let exit = match err.exit {
+                Some(exit) => exit,
+                None => return Err(CliError::new(err.into(), 101)),
+            };
+
+            // If `-q` was passed then we suppress extra error information about
+            // a failed process, we assume the process itself printed out enough
+            // information about why it failed so we don't do so as well
+            let exit_code = exit.code().unwrap_or(101);
+            let is_quiet = config.shell().verbosity() == Verbosity::Quiet;
+            Err(if is_quiet {
+                CliError::code(exit_code)
+            } else {
+                CliError::new(err.into(), exit_code)
+            })
+        }
+    }
+}
+
+/// Builds the options of the build, running the `default-run` binary if
+/// no target was selected.
+fn compile_options<'a>(
+    config: &'a Config,
+    args: &ArgMatches<'_>,
+    ws: &Workspace<'a>,
+) -> CargoResult<CompileOptions<'a>> {
+    let mut compile_opts = args.compile_options(config, CompileMode::Build, Some(ws))?;
 
     if !args.is_present("example") && !args.is_present("bin") {
         let default_runs: Vec<_> = compile_opts
             .spec
-            .get_packages(&ws)?
+            .get_packages(ws)?
             .iter()
             .filter_map(|pkg| pkg.manifest().default_run())
             .collect();
@@ -71,27 +127,6 @@ pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
                 required_features_filterable: false,
             };
         }
-    };
-    match ops::run(&ws, &compile_opts, &values(args, "args"))? {
-        None => Ok(()),
-        Some(err) => {
-            // If we never actually spawned the process then that sounds pretty
-            // bad and we always want to forward that up.
-            let exit = match err.exit {
-                Some(exit) => exit,
-                None => return Err(CliError::new(err.into(), 101)),
-            };
-
-            // If `-q` was passed then we suppress extra error information about
-            // a failed process, we assume the process itself printed out enough
-            // information about why it failed so we don't do so as well
-            let exit_code = exit.code().unwrap_or(101);
-            let is_quiet = config.shell().verbosity() == Verbosity::Quiet;
-            Err(if is_quiet {
-                CliError::code(exit_code)
-            } else {
-                CliError::new(err.into(), exit_code)
-            })
-        }
     }
+    Ok(compile_opts)
 }
@@ -1,4 +1,7 @@
+use cargo::core::Workspace;
 use cargo::ops::{self, CompileFilter, FilterRule, LibRule};
+use cargo::util::CargoResultExt;
+use cargo::CargoResult;
 
 use crate::command_prelude::*;
 
@@ -40,6 +43,24 @@ pub fn cli() -> App {
         .arg(opt("doc", "Test only this library's documentation"))
         .arg(opt("no-run", "Compile, but don't run tests"))
         .arg(opt("no-fail-fast", "Run all tests regardless of failure"))
+        .arg(opt(
+            "summary",
+            "Print a summary of the results of all test binaries",
+        ))
+        .arg(multi_opt(
+            "report",
+            "FORMAT=PATH",
+            "Write a report of the test results, e.g. `junit=report.xml`",
+        ))
+        .arg(opt("shard", "Only run the tests in shard K of N, e.g. `1/4`").value_name("K/N"))
+        .arg(opt("retries", "Rerun failed tests up to N times before failing").value_name("N"))
+        .arg(
+            opt(
+                "test-timeout",
+                "Kill test binaries running longer than DURATION",
+            )
+            .value_name("DURATION"),
+        )
         .arg_package_spec(
             "Package to run tests for",
             "Test all packages in the workspace",
@@ -52,6 +73,7 @@ pub fn cli() -> App {
         .arg_target_dir()
         .arg_manifest_path()
         .arg_message_format()
+        .arg_watch()
         .after_help(
             "\
 The test filtering argument TESTNAME and all the arguments following the
@@ -89,6 +111,35 @@ by passing `--nocapture` to the test binaries:
 
     cargo test -- --nocapture
 
+A report of the results of all test binaries, including doc tests, can be
+written for CI systems with the `--report` flag. The only supported format is
+JUnit XML:
+
+    cargo test --report junit=target/junit.xml
+
+The tests can be split across several machines with `--shard K/N`, which
+runs the K-th of N disjoint subsets of the tests. Every test is assigned to a
+shard by a hash of its package, target and name, so the assignment is stable
+as tests are added. Test binaries with `harness = false` are assigned as a
+whole. A TESTNAME filter selects the tests before they are split into shards.
+
+Tests which fail can be rerun by name with `--retries N`. A test which passes
+when retried is reported as flaky rather than failing, and with
+`--message-format json` a `flaky-test` message is emitted for it.
+
+A test binary which hangs can be killed, along with any process it started,
+with `--test-timeout DURATION`, where the duration is given as `90s`, `10m` or
+`1h`. The `test.timeout` configuration key sets a default. The tests which
+were still running are reported, and the test binary counts as failed.
+
+With `--summary` the results of every test binary, the totals and the names of
+the failed tests are printed once all tests have run. With
+`--message-format json` the summary is also emitted as a `test-summary`
+message.
+
+With `--watch` Cargo keeps running, and builds and runs the tests again
+whenever a manifest, source file or other input of the build changes.
+
 To get the list of all options available for the test binaries use this:
 
     cargo test -- --help
@@ -99,29 +150,61 @@ To get the list of all options available for the test binaries use this:
 pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
     let ws = args.workspace(config)?;
 
-    let mut compile_opts = args.compile_options(config, CompileMode::Test, Some(&ws))?;
+    let ops = test_options(config, args, &ws)?;
 
     // `TESTNAME` is actually an argument of the test binary, but it's
     // important, so we explicitly mention it and reconfigure.
-    let test_name: Option<&str> = args.value_of("TESTNAME");
     let test_args = args.value_of("TESTNAME").into_iter();
     let test_args = test_args.chain(args.values_of("args").unwrap_or_default());
     let test_args = test_args.collect::<Vec<_>>();
 
+    if args.is_present("watch") {
+        let config: &Config = config;
+        ops::watch(&ws, || {
+            // Re-read the options too, as they depend on the manifests.
+            let ws = args.workspace(config)?;
+            let ops = test_options(config, args, &ws)?;
+            let compilation = ops::compile_tests(&ws, &ops)?;
+            if let Some(err) = ops::run_compiled_tests(&ops, &test_args, &compilation)? {
+                config.shell().error(err.hint(&ws, &ops.compile_opts))?;
+            }
+            Ok(ops::WatchRun {
+                input_files: compilation.input_files,
+                child: None,
+            })
+        })?;
+        return Ok(());
+    }
+
+    let err = ops::run_tests(&ws, &ops, &test_args)?;
+    match err {
+        None => Ok(()),
+        Some(err) => Err(match err.exit.as_ref().and_then(|e| e.code()) {
+            Some(i) => CliError::new(
+                failure::format_err!("{}", err.hint(&ws, &ops.compile_opts)),
+                i,
+            ),
+            None => CliError::new(err.into(), 101),
+        }),
+    }
+}
+
+fn test_options<'a>(
+    config: &'a Config,
+    args: &ArgMatches<'_>,
+    ws: &Workspace<'a>,
+) -> CargoResult<ops::TestOptions<'a>> {
+    let test_name: Option<&str> = args.value_of("TESTNAME");
+    let mut compile_opts = args.compile_options(config, CompileMode::Test, Some(ws))?;
+
     let no_run = args.is_present("no-run");
     let doc = args.is_present("doc");
     if doc {
         if let CompileFilter::Only { .. } = compile_opts.filter {
-            return Err(CliError::new(
-                failure::format_err!("Can't mix --doc with other target selecting options"),
-                101,
-            ));
+            failure::bail!("Can't mix --doc with other target selecting options");
         }
         if no_run {
-            return Err(CliError::new(
-                failure::format_err!("Can't skip running doc tests with --no-run"),
-                101,
-            ));
+            failure::bail!("Can't skip running doc tests with --no-run");
         }
         compile_opts.build_config.mode = CompileMode::Doctest;
         compile_opts.filter = ops::CompileFilter::new(
@@ -143,21 +226,34 @@ pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
         }
     }
 
-    let ops = ops::TestOptions {
+    let reports = values(args, "report")
+        .iter()
+        .map(|report| ops::TestReport::parse(report, config.cwd()))
+        .collect::<cargo::CargoResult<Vec<_>>>()?;
+    let shard = match args.value_of("shard") {
+        Some(shard) => Some(ops::TestShard::parse(shard)?),
+        None => None,
+    };
+
+    let timeout = match args.value_of("test-timeout") {
+        Some(timeout) => Some(ops::parse_test_timeout(timeout)?),
+        None => match config.get_string("test.timeout")? {
+            Some(timeout) => Some(ops::parse_test_timeout(&timeout.val).chain_err(|| {
+                format!("invalid `test.timeout` defined in {}", timeout.definition)
+            })?),
+            None => None,
+        },
+    };
+
+    Ok(ops::TestOptions {
         no_run,
         no_fail_fast: args.is_present("no-fail-fast"),
         compile_opts,
-    };
-
-    let err = ops::run_tests(&ws, &ops, &test_args)?;
-    match err {
-        None => Ok(()),
-        Some(err) => Err(match err.exit.as_ref().and_then(|e| e.code()) {
-            Some(i) => CliError::new(
-                failure::format_err!("{}", err.hint(&ws, &ops.compile_opts)),
-                i,
-            ),
-            None => CliError::new(err.into(), 101),
-        }),
-    }
+        reports,
+        shard,
+        retries: args.value_of_u32("retries")?.unwrap_or(0),
+        timeout,
+        summary: args.is_present("summary"),
+        baselines: ops::BaselineOptions::default(),
+    })
 }
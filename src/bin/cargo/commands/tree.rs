@@ -0,0 +1,109 @@
+use crate::command_prelude::*;
+
+use cargo::ops::{self, Packages, TreeOptions};
+
+pub fn cli() -> App {
+    subcommand("tree")
+        .about("Display the resolved dependency graph of a package as a tree")
+        .arg_package_spec(
+            "Package(s) to display the dependencies of",
+            "Display the dependencies of all packages in the workspace",
+            "Exclude packages from the display",
+        )
+        .arg_features()
+        .arg_target_triple("Only display the dependencies used on the target triple")
+        .arg_manifest_path()
+        .arg(
+            opt(
+                "invert",
+                "Display the packages which depend on the given package",
+            )
+            .short("i")
+            .value_name("SPEC"),
+        )
+        .arg(
+            opt(
+                "duplicates",
+                "Display the packages which depend on packages used in several versions",
+            )
+            .short("d"),
+        )
+        .arg(
+            opt("edges", "The kinds of dependencies to display")
+                .short("e")
+                .value_name("KINDS"),
+        )
+        .arg(opt("depth", "Maximum depth of the tree to display").value_name("DEPTH"))
+        .arg(
+            opt("format", "Format string used to display each package")
+                .short("f")
+                .value_name("FORMAT")
+                .default_value("{p}"),
+        )
+        .arg(
+            opt("message-format", "Output representation")
+                .value_name("FMT")
+                .possible_values(&["human", "json"])
+                .default_value("human"),
+        )
+        .after_help(
+            "\
+The tree starts at the current package, or at every package of the workspace
+with `--all` or a virtual manifest. A package which was already displayed
+higher up in the tree is marked with `(*)` and its dependencies are left out.
+
+With `--invert SPEC` the tree shows the packages which depend on SPEC instead,
+answering why it is part of the build. `--duplicates` does the same for every
+package which is used in more than one version.
+
+`--edges` takes a comma separated list of the kinds of dependencies to follow,
+out of `normal`, `build` and `dev`; all of them are followed by default. With
+`--target`, platform-specific dependencies which aren't used on the target
+are left out.
+
+The `--format` string may contain the following placeholders:
+
+    {p}  the package name, version and source
+    {l}  the license of the package
+    {r}  the repository URL of the package
+    {f}  the enabled features of the package
+
+With `--message-format json` the tree is printed as a JSON document instead.
+",
+        )
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
+    if !config.cli_unstable().unstable_options {
+        return Err(failure::format_err!(
+            "`cargo tree` is unstable, pass `-Z unstable-options` to enable it"
+        )
+        .into());
+    }
+
+    let ws = args.workspace(config)?;
+
+    let edge_kinds = match args.value_of("edges") {
+        Some(edges) => ops::parse_edge_kinds(edges)?,
+        None => ops::parse_edge_kinds("normal,build,dev")?,
+    };
+    let opts = TreeOptions {
+        features: values(args, "features"),
+        all_features: args.is_present("all-features"),
+        no_default_features: args.is_present("no-default-features"),
+        packages: Packages::from_flags(
+            args.is_present("all"),
+            values(args, "exclude"),
+            values(args, "package"),
+        )?,
+        target: args.target(),
+        invert: args.value_of("invert").map(|s| s.to_string()),
+        duplicates: args.is_present("duplicates"),
+        edge_kinds,
+        max_depth: args.value_of_u32("depth")?,
+        format: args.value_of("format").unwrap().to_string(),
+        json: args.value_of("message-format") == Some("json"),
+    };
+    ops::tree(&ws, &opts)?;
+    Ok(())
+}
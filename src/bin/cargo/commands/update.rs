@@ -13,6 +13,12 @@ pub fn cli() -> App {
         ))
         .arg_dry_run("Don't actually write the lockfile")
         .arg(opt("precise", "Update a single dependency to exactly PRECISE").value_name("PRECISE"))
+        .arg(
+            opt("message-format", "Output representation")
+                .value_name("FMT")
+                .possible_values(&["human", "json"])
+                .default_value("human"),
+        )
         .arg_manifest_path()
         .after_help(
             "\
@@ -34,6 +40,11 @@ be updated to.
 If SPEC is not given, then all dependencies will be re-resolved and
 updated.
 
+With `--dry-run`, the lockfile is left untouched and changes which aren't
+semver compatible, such as from 1.2.0 to 2.0.0 or from 0.1.0 to 0.2.0, are
+marked as breaking. With `--message-format json` the added, removed, upgraded
+and downgraded packages are printed as a JSON document instead.
+
 For more information about package ID specifications, see `cargo help pkgid`.
 ",
         )
@@ -47,6 +58,7 @@ pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
         precise: args.value_of("precise"),
         to_update: values(args, "package"),
         dry_run: args.is_present("dry-run"),
+        json: args.value_of("message-format") == Some("json"),
         config,
     };
     ops::update_lockfile(&ws, &update_opts)?;
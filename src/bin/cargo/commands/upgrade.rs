@@ -0,0 +1,59 @@
+use crate::command_prelude::*;
+
+use cargo::ops::{self, UpgradeOptions};
+
+pub fn cli() -> App {
+    subcommand("upgrade")
+        .about("Upgrade the version requirements of dependencies in the manifests")
+        .arg(Arg::with_name("dependency").multiple(true))
+        .arg(opt(
+            "compatible",
+            "Only upgrade to newer versions the requirements already allow",
+        ))
+        .arg(opt(
+            "incompatible",
+            "Only upgrade to newer versions the requirements don't allow yet",
+        ))
+        .arg(opt(
+            "dry-run",
+            "Report the upgrades without changing the manifests",
+        ))
+        .arg_manifest_path()
+        .after_help(
+            "\
+Rewrites the version requirements of the dependencies of every workspace member
+to the newest available versions, keeping the formatting of the manifests, and
+updates Cargo.lock accordingly. Given DEPENDENCY names, only the dependencies on
+those packages are upgraded.
+
+By default both compatible upgrades, such as `1.0` to `1.4`, and incompatible
+ones, such as `1.0` to `2.1`, are made. `--compatible` and `--incompatible`
+restrict the upgrades to one kind.
+
+The operator and precision of each requirement are kept. Pinned requirements
+like `=1.0.3`, ranges and wildcards are never changed. Path dependencies on
+other workspace members are upgraded to the member's current version.
+Pre-release versions are only used if the requirement already names one.
+",
+        )
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
+    if !config.cli_unstable().unstable_options {
+        return Err(failure::format_err!(
+            "`cargo upgrade` is unstable, pass `-Z unstable-options` to enable it"
+        )
+        .into());
+    }
+
+    let ws = args.workspace(config)?;
+
+    let opts = UpgradeOptions {
+        to_upgrade: values(args, "dependency"),
+        compatible: args.is_present("compatible"),
+        incompatible: args.is_present("incompatible"),
+        dry_run: args.is_present("dry-run"),
+    };
+    ops::upgrade(&ws, &opts)?;
+    Ok(())
+}
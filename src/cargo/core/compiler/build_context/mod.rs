@@ -5,7 +5,9 @@ use std::str;
 
 use log::debug;
 
+use crate::core::dependency::Platform;
 use crate::core::profiles::Profiles;
+use crate::core::resolver::{FeaturesFor, ResolvedFeatures};
 use crate::core::{Dependency, Workspace};
 use crate::core::{PackageId, PackageSet, Resolve};
 use crate::util::errors::CargoResult;
@@ -29,6 +31,9 @@ pub struct BuildContext<'a, 'cfg: 'a> {
     /// Extra compiler args for either `rustc` or `rustdoc`.
     pub extra_compiler_args: HashMap<Unit<'a>, Vec<String>>,
     pub packages: &'a PackageSet<'cfg>,
+    /// The features of every package when the workspace uses
+    /// `resolver = "2"`, otherwise the features in `resolve` are used.
+    pub features: Option<ResolvedFeatures>,
 
     /// Information about the compiler.
     pub rustc: Rustc,
@@ -83,20 +88,71 @@ impl<'a, 'cfg> BuildContext<'a, 'cfg> {
             build_config,
             profiles,
             extra_compiler_args,
+            features: None,
         })
     }
 
+    /// Returns the features enabled for the package of `unit`, sorted.
+    pub fn unit_features(&self, unit: &Unit<'a>) -> Vec<&str> {
+        let pkg_id = unit.pkg.package_id();
+        match &self.features {
+            Some(features) => features.activated(pkg_id, unit.features_for),
+            None => self.resolve.features_sorted(pkg_id),
+        }
+    }
+
+    /// Whether the feature or optional dependency `name` is enabled for the
+    /// package of `unit`.
+    pub fn is_feature_activated(&self, unit: &Unit<'a>, name: &str) -> bool {
+        let pkg_id = unit.pkg.package_id();
+        match &self.features {
+            Some(features) => features.is_activated(pkg_id, unit.features_for, name),
+            None => self.resolve.features(pkg_id).contains(name),
+        }
+    }
+
+    /// Whether the optional dependency `dep_name` of the package of `unit` is
+    /// enabled, either by name or with `dep:`.
+    pub fn is_dep_activated(&self, unit: &Unit<'a>, dep_name: &str) -> bool {
+        if self.is_feature_activated(unit, &format!("dep:{}", dep_name)) {
+            return true;
+        }
+        !unit.pkg.summary().is_explicit_dep(dep_name) && self.is_feature_activated(unit, dep_name)
+    }
+
+    /// Returns which feature set a unit of `pkg_id` is built with, given
+    /// whether it is built for the host, see `FeaturesFor`.
+    pub fn features_for(&self, pkg_id: PackageId, for_host: bool) -> FeaturesFor {
+        match &self.features {
+            Some(features) => features.features_for(pkg_id, for_host),
+            None => FeaturesFor::Normal,
+        }
+    }
+
     pub fn extern_crate_name(&self, unit: &Unit<'a>, dep: &Unit<'a>) -> CargoResult<String> {
         self.resolve
             .extern_crate_name(unit.pkg.package_id(), dep.pkg.package_id(), dep.target)
     }
 
+    /// Whether `dep` is a public dependency of `unit`, as opposed to one only
+    /// used in its implementation.
+    pub fn is_public_dependency(&self, unit: &Unit<'a>, dep: &Unit<'a>) -> bool {
+        self.resolve
+            .is_public_dep(unit.pkg.package_id(), dep.pkg.package_id())
+    }
+
     /// Whether a dependency should be compiled for the host or target platform,
     /// specified by `Kind`.
     pub fn dep_platform_activated(&self, dep: &Dependency, kind: Kind) -> bool {
         // If this dependency is only available for certain platforms,
         // make sure we're only enabling it for that platform.
-        let platform = match dep.platform() {
+        self.platform_activated(dep.platform(), kind)
+    }
+
+    /// Whether something only used on `platform`, if it is given, is used
+    /// when building for the host or target platform.
+    pub fn platform_activated(&self, platform: Option<&Platform>, kind: Kind) -> bool {
+        let platform = match platform {
             Some(p) => p,
             None => return true,
         };
@@ -64,6 +64,12 @@ pub struct Compilation<'cfg> {
     /// Flags to pass to rustdoc when invoked from cargo test, per package.
     pub rustdocflags: HashMap<PackageId, Vec<String>>,
 
+    /// Files the local packages were built from, as listed in the rustc
+    /// dep-info and the `rerun-if-changed` paths of build scripts.
+    ///
+    /// This is used by `--watch` to find out what to watch.
+    pub input_files: BTreeSet<PathBuf>,
+
     pub host: String,
     pub target: String,
 
@@ -98,6 +104,7 @@ impl<'cfg> Compilation<'cfg> {
             to_doc_test: Vec::new(),
             cfgs: HashMap::new(),
             rustdocflags: HashMap::new(),
+            input_files: BTreeSet::new(),
             config: bcx.config,
             rustc_process: rustc,
             host: bcx.host_triple().to_string(),
@@ -476,9 +476,7 @@ fn compute_metadata<'a, 'cfg>(
 
     // Also mix in enabled features to our metadata. This'll ensure that
     // when changing feature sets each lib is separately cached.
-    bcx.resolve
-        .features_sorted(unit.pkg.package_id())
-        .hash(&mut hasher);
+    bcx.unit_features(unit).hash(&mut hasher);
 
     // Mix in the target-metadata of all the dependencies of this target.
     {
@@ -1,5 +1,5 @@
 #![allow(deprecated)]
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::ffi::OsStr;
 use std::fmt::Write;
 use std::path::PathBuf;
@@ -9,6 +9,7 @@ use jobserver::Client;
 
 use crate::core::compiler::compilation;
 use crate::core::profiles::Profile;
+use crate::core::resolver::FeaturesFor;
 use crate::core::{Package, PackageId, Resolve, Target};
 use crate::util::errors::{CargoResult, CargoResultExt};
 use crate::util::{internal, profile, short_hash, Config};
@@ -62,6 +63,9 @@ pub struct Unit<'a> {
     pub kind: Kind,
     /// The "mode" this unit is being compiled for. See [`CompileMode`] for more details.
     pub mode: CompileMode,
+    /// Which of the feature sets of `pkg` this unit is built with. This is always
+    /// `FeaturesFor::Normal` unless the workspace uses `resolver = "2"`.
+    pub features_for: FeaturesFor,
 }
 
 impl<'a> Unit<'a> {
@@ -134,6 +138,8 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
         let mut plan = BuildPlan::new();
         let build_plan = self.bcx.build_config.build_plan;
         self.prepare_units(export_dir, units)?;
+        self.check_rust_version()?;
+        self.check_platform_features()?;
         self.prepare()?;
         custom_build::build_map(&mut self, units)?;
         self.check_collistions()?;
@@ -156,6 +162,7 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
             plan.output_plan();
         }
 
+        let mut input_units = HashSet::new();
         for unit in units.iter() {
             for output in self.outputs(unit)?.iter() {
                 if output.flavor == FileFlavor::DebugInfo {
@@ -226,7 +233,7 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
                 });
             }
 
-            let feats = self.bcx.resolve.features(unit.pkg.package_id());
+            let feats = self.bcx.unit_features(unit);
             if !feats.is_empty() {
                 self.compilation
                     .cfgs
@@ -247,6 +254,14 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
             }
 
             super::output_depinfo(&mut self, unit)?;
+            let mut input_files = BTreeSet::new();
+            super::output_depinfo::add_input_files(
+                &mut input_files,
+                &mut self,
+                unit,
+                &mut input_units,
+            );
+            self.compilation.input_files.extend(input_files);
         }
 
         for (&(ref pkg, _), output) in self.build_state.outputs.lock().unwrap().iter() {
@@ -413,6 +428,62 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
         Ok(inputs)
     }
 
+    /// Fails if any of the packages to build requires a newer rustc, through
+    /// its `rust-version`, than the one which is used.
+    fn check_rust_version(&self) -> CargoResult<()> {
+        let rustc_version = &self.bcx.rustc.version;
+        // Pre-release compilers, such as nightlies, are considered to
+        // support the version they are a pre-release of.
+        let rustc_version = semver::Version::new(
+            rustc_version.major,
+            rustc_version.minor,
+            rustc_version.patch,
+        );
+        // Report the first one by id so the error is deterministic.
+        let too_new = self
+            .unit_dependencies
+            .keys()
+            .map(|unit| unit.pkg)
+            .filter(|pkg| match pkg.manifest().rust_version() {
+                Some(rust_version) => *rust_version > rustc_version,
+                None => false,
+            })
+            .min_by_key(|pkg| pkg.package_id());
+        if let Some(pkg) = too_new {
+            failure::bail!(
+                "package `{}` cannot be built because it requires rustc {} or newer, \
+                 while the currently active rustc version is {}",
+                pkg.package_id(),
+                pkg.manifest().rust_version().unwrap(),
+                self.bcx.rustc.version
+            );
+        }
+        Ok(())
+    }
+
+    /// The `[target.'cfg(..)'.features]` of a package are only evaluated by
+    /// the `resolver = "2"` feature resolver, the default one would enable
+    /// the values of every platform.
+    fn check_platform_features(&self) -> CargoResult<()> {
+        if self.bcx.features.is_some() {
+            return Ok(());
+        }
+        let uses_platform_features = self
+            .unit_dependencies
+            .keys()
+            .map(|unit| unit.pkg)
+            .filter(|pkg| !pkg.summary().platform_features().is_empty())
+            .min_by_key(|pkg| pkg.package_id());
+        if let Some(pkg) = uses_platform_features {
+            failure::bail!(
+                "package `{}` has `[target.'cfg(..)'.features]`, which are only \
+                 supported with `resolver = \"2\"` in the root manifest of the workspace",
+                pkg.package_id()
+            );
+        }
+        Ok(())
+    }
+
     fn check_collistions(&self) -> CargoResult<()> {
         let mut output_collisions = HashMap::new();
         let describe_collision =
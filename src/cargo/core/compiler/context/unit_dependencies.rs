@@ -158,7 +158,7 @@ fn compute_deps<'a, 'cfg, 'tmp>(
 
             // If the dependency is optional, then we're only activating it
             // if the corresponding feature was activated
-            if dep.is_optional() && !bcx.resolve.features(id).contains(&*dep.name_in_toml()) {
+            if dep.is_optional() && !bcx.is_dep_activated(unit, &dep.name_in_toml()) {
                 return false;
             }
 
@@ -228,7 +228,7 @@ fn compute_deps<'a, 'cfg, 'tmp>(
                     t.is_bin() &&
                         // Skip binaries with required features that have not been selected.
                         t.required_features().unwrap_or(&no_required_features).iter().all(|f| {
-                            bcx.resolve.features(id).contains(f)
+                            bcx.is_feature_activated(unit, f)
                         })
                 })
                 .map(|t| {
@@ -267,7 +267,7 @@ fn compute_deps_custom_build<'a, 'cfg>(
     // We don't have a great way of handling (2) here right now so this is
     // deferred until after the graph of all unit dependencies has been
     // constructed.
-    let unit = new_unit(
+    let script = new_unit(
         bcx,
         unit.pkg,
         unit.target,
@@ -276,6 +276,12 @@ fn compute_deps_custom_build<'a, 'cfg>(
         Kind::Host,
         CompileMode::Build,
     );
+    // The build script sees the same features as the package it is built
+    // for, only its dependencies use the host features.
+    let unit = Unit {
+        features_for: unit.features_for,
+        ..script
+    };
     // All dependencies of this unit should use profiles for custom
     // builds.
     Ok(vec![(unit, UnitFor::new_build())])
@@ -377,6 +383,7 @@ fn dep_build_script<'a>(
                     profile: bcx.profiles.get_profile_run_custom_build(&unit.profile),
                     kind: unit.kind,
                     mode: CompileMode::RunCustomBuild,
+                    features_for: unit.features_for,
                 },
                 UnitFor::new_build(),
             )
@@ -422,6 +429,7 @@ fn new_unit<'a>(
         profile,
         kind,
         mode,
+        features_for: bcx.features_for(pkg.package_id(), unit_for.is_build()),
     }
 }
 
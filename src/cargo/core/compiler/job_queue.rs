@@ -18,6 +18,7 @@ use super::job::{
 };
 use super::{BuildContext, BuildPlan, CompileMode, Context, Kind, Unit};
 use crate::core::profiles::Profile;
+use crate::core::resolver::FeaturesFor;
 use crate::core::{PackageId, Target, TargetKind};
 use crate::handle_error;
 use crate::util;
@@ -57,6 +58,7 @@ struct Key<'a> {
     profile: Profile,
     kind: Kind,
     mode: CompileMode,
+    features_for: FeaturesFor,
 }
 
 impl<'a> Key<'a> {
@@ -534,6 +536,7 @@ impl<'a> Key<'a> {
             profile: unit.profile,
             kind: unit.kind,
             mode: unit.mode,
+            features_for: unit.features_for,
         }
     }
 
@@ -544,6 +547,7 @@ impl<'a> Key<'a> {
             profile: self.profile,
             kind: self.kind,
             mode: self.mode,
+            features_for: self.features_for,
         };
         let targets = cx.dep_targets(&unit);
         Ok(targets
@@ -35,7 +35,7 @@ pub use self::layout::is_bad_artifact_name;
 use self::output_depinfo::output_depinfo;
 use crate::core::manifest::TargetSourcePath;
 use crate::core::profiles::{Lto, PanicStrategy, Profile};
-use crate::core::{PackageId, Target};
+use crate::core::{Feature, PackageId, Target};
 use crate::util::errors::{CargoResult, CargoResultExt, Internal, ProcessError};
 use crate::util::paths;
 use crate::util::{self, machine_message, process, ProcessBuilder};
@@ -410,8 +410,7 @@ fn link_targets<'a, 'cfg>(
     let profile = unit.profile;
     let unit_mode = unit.mode;
     let features = bcx
-        .resolve
-        .features_sorted(package_id)
+        .unit_features(unit)
         .into_iter()
         .map(|s| s.to_owned())
         .collect();
@@ -631,7 +630,7 @@ fn rustdoc<'a, 'cfg>(cx: &mut Context<'a, 'cfg>, unit: &Unit<'a>) -> CargoResult
 
     rustdoc.arg("-o").arg(doc_dir);
 
-    for feat in bcx.resolve.features_sorted(unit.pkg.package_id()) {
+    for feat in bcx.unit_features(unit) {
         rustdoc.arg("--cfg").arg(&format!("feature=\"{}\"", feat));
     }
 
@@ -862,7 +861,7 @@ fn build_base_args<'a, 'cfg>(
     // We ideally want deterministic invocations of rustc to ensure that
     // rustc-caching strategies like sccache are able to cache more, so sort the
     // feature list here.
-    for feat in bcx.resolve.features_sorted(unit.pkg.package_id()) {
+    for feat in bcx.unit_features(unit) {
         cmd.arg("--cfg").arg(&format!("feature=\"{}\"", feat));
     }
 
@@ -964,15 +963,22 @@ fn build_deps_args<'a, 'cfg>(
         }
     }
 
+    let mut unstable_opts = false;
     for dep in dep_targets {
         if dep.mode.is_run_custom_build() {
             cmd.env("OUT_DIR", &cx.files().build_script_out_dir(&dep));
         }
         if dep.target.linkable() && !dep.mode.is_doc() {
-            link_to(cmd, cx, unit, &dep)?;
+            link_to(cmd, cx, unit, &dep, &mut unstable_opts)?;
         }
     }
 
+    // Private dependencies are passed with an unstable flag, which is only
+    // used by packages opting into the unstable `public` specifier.
+    if unstable_opts {
+        cmd.arg("-Z").arg("unstable-options");
+    }
+
     return Ok(());
 
     fn link_to<'a, 'cfg>(
@@ -980,8 +986,15 @@ fn build_deps_args<'a, 'cfg>(
         cx: &mut Context<'a, 'cfg>,
         current: &Unit<'a>,
         dep: &Unit<'a>,
+        unstable_opts: &mut bool,
     ) -> CargoResult<()> {
         let bcx = cx.bcx;
+        let private = current
+            .pkg
+            .manifest()
+            .features()
+            .is_enabled(Feature::public_dependency())
+            && !bcx.is_public_dependency(current, dep);
         for output in cx.outputs(dep)?.iter() {
             if output.flavor != FileFlavor::Linkable {
                 continue;
@@ -993,7 +1006,12 @@ fn build_deps_args<'a, 'cfg>(
             v.push(cx.files().out_dir(dep));
             v.push(&path::MAIN_SEPARATOR.to_string());
             v.push(&output.path.file_name().unwrap());
-            cmd.arg("--extern").arg(&v);
+            if private {
+                cmd.arg("--extern-private").arg(&v);
+                *unstable_opts = true;
+            } else {
+                cmd.arg("--extern").arg(&v);
+            }
         }
         Ok(())
     }
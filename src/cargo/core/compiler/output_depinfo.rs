@@ -71,6 +71,22 @@ fn add_deps_for_unit<'a, 'b>(
     Ok(())
 }
 
+/// Adds the files `unit` and its path dependencies were built from to
+/// `files`, skipping any unit without dep-info (such as doctests).
+///
+/// The units in `visited` are skipped, so sharing it between the calls for
+/// all the units of a build reads each dep-info file only once.
+pub fn add_input_files<'a, 'b>(
+    files: &mut BTreeSet<PathBuf>,
+    cx: &mut Context<'a, 'b>,
+    unit: &Unit<'a>,
+    visited: &mut HashSet<Unit<'a>>,
+) {
+    if add_deps_for_unit(files, cx, unit, visited).is_err() {
+        debug!("incomplete input files for {:?}", unit);
+    }
+}
+
 pub fn output_depinfo<'a, 'b>(cx: &mut Context<'a, 'b>, unit: &Unit<'a>) -> CargoResult<()> {
     let bcx = cx.bcx;
     let mut deps = BTreeSet::new();
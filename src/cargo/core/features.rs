@@ -199,6 +199,31 @@ features! {
 
         // Declarative build scripts.
         [unstable] metabuild: bool,
+
+        // The "resolver" manifest option, picking the feature resolver.
+        [unstable] resolver: bool,
+
+        // Features of optional dependencies written "dep?/feature".
+        [unstable] weak_dep_features: bool,
+
+        // Optional dependencies enabled with "dep:name", without an implicit
+        // feature.
+        [unstable] dep_features: bool,
+
+        // The "rust-version" manifest option.
+        [unstable] rust_version: bool,
+
+        // The `[workspace.policy]` table, rules for the resolved graph.
+        [unstable] workspace_policy: bool,
+
+        // The "public" specifier of dependencies.
+        [unstable] public_dependency: bool,
+
+        // Features of `[target.'cfg(..)'.features]` tables.
+        [unstable] platform_features: bool,
+
+        // Values inherited from the root manifest of the workspace.
+        [unstable] workspace_inheritance: bool,
     }
 }
 
@@ -323,12 +348,15 @@ pub struct CliUnstable {
     pub no_index_update: bool,
     pub avoid_dev_deps: bool,
     pub minimal_versions: bool,
+    pub direct_minimal_versions: bool,
     pub package_features: bool,
     pub advanced_env: bool,
     pub config_profile: bool,
     pub dual_proc_macros: bool,
     pub mtime_on_use: bool,
     pub install_upgrade: bool,
+    pub msrv_policy: bool,
+    pub lockfile_v2: bool,
 }
 
 impl CliUnstable {
@@ -368,12 +396,15 @@ impl CliUnstable {
             "no-index-update" => self.no_index_update = true,
             "avoid-dev-deps" => self.avoid_dev_deps = true,
             "minimal-versions" => self.minimal_versions = true,
+            "direct-minimal-versions" => self.direct_minimal_versions = true,
             "package-features" => self.package_features = true,
             "advanced-env" => self.advanced_env = true,
             "config-profile" => self.config_profile = true,
             "dual-proc-macros" => self.dual_proc_macros = true,
             "mtime-on-use" => self.mtime_on_use = true,
             "install-upgrade" => self.install_upgrade = true,
+            "msrv-policy" => self.msrv_policy = true,
+            "lockfile-v2" => self.lockfile_v2 = true,
             _ => failure::bail!("unknown `-Z` flag specified: {}", k),
         }
 
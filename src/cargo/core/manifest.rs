@@ -11,6 +11,7 @@ use url::Url;
 
 use crate::core::interning::InternedString;
 use crate::core::profiles::Profiles;
+use crate::core::resolver::ResolveBehavior;
 use crate::core::{Dependency, PackageId, PackageIdSpec, SourceId, Summary};
 use crate::core::{Edition, Feature, Features, WorkspaceConfig};
 use crate::util::errors::*;
@@ -45,6 +46,7 @@ pub struct Manifest {
     im_a_teapot: Option<bool>,
     default_run: Option<String>,
     metabuild: Option<Vec<String>>,
+    resolve_behavior: Option<ResolveBehavior>,
 }
 
 /// When parsing `Cargo.toml`, some warnings should silenced
@@ -67,6 +69,7 @@ pub struct VirtualManifest {
     profiles: Profiles,
     warnings: Warnings,
     features: Features,
+    resolve_behavior: Option<ResolveBehavior>,
 }
 
 /// General metadata about a package which is just blindly uploaded to the
@@ -382,6 +385,7 @@ impl Manifest {
         default_run: Option<String>,
         original: Rc<TomlManifest>,
         metabuild: Option<Vec<String>>,
+        resolve_behavior: Option<ResolveBehavior>,
     ) -> Manifest {
         Manifest {
             summary,
@@ -404,6 +408,7 @@ impl Manifest {
             default_run,
             publish_lockfile,
             metabuild,
+            resolve_behavior,
         }
     }
 
@@ -461,6 +466,9 @@ impl Manifest {
     pub fn links(&self) -> Option<&str> {
         self.links.as_ref().map(|s| &s[..])
     }
+    pub fn rust_version(&self) -> Option<&Version> {
+        self.summary.rust_version()
+    }
 
     pub fn workspace_config(&self) -> &WorkspaceConfig {
         &self.workspace
@@ -527,6 +535,11 @@ impl Manifest {
         self.metabuild.as_ref()
     }
 
+    /// The feature resolver picked with the `resolver` key, if any.
+    pub fn resolve_behavior(&self) -> Option<ResolveBehavior> {
+        self.resolve_behavior
+    }
+
     pub fn metabuild_path(&self, target_dir: Filesystem) -> PathBuf {
         let hash = short_hash(&self.package_id());
         target_dir
@@ -543,6 +556,7 @@ impl VirtualManifest {
         workspace: WorkspaceConfig,
         profiles: Profiles,
         features: Features,
+        resolve_behavior: Option<ResolveBehavior>,
     ) -> VirtualManifest {
         VirtualManifest {
             replace,
@@ -551,6 +565,7 @@ impl VirtualManifest {
             profiles,
             warnings: Warnings::new(),
             features,
+            resolve_behavior,
         }
     }
 
@@ -581,6 +596,11 @@ impl VirtualManifest {
     pub fn features(&self) -> &Features {
         &self.features
     }
+
+    /// The feature resolver picked with the `resolver` key, if any.
+    pub fn resolve_behavior(&self) -> Option<ResolveBehavior> {
+        self.resolve_behavior
+    }
 }
 
 impl Target {
@@ -3,6 +3,7 @@ pub use self::features::{
     enable_nightly_features, maybe_allow_nightly_features, nightly_features_allowed,
 };
 pub use self::features::{CliUnstable, Edition, Feature, Features};
+pub use self::interning::InternedString;
 pub use self::manifest::{EitherManifest, VirtualManifest};
 pub use self::manifest::{LibKind, Manifest, Target, TargetKind};
 pub use self::package::{Package, PackageSet};
@@ -12,8 +13,10 @@ pub use self::registry::Registry;
 pub use self::resolver::Resolve;
 pub use self::shell::{Shell, Verbosity};
 pub use self::source::{GitReference, Source, SourceId, SourceMap};
-pub use self::summary::{FeatureMap, FeatureValue, Summary};
-pub use self::workspace::{Members, Workspace, WorkspaceConfig, WorkspaceRootConfig};
+pub use self::summary::{FeatureMap, FeatureValue, PlatformFeatures, Summary};
+pub use self::workspace::{
+    Members, PolicyLevel, Workspace, WorkspaceConfig, WorkspacePolicy, WorkspaceRootConfig,
+};
 
 pub mod compiler;
 pub mod dependency;
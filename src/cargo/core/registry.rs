@@ -28,6 +28,12 @@ pub trait Registry {
         Ok(ret)
     }
 
+    /// Attempt to find the yanked packages that match a dependency request,
+    /// used to explain why resolution failed.
+    fn query_yanked(&mut self, _dep: &Dependency, _f: &mut dyn FnMut(Summary)) -> CargoResult<()> {
+        Ok(())
+    }
+
     fn describe_source(&self, source: SourceId) -> String;
     fn is_replaced(&self, source: SourceId) -> bool;
 }
@@ -176,6 +182,33 @@ impl<'cfg> PackageRegistry<'cfg> {
         self.yanked_whitelist.extend(pkgs);
     }
 
+    /// Returns whether the package `id` has been yanked from its source,
+    /// which is only known once the source has been loaded.
+    pub fn is_yanked(&mut self, id: PackageId) -> CargoResult<bool> {
+        match self.sources.get_mut(id.source_id()) {
+            Some(source) => source.is_yanked(id),
+            None => Ok(false),
+        }
+    }
+
+    /// Like `query_vec`, but only queries the source of `dep` if it has
+    /// already been loaded, so that it's never updated.
+    pub fn query_loaded(&mut self, dep: &Dependency) -> CargoResult<Vec<Summary>> {
+        match self.sources.get_mut(dep.source_id()) {
+            Some(source) => source.query_vec(dep),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Returns the version of the index of the loaded source `id`, see
+    /// `Source::index_version`.
+    pub fn index_version(&mut self, id: SourceId) -> CargoResult<Option<String>> {
+        match self.sources.get_mut(id) {
+            Some(source) => source.index_version(),
+            None => Ok(None),
+        }
+    }
+
     pub fn register_lock(&mut self, id: PackageId, deps: Vec<PackageId>) {
         trace!("register_lock: {}", id);
         for dep in deps.iter() {
@@ -556,6 +589,13 @@ impl<'cfg> Registry for PackageRegistry<'cfg> {
         Ok(())
     }
 
+    fn query_yanked(&mut self, dep: &Dependency, f: &mut dyn FnMut(Summary)) -> CargoResult<()> {
+        match self.sources.get_mut(dep.source_id()) {
+            Some(source) => source.query_yanked(dep, f),
+            None => Ok(()),
+        }
+    }
+
     fn describe_source(&self, id: SourceId) -> String {
         match self.sources.get(id) {
             Some(src) => src.describe(),
@@ -181,7 +181,7 @@ impl Context {
         let mut deps = deps
             .into_iter()
             .map(|(dep, features)| {
-                let candidates = registry.query(&dep)?;
+                let candidates = registry.query(&dep, candidate.package_id())?;
                 Ok((dep, candidates, Rc::new(features)))
             })
             .collect::<CargoResult<Vec<DepInfo>>>()?;
@@ -244,7 +244,14 @@ impl Context {
         let deps = s.dependencies();
         let deps = deps.iter().filter(|d| d.is_transitive() || dev_deps);
 
-        let reqs = build_requirements(s, method)?;
+        let mut reqs = build_requirements(s, method)?;
+        // Whether a weak dependency feature applies depends on the features
+        // enabled by every activation of the package, not just this one.
+        if s.has_weak_dep_features() {
+            if let Some(previous) = self.resolve_features.get(&s.package_id()) {
+                reqs.require_previous(previous)?;
+            }
+        }
         let mut ret = Vec::new();
         let mut used_features = HashSet::new();
         let default_dep = (false, Vec::new());
@@ -278,6 +285,12 @@ impl Context {
             }
             let mut base = base.1.clone();
             base.extend(dep.features().iter());
+            base.extend(
+                reqs.weak
+                    .iter()
+                    .filter(|&&(name, _)| name == dep.name_in_toml())
+                    .map(|&(_, feature)| feature),
+            );
             for feature in base.iter() {
                 if feature.contains('/') {
                     return Err(failure::format_err!(
@@ -376,7 +389,11 @@ fn build_requirements<'a, 'b: 'a>(
                 reqs.require_feature(*key)?;
             }
             for dep in s.dependencies().iter().filter(|d| d.is_optional()) {
-                reqs.require_dependency(dep.name_in_toml());
+                if s.is_explicit_dep(&dep.name_in_toml()) {
+                    reqs.require_explicit_dependency(dep.name_in_toml());
+                } else {
+                    reqs.require_dependency(dep.name_in_toml());
+                }
             }
         }
         Method::Required {
@@ -385,7 +402,21 @@ fn build_requirements<'a, 'b: 'a>(
             ..
         } => {
             for &f in requested.iter() {
-                reqs.require_value(&FeatureValue::new(f, s))?;
+                let fv = FeatureValue::new(f, s);
+                if let FeatureValue::Crate(dep) = fv {
+                    if s.is_explicit_dep(&dep) {
+                        bail!(
+                            "Package `{}` does not have feature `{}`. It has an optional \
+                             dependency with that name, but that dependency is enabled with \
+                             `dep:{}` in its features, so it has no implicit feature of the \
+                             same name.",
+                            s.package_id(),
+                            dep,
+                            dep
+                        );
+                    }
+                }
+                reqs.require_value(&fv)?;
             }
         }
     }
@@ -420,6 +451,9 @@ struct Requirements<'a> {
     // features were enabled.
     used: HashSet<InternedString>,
     visited: HashSet<InternedString>,
+    // The features of optional dependencies requested with `dep?/feature`,
+    // which only apply if the dependency is enabled anyway.
+    weak: Vec<(InternedString, InternedString)>,
 }
 
 impl<'r> Requirements<'r> {
@@ -429,16 +463,31 @@ impl<'r> Requirements<'r> {
             deps: HashMap::new(),
             used: HashSet::new(),
             visited: HashSet::new(),
+            weak: Vec::new(),
         }
     }
 
-    fn require_crate_feature(&mut self, package: InternedString, feat: InternedString) {
-        self.used.insert(package);
+    fn require_crate_feature(
+        &mut self,
+        package: InternedString,
+        feat: InternedString,
+    ) -> CargoResult<()> {
+        if self.summary.is_explicit_dep(&package) {
+            // There is no implicit feature for the dependency, but a feature
+            // of the same name is enabled along with it.
+            self.require_explicit_dependency(package);
+            if self.summary.features().contains_key(&package) {
+                self.require_feature(package)?;
+            }
+        } else {
+            self.used.insert(package);
+        }
         self.deps
             .entry(package)
             .or_insert((false, Vec::new()))
             .1
             .push(feat);
+        Ok(())
     }
 
     fn seen(&mut self, feat: InternedString) -> bool {
@@ -457,6 +506,35 @@ impl<'r> Requirements<'r> {
         self.deps.entry(pkg).or_insert((false, Vec::new())).0 = true;
     }
 
+    /// Enables an optional dependency written `dep:pkg`. It is recorded as
+    /// `dep:pkg` in the used features, as it isn't a feature of its own.
+    fn require_explicit_dependency(&mut self, pkg: InternedString) {
+        if self.seen(InternedString::new(&format!("dep:{}", pkg))) {
+            return;
+        }
+        self.deps.entry(pkg).or_insert((false, Vec::new())).0 = true;
+    }
+
+    /// Requires again the features used by earlier activations of the
+    /// package.
+    fn require_previous(&mut self, used: &HashSet<InternedString>) -> CargoResult<()> {
+        for &name in used {
+            if name.starts_with("dep:") {
+                self.require_explicit_dependency(InternedString::new(&name[4..]));
+            } else if self.summary.features().contains_key(&name) {
+                self.require_feature(name)?;
+            } else if self
+                .summary
+                .dependencies()
+                .iter()
+                .any(|d| d.is_optional() && d.name_in_toml() == name)
+            {
+                self.require_dependency(name);
+            }
+        }
+        Ok(())
+    }
+
     fn require_feature(&mut self, feat: InternedString) -> CargoResult<()> {
         if feat.is_empty() || self.seen(feat) {
             return Ok(());
@@ -483,9 +561,11 @@ impl<'r> Requirements<'r> {
         match fv {
             FeatureValue::Feature(feat) => self.require_feature(*feat)?,
             FeatureValue::Crate(dep) => self.require_dependency(*dep),
+            FeatureValue::Dep(dep) => self.require_explicit_dependency(*dep),
             FeatureValue::CrateFeature(dep, dep_feat) => {
-                self.require_crate_feature(*dep, *dep_feat)
+                self.require_crate_feature(*dep, *dep_feat)?
             }
+            FeatureValue::WeakCrateFeature(dep, dep_feat) => self.weak.push((*dep, *dep_feat)),
         };
         Ok(())
     }
@@ -1,20 +1,27 @@
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::cmp;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt;
+use std::mem;
 use std::str::FromStr;
 
 use log::debug;
+use semver::Version;
 use serde::de;
 use serde::ser;
 use serde::{Deserialize, Serialize};
 
+use crate::core::interning::InternedString;
 use crate::core::{Dependency, Package, PackageId, SourceId, Workspace};
 use crate::util::errors::{CargoResult, CargoResultExt};
 use crate::util::{internal, Graph};
 
-use super::Resolve;
+use super::{Resolve, ResolveVersion};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct EncodableResolve {
+    /// The format of the lock file, see `ResolveVersion`. Missing in the
+    /// original format.
+    version: Option<u32>,
     package: Option<Vec<EncodableDependency>>,
     /// `root` is optional to allow backward compatibility.
     root: Option<EncodableDependency>,
@@ -51,7 +58,7 @@ impl EncodableResolve {
             for pkg in packages.iter() {
                 let enc_id = EncodablePackageId {
                     name: pkg.name.clone(),
-                    version: pkg.version.clone(),
+                    version: Some(pkg.version.clone()),
                     source: pkg.source,
                 };
 
@@ -73,8 +80,53 @@ impl EncodableResolve {
             live_pkgs
         };
 
+        // Dependencies in a V2 lock file are written without their version
+        // and source when they're unambiguous, so they're looked up by name,
+        // then by version and source if there's more than one candidate.
+        let mut by_name = HashMap::new();
+        for &(id, _) in live_pkgs.values() {
+            by_name
+                .entry(id.name().to_string())
+                .or_insert_with(Vec::new)
+                .push(id);
+        }
         let lookup_id = |enc_id: &EncodablePackageId| -> Option<PackageId> {
-            live_pkgs.get(enc_id).map(|&(id, _)| id)
+            let candidates = by_name
+                .get(&enc_id.name)?
+                .iter()
+                .filter(|id| match enc_id.version {
+                    Some(ref version) => id.version().to_string() == *version,
+                    None => true,
+                })
+                .filter(|id| match enc_id.source {
+                    Some(source) => encode_source(id.source_id()) == Some(source),
+                    None => true,
+                })
+                .collect::<Vec<_>>();
+            if candidates.len() == 1 {
+                return Some(*candidates[0]);
+            }
+            // Path dependencies have no source in the lock file, so they are
+            // what a dependency without a source refers to if it's ambiguous.
+            if enc_id.source.is_some() {
+                return None;
+            }
+            let mut paths = candidates.iter().filter(|id| id.source_id().is_path());
+            match (paths.next(), paths.next()) {
+                (Some(&&id), None) => Some(id),
+                _ => None,
+            }
+        };
+
+        let version = match self.version {
+            None => ResolveVersion::V1,
+            Some(2) => ResolveVersion::V2,
+            Some(version) => failure::bail!(
+                "lock file version `{}` was found, but this version of Cargo \
+                 does not understand this lock file, perhaps Cargo needs \
+                 to be updated?",
+                version
+            ),
         };
 
         let g = {
@@ -155,6 +207,12 @@ impl EncodableResolve {
             metadata.remove(&k);
         }
 
+        for &(id, pkg) in live_pkgs.values() {
+            if let Some(ref checksum) = pkg.checksum {
+                checksums.insert(id, Some(checksum.clone()));
+            }
+        }
+
         let mut unused_patches = Vec::new();
         for pkg in self.patch.unused {
             let id = match pkg.source.as_ref().or_else(|| path_deps.get(&pkg.name)) {
@@ -171,8 +229,200 @@ impl EncodableResolve {
             checksums,
             metadata,
             unused_patches,
+            version,
         ))
     }
+
+    /// Merges the lock files `ours` and `theirs`, which both derive from
+    /// `base`, the way a three-way merge of their package lists would:
+    /// packages added on either side are kept, and packages removed on either
+    /// side are dropped. If both sides upgraded a package, both versions are
+    /// kept and a later resolution picks the one to use. If both sides
+    /// changed the dependencies of the same package, the dependencies added
+    /// on either side are kept and the ones removed on either side are
+    /// dropped, so the later resolution sees the changes of both.
+    ///
+    /// This fails if both sides locked a git dependency to different
+    /// revisions, or recorded different checksums or replacements for a
+    /// package, since there is no way to tell which one is right.
+    pub fn merge(
+        mut base: EncodableResolve,
+        mut ours: EncodableResolve,
+        mut theirs: EncodableResolve,
+    ) -> CargoResult<EncodableResolve> {
+        // The precise part of the source (the revision of git sources) is not
+        // taken into account when comparing `SourceId`s, so it's compared
+        // separately below.
+        type Key = (String, String, Option<SourceId>);
+        fn packages(resolve: EncodableResolve) -> BTreeMap<Key, EncodableDependency> {
+            resolve
+                .root
+                .into_iter()
+                .chain(resolve.package.unwrap_or_default())
+                .map(|pkg| ((pkg.name.clone(), pkg.version.clone(), pkg.source), pkg))
+                .collect()
+        }
+        fn precise(pkg: &EncodableDependency) -> Option<&'static str> {
+            pkg.source.and_then(|s| s.precise())
+        }
+
+        // A package list in the newer format on either side is kept in it.
+        let version = cmp::max(ours.version, theirs.version);
+        let base_metadata = base.metadata.take().unwrap_or_default();
+        let our_metadata = ours.metadata.take().unwrap_or_default();
+        let their_metadata = theirs.metadata.take().unwrap_or_default();
+        let mut unused_patches = mem::replace(&mut ours.patch.unused, Vec::new());
+        for pkg in mem::replace(&mut theirs.patch.unused, Vec::new()) {
+            if !unused_patches.contains(&pkg) {
+                unused_patches.push(pkg);
+            }
+        }
+        let base = packages(base);
+        let mut ours = packages(ours);
+        let mut theirs = packages(theirs);
+
+        let keys = ours
+            .keys()
+            .chain(theirs.keys())
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        let mut merged = Vec::new();
+        for key in keys {
+            let in_base = base.get(&key);
+            let pkg = match (ours.remove(&key), theirs.remove(&key)) {
+                (Some(our_pkg), Some(their_pkg)) => {
+                    let our_changed = in_base.map(|b| b != &our_pkg).unwrap_or(true);
+                    let their_changed = in_base.map(|b| b != &their_pkg).unwrap_or(true);
+                    if precise(&our_pkg) != precise(&their_pkg)
+                        && in_base.map(precise) != Some(precise(&our_pkg))
+                        && in_base.map(precise) != Some(precise(&their_pkg))
+                    {
+                        failure::bail!(
+                            "`{} v{}` is locked to revision `{}` on one side and to \
+                             revision `{}` on the other side",
+                            key.0,
+                            key.1,
+                            precise(&our_pkg).unwrap_or("<none>"),
+                            precise(&their_pkg).unwrap_or("<none>"),
+                        );
+                    }
+                    // Take the side which changed the package, if any.
+                    if our_changed && their_changed && our_pkg != their_pkg {
+                        merge_package(&key.0, &key.1, in_base, our_pkg, their_pkg)?
+                    } else if our_changed {
+                        our_pkg
+                    } else {
+                        their_pkg
+                    }
+                }
+                // Only one side has the package, so it was either added on
+                // that side or removed on the other one.
+                (Some(pkg), None) | (None, Some(pkg)) => {
+                    if in_base.is_some() {
+                        continue;
+                    }
+                    pkg
+                }
+                (None, None) => unreachable!(),
+            };
+            merged.push(pkg);
+        }
+
+        // The metadata holds the checksums of packages, which are merged the
+        // same way.
+        let mut metadata = Metadata::new();
+        let keys = base_metadata
+            .keys()
+            .chain(our_metadata.keys())
+            .chain(their_metadata.keys())
+            .collect::<BTreeSet<_>>();
+        for key in keys {
+            let base_value = base_metadata.get(key);
+            let our_value = our_metadata.get(key);
+            let their_value = their_metadata.get(key);
+            let value = if our_value == their_value || their_value == base_value {
+                our_value
+            } else if our_value == base_value {
+                their_value
+            } else {
+                failure::bail!("conflicting values for `{}` in the lock file metadata", key);
+            };
+            if let Some(value) = value {
+                metadata.insert(key.clone(), value.clone());
+            }
+        }
+
+        Ok(EncodableResolve {
+            version,
+            package: Some(merged),
+            root: None,
+            metadata: Some(metadata),
+            patch: Patch {
+                unused: unused_patches,
+            },
+        })
+    }
+}
+
+/// Merges `ours` and `theirs`, two versions of the same package in a lock
+/// file which both changed it compared to `base`, for `EncodableResolve::merge`.
+fn merge_package(
+    name: &str,
+    version: &str,
+    base: Option<&EncodableDependency>,
+    ours: EncodableDependency,
+    theirs: EncodableDependency,
+) -> CargoResult<EncodableDependency> {
+    if ours.checksum != theirs.checksum {
+        failure::bail!(
+            "`{} v{}` has a different checksum on each side",
+            name,
+            version
+        );
+    }
+    if ours.replace != theirs.replace {
+        failure::bail!(
+            "`{} v{}` is replaced by a different package on each side",
+            name,
+            version
+        );
+    }
+
+    // A revision changed on one side only is taken from that side, the
+    // revisions conflicting on both sides were reported by the caller.
+    let base_precise = base.and_then(|b| b.source).and_then(|s| s.precise());
+    let source = match ours.source {
+        Some(s) if s.precise() != base_precise => ours.source,
+        _ => theirs.source,
+    };
+
+    let base_deps = base
+        .and_then(|b| b.dependencies.as_ref())
+        .map(|deps| deps.iter().collect::<BTreeSet<_>>())
+        .unwrap_or_default();
+    let our_deps = ours.dependencies.unwrap_or_default();
+    let their_deps = theirs.dependencies.unwrap_or_default();
+    let dependencies = our_deps
+        .iter()
+        .chain(their_deps.iter())
+        .filter(|&dep| {
+            !base_deps.contains(&dep) || (our_deps.contains(dep) && their_deps.contains(dep))
+        })
+        .cloned()
+        .collect::<BTreeSet<_>>();
+
+    Ok(EncodableDependency {
+        name: ours.name,
+        version: ours.version,
+        source,
+        checksum: ours.checksum,
+        dependencies: if dependencies.is_empty() {
+            None
+        } else {
+            Some(dependencies.into_iter().collect())
+        },
+        replace: ours.replace,
+    })
 }
 
 fn build_path_deps(ws: &Workspace<'_>) -> HashMap<String, SourceId> {
@@ -254,6 +504,7 @@ pub struct EncodableDependency {
     name: String,
     version: String,
     source: Option<SourceId>,
+    checksum: Option<String>,
     dependencies: Option<Vec<EncodablePackageId>>,
     replace: Option<EncodablePackageId>,
 }
@@ -261,13 +512,16 @@ pub struct EncodableDependency {
 #[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Hash, Clone)]
 pub struct EncodablePackageId {
     name: String,
-    version: String,
+    version: Option<String>,
     source: Option<SourceId>,
 }
 
 impl fmt::Display for EncodablePackageId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} {}", self.name, self.version)?;
+        write!(f, "{}", self.name)?;
+        if let Some(ref version) = self.version {
+            write!(f, " {}", version)?;
+        }
         if let Some(ref s) = self.source {
             write!(f, " ({})", s.to_url())?;
         }
@@ -281,9 +535,7 @@ impl FromStr for EncodablePackageId {
     fn from_str(s: &str) -> CargoResult<EncodablePackageId> {
         let mut s = s.splitn(3, ' ');
         let name = s.next().unwrap();
-        let version = s
-            .next()
-            .ok_or_else(|| internal("invalid serialized PackageId"))?;
+        let version = s.next();
         let source_id = match s.next() {
             Some(s) => {
                 if s.starts_with('(') && s.ends_with(')') {
@@ -297,7 +549,7 @@ impl FromStr for EncodablePackageId {
 
         Ok(EncodablePackageId {
             name: name.to_string(),
-            version: version.to_string(),
+            version: version.map(|v| v.to_string()),
             source: source_id,
         })
     }
@@ -338,20 +590,24 @@ impl<'a, 'cfg> ser::Serialize for WorkspaceResolve<'a, 'cfg> {
         let mut ids: Vec<_> = self.resolve.iter().collect();
         ids.sort();
 
+        let state = EncodeState::new(self.resolve);
+
         let encodable = ids
             .iter()
-            .map(|&id| encodable_resolve_node(id, self.resolve))
+            .map(|&id| encodable_resolve_node(id, self.resolve, &state))
             .collect::<Vec<_>>();
 
         let mut metadata = self.resolve.metadata().clone();
 
-        for &id in ids.iter().filter(|id| !id.source_id().is_path()) {
-            let checksum = match self.resolve.checksums()[&id] {
-                Some(ref s) => &s[..],
-                None => "<none>",
-            };
-            let id = encodable_package_id(id);
-            metadata.insert(format!("checksum {}", id.to_string()), checksum.to_string());
+        if self.resolve.version() == ResolveVersion::V1 {
+            for &id in ids.iter().filter(|id| !id.source_id().is_path()) {
+                let checksum = match self.resolve.checksums()[&id] {
+                    Some(ref s) => &s[..],
+                    None => "<none>",
+                };
+                let id = encodable_package_id(id);
+                metadata.insert(format!("checksum {}", id.to_string()), checksum.to_string());
+            }
         }
 
         let metadata = if metadata.is_empty() {
@@ -369,12 +625,19 @@ impl<'a, 'cfg> ser::Serialize for WorkspaceResolve<'a, 'cfg> {
                     name: id.name().to_string(),
                     version: id.version().to_string(),
                     source: encode_source(id.source_id()),
+                    checksum: None,
                     dependencies: None,
                     replace: None,
                 })
                 .collect(),
         };
+        let version = match self.resolve.version() {
+            ResolveVersion::V1 => None,
+            ResolveVersion::V2 => Some(2),
+        };
+
         EncodableResolve {
+            version,
             package: Some(encodable),
             root: None,
             metadata,
@@ -384,32 +647,83 @@ impl<'a, 'cfg> ser::Serialize for WorkspaceResolve<'a, 'cfg> {
     }
 }
 
-fn encodable_resolve_node(id: PackageId, resolve: &Resolve) -> EncodableDependency {
+/// How many packages of each name and version there are in a `Resolve`,
+/// which tells how much of a dependency is written in a V2 lock file.
+struct EncodeState {
+    counts: Option<HashMap<InternedString, HashMap<&'static Version, usize>>>,
+}
+
+impl EncodeState {
+    fn new(resolve: &Resolve) -> EncodeState {
+        let counts = if resolve.version() == ResolveVersion::V2 {
+            let mut counts = HashMap::new();
+            for id in resolve.iter() {
+                *counts
+                    .entry(id.name())
+                    .or_insert_with(HashMap::new)
+                    .entry(id.version())
+                    .or_insert(0) += 1;
+            }
+            Some(counts)
+        } else {
+            None
+        };
+        EncodeState { counts }
+    }
+}
+
+fn encodable_resolve_node(
+    id: PackageId,
+    resolve: &Resolve,
+    state: &EncodeState,
+) -> EncodableDependency {
     let (replace, deps) = match resolve.replacement(id) {
-        Some(id) => (Some(encodable_package_id(id)), None),
+        Some(id) => (Some(encodable_dependency_id(id, state)), None),
         None => {
             let mut deps = resolve
                 .deps_not_replaced(id)
-                .map(encodable_package_id)
+                .map(|id| encodable_dependency_id(id, state))
                 .collect::<Vec<_>>();
             deps.sort();
             (None, Some(deps))
         }
     };
 
+    let checksum = match resolve.version() {
+        ResolveVersion::V1 => None,
+        ResolveVersion::V2 => resolve.checksums().get(&id).and_then(|s| s.clone()),
+    };
+
     EncodableDependency {
         name: id.name().to_string(),
         version: id.version().to_string(),
         source: encode_source(id.source_id()),
+        checksum,
         dependencies: deps,
         replace,
     }
 }
 
+/// The id of a dependency of a package, which leaves out the version and
+/// source in a V2 lock file if they aren't needed to tell packages apart.
+fn encodable_dependency_id(id: PackageId, state: &EncodeState) -> EncodablePackageId {
+    let mut enc_id = encodable_package_id(id);
+    if let Some(ref counts) = state.counts {
+        let versions = &counts[&id.name()];
+        if versions.values().sum::<usize>() == 1 {
+            enc_id.version = None;
+            enc_id.source = None;
+        } else if versions[id.version()] == 1 {
+            enc_id.source = None;
+        }
+    }
+    enc_id
+}
+
 pub fn encodable_package_id(id: PackageId) -> EncodablePackageId {
     EncodablePackageId {
         name: id.name().to_string(),
-        version: id.version().to_string(),
+        version: Some(id.version().to_string()),
         source: encode_source(id.source_id()).map(|s| s.with_precise(None)),
     }
 }
@@ -161,6 +161,68 @@ pub(super) fn activation_error(
         msg.push_str(&*dep.package_name());
         msg.push_str("` which could resolve this conflict");
 
+        let requirements = conflicting_requirements(cx, parent, dep, &other_errors);
+        if requirements.len() > 1 {
+            msg.push_str("\n\nthe conflicting requirements on `");
+            msg.push_str(&*dep.package_name());
+            msg.push_str("` are:");
+            for &(id, ref req) in requirements.iter() {
+                msg.push_str("\n  `");
+                msg.push_str(&describe_requirement(req));
+                msg.push_str("` required by ");
+                msg.push_str(&describe_path(&cx.parents.path_to_bottom(&id)));
+            }
+        }
+
+        // Failing to look up the other versions shouldn't hide the actual
+        // resolution error, so errors here are ignored.
+        let mut all_dep = dep.clone();
+        all_dep.set_version_req(semver::VersionReq::any());
+        let all_versions = registry.query_vec(&all_dep, false).unwrap_or_default();
+        msg.push_str(&describe_skipped_versions(registry, dep, &all_versions));
+
+        if requirements.len() > 1 {
+            msg.push_str(&suggest_fixes(
+                cx,
+                registry,
+                dep,
+                &requirements,
+                &all_versions,
+            ));
+        }
+
+        let mut links: Vec<_> = links_errors
+            .iter()
+            .filter_map(|&(_, r)| match *r {
+                ConflictReason::Links(ref link) => Some(link),
+                _ => None,
+            })
+            .collect();
+        links.sort_unstable();
+        links.dedup();
+        for link in links {
+            msg.push_str(
+                "\nhelp: only one package in the dependency graph may specify `links = \"",
+            );
+            msg.push_str(link);
+            msg.push_str(
+                "\"`, so that the native library is only linked once, \
+                 adjust the dependencies above so that a single package links to it",
+            );
+        }
+
+        if other_errors
+            .iter()
+            .any(|&(_, r)| *r == ConflictReason::PublicDependency)
+        {
+            msg.push_str("\nhelp: a package may only see one version of `");
+            msg.push_str(&*dep.package_name());
+            msg.push_str(
+                "` through its dependencies and their public dependencies, \
+                 make some of the dependencies above private or use a single version",
+            );
+        }
+
         return to_resolve_err(failure::format_err!("{}", msg));
     }
 
@@ -274,6 +336,8 @@ pub(super) fn activation_error(
         msg
     };
 
+    msg.push_str(&describe_skipped_versions(registry, dep, &candidates));
+
     if let Some(config) = config {
         if config.cli_unstable().offline {
             msg.push_str(
@@ -289,7 +353,7 @@ pub(super) fn activation_error(
 }
 
 /// Returns String representation of dependency chain for a particular `pkgid`.
-pub(super) fn describe_path(path: &[&PackageId]) -> String {
+pub fn describe_path(path: &[&PackageId]) -> String {
     use std::fmt::Write;
     let mut dep_path_desc = format!("package `{}`", path[0]);
     for dep in path[1..].iter() {
@@ -297,3 +361,230 @@ pub(super) fn describe_path(path: &[&PackageId]) -> String {
     }
     dep_path_desc
 }
+
+/// Returns every requirement on the package `dep` refers to which took part
+/// in a semver conflict, along with the package declaring it. The first entry
+/// is always `dep` itself, as declared by `parent`.
+fn conflicting_requirements(
+    cx: &Context,
+    parent: &Summary,
+    dep: &Dependency,
+    conflicts: &[(&PackageId, &ConflictReason)],
+) -> Vec<(PackageId, Dependency)> {
+    let mut requirements = vec![(parent.package_id(), dep.clone())];
+    for &(p, _) in conflicts.iter() {
+        if p.name() != dep.package_name() {
+            continue;
+        }
+        for &(dependent, ref deps) in cx.parents.edges(p) {
+            for d in deps.iter().filter(|d| d.package_name() == p.name()) {
+                let seen = requirements
+                    .iter()
+                    .any(|&(id, ref r)| id == dependent && r.version_req() == d.version_req());
+                if !seen {
+                    requirements.push((dependent, d.clone()));
+                }
+            }
+        }
+    }
+    requirements
+}
+
+/// Returns a requirement as it would be written in a manifest, like
+/// `foo = "^1.0"`.
+fn describe_requirement(dep: &Dependency) -> String {
+    format!("{} = \"{}\"", dep.package_name(), dep.version_req())
+}
+
+/// Returns notes about versions matching `dep` that were never considered
+/// because they have been yanked, or because they are pre-releases and the
+/// requirement doesn't ask for one. `all_versions` are the summaries of every
+/// version of the package.
+fn describe_skipped_versions(
+    registry: &mut dyn Registry,
+    dep: &Dependency,
+    all_versions: &[Summary],
+) -> String {
+    use std::fmt::Write;
+    let mut msg = String::new();
+    let req = describe_requirement(dep);
+
+    let mut yanked = Vec::new();
+    // This is purely informational, so a failure to query is ignored.
+    let _ = registry.query_yanked(dep, &mut |s| yanked.push(s.version().clone()));
+    yanked.sort_unstable_by(|a, b| b.cmp(a));
+    yanked.dedup();
+    for version in yanked.iter() {
+        write!(
+            msg,
+            "\nnote: version {} of `{}` matches `{}` but was skipped because it has been yanked",
+            version,
+            dep.package_name(),
+            req
+        )
+        .unwrap();
+    }
+
+    let mut pre_releases = all_versions
+        .iter()
+        .map(|s| s.version())
+        .filter(|v| v.is_prerelease() && !dep.version_req().matches(v))
+        .filter(|v| {
+            let mut release = (*v).clone();
+            release.pre.clear();
+            dep.version_req().matches(&release)
+        })
+        .collect::<Vec<_>>();
+    pre_releases.sort_unstable_by(|a, b| b.cmp(a));
+    pre_releases.dedup();
+    if let Some(newest) = pre_releases.first() {
+        write!(
+            msg,
+            "\nnote: the pre-release versions {} of `{}` were skipped, \
+             pre-releases are only selected by requirements naming one\n\
+             help: to use {}, change the requirement to `{} = \"{}\"`",
+            pre_releases
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            dep.package_name(),
+            newest,
+            dep.package_name(),
+            newest
+        )
+        .unwrap();
+    }
+    msg
+}
+
+/// Suggests how the conflict between `requirements`, as returned by
+/// `conflicting_requirements`, could be resolved. `all_versions` are the
+/// summaries of every version of the package.
+///
+/// For a requirement declared by a published package, the registry is
+/// searched for a newer version of that package whose requirement is
+/// compatible with the other ones. Patching the package is only suggested
+/// when there's no such version.
+fn suggest_fixes(
+    cx: &Context,
+    registry: &mut dyn Registry,
+    dep: &Dependency,
+    requirements: &[(PackageId, Dependency)],
+    all_versions: &[Summary],
+) -> String {
+    use std::fmt::Write;
+    let mut msg = String::new();
+    let mut patches = Vec::new();
+    for (i, &(id, ref req)) in requirements.iter().enumerate() {
+        // The versions the other requirements allow, newest first.
+        let mut compatible = all_versions
+            .iter()
+            .map(|s| s.version())
+            .filter(|v| {
+                requirements
+                    .iter()
+                    .enumerate()
+                    .all(|(j, &(_, ref r))| i == j || r.version_req().matches(v))
+            })
+            .collect::<Vec<_>>();
+        compatible.sort_unstable_by(|a, b| b.cmp(a));
+        compatible.dedup();
+        let newest = match compatible.first() {
+            Some(newest) => *newest,
+            // Changing this requirement alone can't resolve the conflict.
+            None => continue,
+        };
+
+        if id.source_id().is_path() {
+            write!(
+                msg,
+                "\nhelp: change the requirement `{}` in the manifest of `{}` to \
+                 `{} = \"{}\"`, which is compatible with the other requirements",
+                describe_requirement(req),
+                id,
+                dep.package_name(),
+                newest
+            )
+            .unwrap();
+            continue;
+        }
+
+        match find_upgrade(cx, registry, id, dep, &compatible) {
+            Some(version) => write!(
+                msg,
+                "\nhelp: upgrade `{}` to v{}, whose requirement on `{}` is compatible \
+                 with the other requirements, with `cargo update -p {}:{} --precise {}`",
+                id,
+                version,
+                dep.package_name(),
+                id.name(),
+                id.version(),
+                version
+            )
+            .unwrap(),
+            None => patches.push((id, newest)),
+        }
+    }
+    for (id, newest) in patches {
+        let table = if id.source_id().is_default_registry() {
+            "crates-io".to_string()
+        } else {
+            format!("\"{}\"", id.source_id().url())
+        };
+        write!(
+            msg,
+            "\nhelp: no version of `{name}` allowed by its dependents has a requirement \
+             on `{dep}` compatible with the other requirements. A copy of `{id}` whose \
+             requirement is changed to `{dep} = \"{version}\"` can be used instead by \
+             adding it to the `[patch]` section of the workspace root's `Cargo.toml`, \
+             e.g.:\n\
+             \n    [patch.{table}]\
+             \n    {name} = {{ path = \"../{name}\" }}",
+            name = id.name(),
+            dep = dep.package_name(),
+            id = id,
+            version = newest,
+            table = table
+        )
+        .unwrap();
+    }
+    msg
+}
+
+/// Returns the oldest version of the published package `id` which is newer
+/// than it, is allowed by the packages depending on it, and either requires
+/// one of the `compatible` versions of `dep` or doesn't depend on it at all.
+fn find_upgrade(
+    cx: &Context,
+    registry: &mut dyn Registry,
+    id: PackageId,
+    dep: &Dependency,
+    compatible: &[&semver::Version],
+) -> Option<semver::Version> {
+    let query = Dependency::parse_no_deprecated(&id.name(), None, id.source_id()).ok()?;
+    // This is purely informational, so a failure to query is ignored.
+    let summaries = registry.query_vec(&query, false).ok()?;
+    let dependents = cx
+        .parents
+        .edges(&id)
+        .flat_map(|&(_, ref deps)| deps.iter())
+        .filter(|d| d.package_name() == id.name())
+        .collect::<Vec<_>>();
+    summaries
+        .iter()
+        .filter(|s| s.version() > id.version())
+        .filter(|s| {
+            dependents
+                .iter()
+                .all(|d| d.version_req().matches(s.version()))
+        })
+        .filter(|s| {
+            s.dependencies()
+                .iter()
+                .filter(|d| d.package_name() == dep.package_name())
+                .all(|d| compatible.iter().any(|v| d.version_req().matches(v)))
+        })
+        .map(|s| s.version().clone())
+        .min()
+}
@@ -0,0 +1,452 @@
+//! Feature resolution for workspaces with `resolver = "2"`.
+//!
+//! `Resolve` unifies the features of a package across everything which
+//! depends on it. The feature resolver here instead computes the features of
+//! every package separately for the packages built for the host (build
+//! scripts, proc macros and everything they depend on) and for the packages
+//! built for the target. Dependencies which aren't used on the platform they
+//! are built for don't enable any features, the `[target.'cfg(..)'.features]`
+//! of other platforms don't apply, and dev-dependencies only enable features
+//! when a target which uses them is built.
+//!
+//! This runs after `Resolve` was computed, which already checked that every
+//! requested feature exists, so it doesn't report any errors of its own.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::core::dependency::Platform;
+use crate::core::interning::InternedString;
+use crate::core::{Dependency, FeatureValue, PackageId, PackageSet, Workspace};
+use crate::util::errors::CargoResult;
+
+use super::{Method, Resolve};
+
+/// Which feature resolver a workspace uses, picked with the `resolver` key in
+/// `[package]` or `[workspace]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResolveBehavior {
+    /// Features are unified across all uses of a package.
+    V1,
+    /// Features are resolved separately for the host and the target.
+    V2,
+}
+
+impl ResolveBehavior {
+    pub fn from_manifest(resolver: &str) -> CargoResult<ResolveBehavior> {
+        match resolver {
+            "1" => Ok(ResolveBehavior::V1),
+            "2" => Ok(ResolveBehavior::V2),
+            s => failure::bail!(
+                "`resolver` setting `{}` is not valid, valid options are \"1\" or \"2\"",
+                s
+            ),
+        }
+    }
+}
+
+/// Which of the feature sets of a package a unit is built with.
+///
+/// This is always `Normal` unless `resolver = "2"` is used, and it is only
+/// `Host` for the packages whose host features differ from their normal
+/// ones, so that identical units are still shared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FeaturesFor {
+    /// The features of the package when built for the target.
+    Normal,
+    /// The features of the package when it is a build-dependency or a
+    /// proc macro, or a dependency of one.
+    Host,
+}
+
+/// The features of every package, as computed by `resolve_features`.
+#[derive(Debug)]
+pub struct ResolvedFeatures {
+    activated: HashMap<(PackageId, FeaturesFor), BTreeSet<InternedString>>,
+}
+
+impl ResolvedFeatures {
+    /// The features enabled for `pkg_id`, sorted, like
+    /// `Resolve::features_sorted`.
+    pub fn activated(&self, pkg_id: PackageId, features_for: FeaturesFor) -> Vec<&str> {
+        match self.activated.get(&(pkg_id, features_for)) {
+            Some(features) => features
+                .iter()
+                .map(|f| f.as_str())
+                .filter(|f| !f.starts_with("dep:"))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn is_activated(&self, pkg_id: PackageId, features_for: FeaturesFor, name: &str) -> bool {
+        self.activated
+            .get(&(pkg_id, features_for))
+            .map_or(false, |features| {
+                features.iter().any(|f| f.as_str() == name)
+            })
+    }
+
+    /// The feature set a unit of `pkg_id` uses, given whether it is built
+    /// for the host.
+    pub fn features_for(&self, pkg_id: PackageId, for_host: bool) -> FeaturesFor {
+        if !for_host {
+            return FeaturesFor::Normal;
+        }
+        let host = self.activated.get(&(pkg_id, FeaturesFor::Host));
+        let normal = self.activated.get(&(pkg_id, FeaturesFor::Normal));
+        match (host, normal) {
+            (Some(host), Some(normal)) if host != normal => FeaturesFor::Host,
+            (Some(_), None) => FeaturesFor::Host,
+            _ => FeaturesFor::Normal,
+        }
+    }
+}
+
+/// Computes the features of the packages reachable from `roots` with
+/// `resolver = "2"`.
+///
+/// `method` holds the features requested on the command line, and its
+/// `dev_deps` whether any target using dev-dependencies is built.
+/// `platform_activated` tells whether a dependency or feature value for the
+/// given platform, if any, is used on the platform of the host or the target.
+pub fn resolve_features<'cfg>(
+    ws: &Workspace<'cfg>,
+    resolve: &Resolve,
+    packages: &PackageSet<'cfg>,
+    roots: &[PackageId],
+    method: Method<'_>,
+    platform_activated: &dyn Fn(Option<&Platform>, FeaturesFor) -> bool,
+) -> CargoResult<ResolvedFeatures> {
+    let dev_deps = match method {
+        Method::Everything => true,
+        Method::Required { dev_deps, .. } => dev_deps,
+    };
+    let mut r = FeatureResolver {
+        ws,
+        resolve,
+        packages,
+        platform_activated,
+        dev_deps,
+        activated: HashMap::new(),
+        visited: HashSet::new(),
+        weak: HashMap::new(),
+    };
+    r.download(roots)?;
+    for &root in roots {
+        r.activate_pkg(root, FeaturesFor::Normal, method)?;
+    }
+    Ok(ResolvedFeatures {
+        activated: r.activated,
+    })
+}
+
+struct FeatureResolver<'a, 'cfg> {
+    ws: &'a Workspace<'cfg>,
+    resolve: &'a Resolve,
+    packages: &'a PackageSet<'cfg>,
+    platform_activated: &'a dyn Fn(Option<&Platform>, FeaturesFor) -> bool,
+    dev_deps: bool,
+    activated: HashMap<(PackageId, FeaturesFor), BTreeSet<InternedString>>,
+    /// The packages whose non-optional dependencies were activated.
+    visited: HashSet<(PackageId, FeaturesFor)>,
+    /// The features of optional dependencies requested with `dep?/feature`,
+    /// applied once the dependency is enabled.
+    weak: HashMap<(PackageId, FeaturesFor), Vec<(InternedString, InternedString)>>,
+}
+
+impl<'a, 'cfg> FeatureResolver<'a, 'cfg> {
+    /// Downloads every package which may be reached at once, rather than one
+    /// by one while walking the graph.
+    fn download(&self, roots: &[PackageId]) -> CargoResult<()> {
+        let mut seen = HashSet::new();
+        let mut queue = roots.to_vec();
+        while let Some(id) = queue.pop() {
+            if !seen.insert(id) {
+                continue;
+            }
+            for (dep_id, deps) in self.resolve.deps(id) {
+                let used = deps.iter().any(|dep| {
+                    (self.platform_activated)(dep.platform(), FeaturesFor::Normal)
+                        || (self.platform_activated)(dep.platform(), FeaturesFor::Host)
+                });
+                if used {
+                    queue.push(dep_id);
+                }
+            }
+        }
+        self.packages.get_many(seen)?;
+        Ok(())
+    }
+
+    fn activate_pkg(
+        &mut self,
+        pkg_id: PackageId,
+        features_for: FeaturesFor,
+        method: Method<'_>,
+    ) -> CargoResult<()> {
+        let summary = self.packages.get_one(pkg_id)?.summary().clone();
+        self.activated
+            .entry((pkg_id, features_for))
+            .or_insert_with(BTreeSet::new);
+        if self.visited.insert((pkg_id, features_for)) {
+            self.activate_deps(pkg_id, features_for, None)?;
+        }
+        let (features, all_features, uses_default_features) = match method {
+            Method::Everything => (&[][..], true, true),
+            Method::Required {
+                features,
+                all_features,
+                uses_default_features,
+                ..
+            } => (features, all_features, uses_default_features),
+        };
+        if all_features {
+            for &feature in summary.features().keys() {
+                self.activate_fv(pkg_id, features_for, &FeatureValue::Feature(feature))?;
+            }
+            for dep in summary.dependencies().iter().filter(|d| d.is_optional()) {
+                let fv = if summary.is_explicit_dep(&dep.name_in_toml()) {
+                    FeatureValue::Dep(dep.name_in_toml())
+                } else {
+                    FeatureValue::Crate(dep.name_in_toml())
+                };
+                self.activate_fv(pkg_id, features_for, &fv)?;
+            }
+        }
+        for &feature in features {
+            let fv = FeatureValue::new(feature, &summary);
+            self.activate_fv(pkg_id, features_for, &fv)?;
+        }
+        let default = InternedString::new("default");
+        if uses_default_features && summary.features().contains_key(&default) {
+            self.activate_fv(pkg_id, features_for, &FeatureValue::Feature(default))?;
+        }
+        Ok(())
+    }
+
+    fn activate_fv(
+        &mut self,
+        pkg_id: PackageId,
+        features_for: FeaturesFor,
+        fv: &FeatureValue,
+    ) -> CargoResult<()> {
+        match *fv {
+            FeatureValue::Feature(feature) => {
+                if !self.insert(pkg_id, features_for, feature) {
+                    return Ok(());
+                }
+                let summary = self.packages.get_one(pkg_id)?.summary().clone();
+                let platform_activated = self.platform_activated;
+                let values = summary.feature_values(feature, &|platform| {
+                    platform_activated(Some(platform), features_for)
+                });
+                for value in values {
+                    self.activate_fv(pkg_id, features_for, value)?;
+                }
+            }
+            FeatureValue::Crate(dep_name) => {
+                if self.insert(pkg_id, features_for, dep_name) {
+                    self.enable_dep(pkg_id, features_for, dep_name)?;
+                }
+            }
+            FeatureValue::Dep(dep_name) => {
+                let name = InternedString::new(&format!("dep:{}", dep_name));
+                if self.insert(pkg_id, features_for, name) {
+                    self.enable_dep(pkg_id, features_for, dep_name)?;
+                }
+            }
+            FeatureValue::CrateFeature(dep_name, dep_feature) => {
+                // Like `Resolve`, this enables the dependency if it is
+                // optional.
+                let summary = self.packages.get_one(pkg_id)?.summary().clone();
+                if summary.is_explicit_dep(&dep_name) {
+                    self.activate_fv(pkg_id, features_for, &FeatureValue::Dep(dep_name))?;
+                    if summary.features().contains_key(&dep_name) {
+                        let fv = FeatureValue::Feature(dep_name);
+                        self.activate_fv(pkg_id, features_for, &fv)?;
+                    }
+                } else {
+                    self.activate_fv(pkg_id, features_for, &FeatureValue::Crate(dep_name))?;
+                }
+                self.activate_dep_feature(pkg_id, features_for, dep_name, dep_feature)?;
+            }
+            FeatureValue::WeakCrateFeature(dep_name, dep_feature) => {
+                let weak = self
+                    .weak
+                    .entry((pkg_id, features_for))
+                    .or_insert_with(Vec::new);
+                if weak.contains(&(dep_name, dep_feature)) {
+                    return Ok(());
+                }
+                weak.push((dep_name, dep_feature));
+                if self.is_dep_enabled(pkg_id, features_for, dep_name)? {
+                    self.activate_dep_feature(pkg_id, features_for, dep_name, dep_feature)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Activates the optional dependency `dep_name` of `pkg_id`, along with
+    /// the weak dependency features waiting for it.
+    fn enable_dep(
+        &mut self,
+        pkg_id: PackageId,
+        features_for: FeaturesFor,
+        dep_name: InternedString,
+    ) -> CargoResult<()> {
+        self.activate_deps(pkg_id, features_for, Some(dep_name))?;
+        let waiting = self
+            .weak
+            .get(&(pkg_id, features_for))
+            .map(|weak| {
+                weak.iter()
+                    .filter(|&&(name, _)| name == dep_name)
+                    .map(|&(_, feature)| feature)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        for feature in waiting {
+            self.activate_dep_feature(pkg_id, features_for, dep_name, feature)?;
+        }
+        Ok(())
+    }
+
+    /// Activates `dep_feature` on the dependencies of `pkg_id` named
+    /// `dep_name`.
+    fn activate_dep_feature(
+        &mut self,
+        pkg_id: PackageId,
+        features_for: FeaturesFor,
+        dep_name: InternedString,
+        dep_feature: InternedString,
+    ) -> CargoResult<()> {
+        for (dep_id, dep, dep_features_for) in self.deps(pkg_id, features_for)? {
+            if dep.name_in_toml() != dep_name {
+                continue;
+            }
+            let summary = self.packages.get_one(dep_id)?.summary().clone();
+            self.activate_pkg(
+                dep_id,
+                dep_features_for,
+                Method::Required {
+                    dev_deps: false,
+                    features: &[],
+                    all_features: false,
+                    uses_default_features: false,
+                },
+            )?;
+            let fv = FeatureValue::new(dep_feature, &summary);
+            self.activate_fv(dep_id, dep_features_for, &fv)?;
+        }
+        Ok(())
+    }
+
+    /// Whether the dependency `dep_name` of `pkg_id` is enabled, because it
+    /// isn't optional or a feature enabled it.
+    fn is_dep_enabled(
+        &self,
+        pkg_id: PackageId,
+        features_for: FeaturesFor,
+        dep_name: InternedString,
+    ) -> CargoResult<bool> {
+        let summary = self.packages.get_one(pkg_id)?.summary();
+        let required = summary
+            .dependencies()
+            .iter()
+            .any(|d| !d.is_optional() && d.name_in_toml() == dep_name);
+        let activated = &self.activated[&(pkg_id, features_for)];
+        let explicit = InternedString::new(&format!("dep:{}", dep_name));
+        Ok(required
+            || activated.contains(&explicit)
+            || (!summary.is_explicit_dep(&dep_name) && activated.contains(&dep_name)))
+    }
+
+    /// Activates the dependencies of `pkg_id`: the non-optional ones and the
+    /// enabled optional ones, or only those named `only`.
+    fn activate_deps(
+        &mut self,
+        pkg_id: PackageId,
+        features_for: FeaturesFor,
+        only: Option<InternedString>,
+    ) -> CargoResult<()> {
+        for (dep_id, dep, dep_features_for) in self.deps(pkg_id, features_for)? {
+            match only {
+                Some(name) if dep.name_in_toml() != name => continue,
+                Some(_) => {}
+                None => {
+                    if dep.is_optional()
+                        && !self.is_dep_enabled(pkg_id, features_for, dep.name_in_toml())?
+                    {
+                        continue;
+                    }
+                }
+            }
+            self.activate_pkg(
+                dep_id,
+                dep_features_for,
+                Method::Required {
+                    dev_deps: false,
+                    features: dep.features(),
+                    all_features: false,
+                    uses_default_features: dep.uses_default_features(),
+                },
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Lists the dependencies of `pkg_id` which are used when it's built
+    /// with `features_for`, along with the feature set they are built with.
+    fn deps(
+        &self,
+        pkg_id: PackageId,
+        features_for: FeaturesFor,
+    ) -> CargoResult<Vec<(PackageId, Dependency, FeaturesFor)>> {
+        let is_member = self.ws.is_member(self.packages.get_one(pkg_id)?);
+        let mut ret = Vec::new();
+        for (dep_id, deps) in self.resolve.deps(pkg_id) {
+            let is_proc_macro = self
+                .packages
+                .get_one(dep_id)?
+                .targets()
+                .iter()
+                .any(|t| t.is_lib() && t.proc_macro());
+            for dep in deps {
+                if !dep.is_transitive() && !(self.dev_deps && is_member) {
+                    continue;
+                }
+                // Build-dependencies are built for the host, so their
+                // platform is the one of the host.
+                let platform = if dep.is_build() {
+                    FeaturesFor::Host
+                } else {
+                    features_for
+                };
+                if !(self.platform_activated)(dep.platform(), platform) {
+                    continue;
+                }
+                let dep_features_for = if dep.is_build() || is_proc_macro {
+                    FeaturesFor::Host
+                } else {
+                    features_for
+                };
+                ret.push((dep_id, dep.clone(), dep_features_for));
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Enables `name` for `pkg_id`, returning whether it wasn't yet.
+    fn insert(
+        &mut self,
+        pkg_id: PackageId,
+        features_for: FeaturesFor,
+        name: InternedString,
+    ) -> bool {
+        self.activated
+            .entry((pkg_id, features_for))
+            .or_insert_with(BTreeSet::new)
+            .insert(name)
+    }
+}
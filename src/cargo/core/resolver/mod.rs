@@ -67,14 +67,16 @@ use self::types::{RcVecIter, RegistryQueryer, RemainingDeps, ResolverProgress};
 
 pub use self::encode::{EncodableDependency, EncodablePackageId, EncodableResolve};
 pub use self::encode::{Metadata, WorkspaceResolve};
-pub use self::errors::{ActivateError, ActivateResult, ResolveError};
-pub use self::resolve::Resolve;
+pub use self::errors::{describe_path, ActivateError, ActivateResult, ResolveError};
+pub use self::features::{resolve_features, FeaturesFor, ResolveBehavior, ResolvedFeatures};
+pub use self::resolve::{Resolve, ResolveVersion};
 pub use self::types::Method;
 
 mod conflict_cache;
 mod context;
 mod encode;
 mod errors;
+mod features;
 mod resolve;
 mod types;
 
@@ -102,6 +104,10 @@ mod types;
 ///   when sorting candidates to activate, but otherwise this isn't used
 ///   anywhere else.
 ///
+/// * `members` - the package IDs of the workspace members. The dependencies
+///   they declare are their direct dependencies, which are resolved to their
+///   minimal versions with `-Z direct-minimal-versions`.
+///
 /// * `config` - a location to print warnings and such, or `None` if no warnings
 ///   should be printed
 ///
@@ -123,6 +129,7 @@ pub fn resolve(
     replacements: &[(PackageIdSpec, Dependency)],
     registry: &mut dyn Registry,
     try_to_use: &HashSet<PackageId>,
+    members: &HashSet<PackageId>,
     config: Option<&Config>,
     print_warnings: bool,
     check_public_visible_dependencies: bool,
@@ -133,9 +140,36 @@ pub fn resolve(
         Some(config) => config.cli_unstable().minimal_versions,
         None => false,
     };
-    let mut registry = RegistryQueryer::new(registry, replacements, try_to_use, minimal_versions);
+    let direct_minimal_versions = match config {
+        Some(config) => config.cli_unstable().direct_minimal_versions,
+        None => false,
+    };
+    // With `-Z msrv-policy` the oldest `rust-version` of the packages being
+    // resolved is the one their dependencies should preferably support.
+    let max_rust_version = match config {
+        Some(config) if config.cli_unstable().msrv_policy => summaries
+            .iter()
+            .filter_map(|(summary, _)| summary.rust_version())
+            .min()
+            .cloned(),
+        _ => None,
+    };
+    let mut registry = RegistryQueryer::new(
+        registry,
+        replacements,
+        try_to_use,
+        members,
+        minimal_versions,
+        direct_minimal_versions,
+        max_rust_version,
+    );
     let cx = activate_deps_loop(cx, &mut registry, summaries, config)?;
 
+    // Existing lock files keep their format, see `Resolve::merge_from`.
+    let resolve_version = match config {
+        Some(config) if config.cli_unstable().lockfile_v2 => ResolveVersion::V2,
+        _ => ResolveVersion::V1,
+    };
     let mut cksums = HashMap::new();
     for (summary, _) in cx.activations.values() {
         let cksum = summary.checksum().map(|s| s.to_string());
@@ -151,6 +185,7 @@ pub fn resolve(
         cksums,
         BTreeMap::new(),
         Vec::new(),
+        resolve_version,
     );
 
     check_cycles(&resolve, &cx.activations)?;
@@ -900,7 +935,7 @@ fn generalize_conflicting(
             // Thus, if all the things it can resolve to have already ben determined
             // to be conflicting, then we can just say that we conflict with the parent.
             if registry
-                .query(&critical_parents_dep)
+                .query(&critical_parents_dep, *critical_parent)
                 .expect("an already used dep now error!?")
                 .iter()
                 .rev() // the last one to be tried is the least likely to be in the cache, so start with that.
@@ -1,4 +1,5 @@
 use std::borrow::Borrow;
+use std::cmp;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::iter::FromIterator;
@@ -29,6 +30,25 @@ pub struct Resolve {
     checksums: HashMap<PackageId, Option<String>>,
     metadata: Metadata,
     unused_patches: Vec<PackageId>,
+    version: ResolveVersion,
+}
+
+/// The format of the `Cargo.lock` a `Resolve` is written to.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, PartialOrd, Ord)]
+pub enum ResolveVersion {
+    /// The checksums are listed in the `[metadata]` table, and every
+    /// dependency is written with its version and source.
+    V1,
+    /// The checksums are listed on each `[[package]]`, and dependencies are
+    /// written with only as much of their version and source as is needed to
+    /// tell them apart.
+    V2,
+}
+
+impl Default for ResolveVersion {
+    fn default() -> ResolveVersion {
+        ResolveVersion::V1
+    }
 }
 
 impl Resolve {
@@ -39,6 +59,7 @@ impl Resolve {
         checksums: HashMap<PackageId, Option<String>>,
         metadata: Metadata,
         unused_patches: Vec<PackageId>,
+        version: ResolveVersion,
     ) -> Resolve {
         let reverse_replacements = replacements.iter().map(|(&p, &r)| (r, p)).collect();
         Resolve {
@@ -50,6 +71,7 @@ impl Resolve {
             unused_patches,
             empty_features: HashSet::new(),
             reverse_replacements,
+            version,
         }
     }
 
@@ -156,6 +178,8 @@ unable to verify that `{0}` is the same as when the lockfile was generated
 
         // Be sure to just copy over any unknown metadata.
         self.metadata = previous.metadata.clone();
+        // A lock file keeps its format, unless a newer one was asked for.
+        self.version = cmp::max(self.version, previous.version);
         Ok(())
     }
 
@@ -197,8 +221,15 @@ unable to verify that `{0}` is the same as when the lockfile was generated
         self.features.get(&pkg).unwrap_or(&self.empty_features)
     }
 
+    /// Returns the enabled features of `pkg`, sorted. Unlike `features`, this
+    /// leaves out the optional dependencies enabled with `dep:`.
     pub fn features_sorted(&self, pkg: PackageId) -> Vec<&str> {
-        let mut v = Vec::from_iter(self.features(pkg).iter().map(|s| s.as_ref()));
+        let mut v = Vec::from_iter(
+            self.features(pkg)
+                .iter()
+                .map(|s| s.as_ref())
+                .filter(|s: &&str| !s.starts_with("dep:")),
+        );
         v.sort_unstable();
         v
     }
@@ -219,6 +250,10 @@ unable to verify that `{0}` is the same as when the lockfile was generated
         &self.metadata
     }
 
+    pub fn version(&self) -> ResolveVersion {
+        self.version
+    }
+
     pub fn extern_crate_name(
         &self,
         from: PackageId,
@@ -249,6 +284,16 @@ unable to verify that `{0}` is the same as when the lockfile was generated
         Ok(name)
     }
 
+    /// Whether `to` is a public dependency of `from`, which is always the
+    /// case for a package's own library.
+    pub fn is_public_dep(&self, from: PackageId, to: PackageId) -> bool {
+        from == to
+            || self
+                .dependencies_listed(from, to)
+                .iter()
+                .any(|d| d.is_public())
+    }
+
     fn dependencies_listed(&self, from: PackageId, to: PackageId) -> &[Dependency] {
         // We've got a dependency on `from` to `to`, but this dependency edge
         // may be affected by [replace]. If the `to` package is listed as the
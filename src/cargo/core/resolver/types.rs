@@ -5,6 +5,7 @@ use std::rc::Rc;
 use std::time::{Duration, Instant};
 
 use log::debug;
+use semver::Version;
 
 use crate::core::interning::InternedString;
 use crate::core::{Dependency, PackageId, PackageIdSpec, Registry, Summary};
@@ -95,11 +96,20 @@ pub struct RegistryQueryer<'a> {
     pub registry: &'a mut (dyn Registry + 'a),
     replacements: &'a [(PackageIdSpec, Dependency)],
     try_to_use: &'a HashSet<PackageId>,
-    cache: HashMap<Dependency, Rc<Vec<Candidate>>>,
+    // The workspace members, whose dependencies are the direct ones.
+    members: &'a HashSet<PackageId>,
+    cache: HashMap<(Dependency, bool), Rc<Vec<Candidate>>>,
     // If set the list of dependency candidates will be sorted by minimal
     // versions first. That allows `cargo update -Z minimal-versions` which will
     // specify minimum dependency versions to be used.
     minimal_versions: bool,
+    // Like `minimal_versions`, but only for the direct dependencies of the
+    // workspace members, `-Z direct-minimal-versions`.
+    direct_minimal_versions: bool,
+    // If set, candidates whose `rust-version` is newer than this one are
+    // sorted after all of the compatible ones, so they're only picked if no
+    // compatible version works out (`-Z msrv-policy`).
+    max_rust_version: Option<Version>,
 }
 
 impl<'a> RegistryQueryer<'a> {
@@ -107,14 +117,29 @@ impl<'a> RegistryQueryer<'a> {
         registry: &'a mut dyn Registry,
         replacements: &'a [(PackageIdSpec, Dependency)],
         try_to_use: &'a HashSet<PackageId>,
+        members: &'a HashSet<PackageId>,
         minimal_versions: bool,
+        direct_minimal_versions: bool,
+        max_rust_version: Option<Version>,
     ) -> Self {
         RegistryQueryer {
             registry,
             replacements,
             cache: HashMap::new(),
             try_to_use,
+            members,
             minimal_versions,
+            direct_minimal_versions,
+            max_rust_version,
+        }
+    }
+
+    /// Whether the `rust-version` of `summary` is supported by the
+    /// `max_rust_version`, if there is one.
+    fn is_rust_version_compatible(&self, summary: &Summary) -> bool {
+        match (&self.max_rust_version, summary.rust_version()) {
+            (Some(max), Some(rust_version)) => rust_version <= max,
+            _ => true,
         }
     }
 
@@ -124,8 +149,18 @@ impl<'a> RegistryQueryer<'a> {
     /// any candidates are returned which match an override then the override is
     /// applied by performing a second query for what the override should
     /// return.
-    pub fn query(&mut self, dep: &Dependency) -> CargoResult<Rc<Vec<Candidate>>> {
-        if let Some(out) = self.cache.get(dep).cloned() {
+    ///
+    /// `parent` is the package declaring `dep`, which makes it a direct
+    /// dependency if it's a workspace member.
+    pub fn query(
+        &mut self,
+        dep: &Dependency,
+        parent: PackageId,
+    ) -> CargoResult<Rc<Vec<Candidate>>> {
+        let direct = self.members.contains(&parent);
+        let minimal_versions = self.minimal_versions || (self.direct_minimal_versions && direct);
+        let key = (dep.clone(), minimal_versions);
+        if let Some(out) = self.cache.get(&key).cloned() {
             return Ok(out);
         }
 
@@ -218,16 +253,20 @@ impl<'a> RegistryQueryer<'a> {
 
         // When we attempt versions for a package we'll want to do so in a
         // sorted fashion to pick the "best candidates" first. Currently we try
-        // prioritized summaries (those in `try_to_use`) and failing that we
-        // list everything from the maximum version to the lowest version.
+        // prioritized summaries (those in `try_to_use`), then the ones
+        // supporting the `max_rust_version`, and failing that we list
+        // everything from the maximum version to the lowest version.
         ret.sort_unstable_by(|a, b| {
             let a_in_previous = self.try_to_use.contains(&a.summary.package_id());
             let b_in_previous = self.try_to_use.contains(&b.summary.package_id());
             let previous_cmp = a_in_previous.cmp(&b_in_previous).reverse();
-            match previous_cmp {
+            let a_compatible = self.is_rust_version_compatible(&a.summary);
+            let b_compatible = self.is_rust_version_compatible(&b.summary);
+            let compatible_cmp = a_compatible.cmp(&b_compatible).reverse();
+            match previous_cmp.then(compatible_cmp) {
                 Ordering::Equal => {
                     let cmp = a.summary.version().cmp(b.summary.version());
-                    if self.minimal_versions {
+                    if minimal_versions {
                         // Lower version ordered first.
                         cmp
                     } else {
@@ -235,13 +274,13 @@ impl<'a> RegistryQueryer<'a> {
                         cmp.reverse()
                     }
                 }
-                _ => previous_cmp,
+                ordering => ordering,
             }
         });
 
         let out = Rc::new(ret);
 
-        self.cache.insert(dep.clone(), out.clone());
+        self.cache.insert(key, out.clone());
 
         Ok(out)
     }
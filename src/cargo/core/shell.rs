@@ -273,6 +273,35 @@ impl Shell {
         }
     }
 
+    /// Whether output relayed to stdout, such as the one of test binaries,
+    /// should be colored.
+    pub fn stdout_supports_color(&self) -> bool {
+        match self.color_choice() {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::CargoAuto => atty::is(atty::Stream::Stdout),
+        }
+    }
+
+    /// Prints a message to stdout, translating ANSI escape codes into
+    /// console colors like `print_ansi`.
+    pub fn print_ansi_stdout(&mut self, message: &[u8]) -> CargoResult<()> {
+        if self.needs_clear {
+            self.err_erase_line();
+        }
+        #[cfg(windows)]
+        {
+            if let ShellOut::Stream { color_choice, .. } = self.err {
+                let mut stream = StandardStream::stdout(color_choice.to_termcolor_color_choice());
+                ::fwdansi::write_ansi(&mut stream, message)?;
+                return Ok(());
+            }
+        }
+        let stdout = std::io::stdout();
+        stdout.lock().write_all(message)?;
+        Ok(())
+    }
+
     /// Prints a message and translates ANSI escape code into console colors.
     pub fn print_ansi(&mut self, message: &[u8]) -> CargoResult<()> {
         if self.needs_clear {
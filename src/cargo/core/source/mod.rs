@@ -36,6 +36,27 @@ pub trait Source {
     /// whereas an `Index` source may return dependencies that have the same canonicalization.
     fn fuzzy_query(&mut self, dep: &Dependency, f: &mut dyn FnMut(Summary)) -> CargoResult<()>;
 
+    /// Attempts to find the packages that match a dependency request but have
+    /// been yanked, and so are never returned by `query`. This is only used to
+    /// explain resolution failures and currently only applies to registry
+    /// sources.
+    fn query_yanked(&mut self, _dep: &Dependency, _f: &mut dyn FnMut(Summary)) -> CargoResult<()> {
+        Ok(())
+    }
+
+    /// Returns whether the package `pkg` has been yanked, even if it's in the
+    /// yanked whitelist. Only registry sources have yanked packages.
+    fn is_yanked(&mut self, _pkg: PackageId) -> CargoResult<bool> {
+        Ok(false)
+    }
+
+    /// Returns an identifier of the current state of the index of this
+    /// source, which changes whenever the index is updated, if the source
+    /// has such an index.
+    fn index_version(&mut self) -> CargoResult<Option<String>> {
+        Ok(None)
+    }
+
     fn query_vec(&mut self, dep: &Dependency) -> CargoResult<Vec<Summary>> {
         let mut ret = Vec::new();
         self.query(dep, &mut |s| ret.push(s))?;
@@ -134,6 +155,21 @@ impl<'a, T: Source + ?Sized + 'a> Source for Box<T> {
         (**self).fuzzy_query(dep, f)
     }
 
+    /// Forwards to `Source::query_yanked`.
+    fn query_yanked(&mut self, dep: &Dependency, f: &mut dyn FnMut(Summary)) -> CargoResult<()> {
+        (**self).query_yanked(dep, f)
+    }
+
+    /// Forwards to `Source::is_yanked`.
+    fn is_yanked(&mut self, pkg: PackageId) -> CargoResult<bool> {
+        (**self).is_yanked(pkg)
+    }
+
+    /// Forwards to `Source::index_version`.
+    fn index_version(&mut self) -> CargoResult<Option<String>> {
+        (**self).index_version()
+    }
+
     /// Forwards to `Source::update`.
     fn update(&mut self) -> CargoResult<()> {
         (**self).update()
@@ -196,6 +232,18 @@ impl<'a, T: Source + ?Sized + 'a> Source for &'a mut T {
         (**self).fuzzy_query(dep, f)
     }
 
+    fn query_yanked(&mut self, dep: &Dependency, f: &mut dyn FnMut(Summary)) -> CargoResult<()> {
+        (**self).query_yanked(dep, f)
+    }
+
+    fn is_yanked(&mut self, pkg: PackageId) -> CargoResult<bool> {
+        (**self).is_yanked(pkg)
+    }
+
+    fn index_version(&mut self) -> CargoResult<Option<String>> {
+        (**self).index_version()
+    }
+
     fn update(&mut self) -> CargoResult<()> {
         (**self).update()
     }
@@ -6,6 +6,7 @@ use std::rc::Rc;
 
 use serde::{Serialize, Serializer};
 
+use crate::core::dependency::Platform;
 use crate::core::interning::InternedString;
 use crate::core::{Dependency, PackageId, SourceId};
 use semver::Version;
@@ -29,8 +30,16 @@ struct Inner {
     checksum: Option<String>,
     links: Option<InternedString>,
     namespaced_features: bool,
+    rust_version: Option<Version>,
+    /// The feature values which are only enabled on a platform, from
+    /// `[target.'cfg(..)'.features]`, as `(feature, value, platform)`.
+    platform_features: Vec<(InternedString, FeatureValue, Platform)>,
 }
 
+/// The features of `[target.'cfg(..)'.features]` tables, as `(platform,
+/// features)` pairs.
+pub type PlatformFeatures = Vec<(Platform, BTreeMap<String, Vec<String>>)>;
+
 impl Summary {
     pub fn new<K>(
         pkg_id: PackageId,
@@ -42,9 +51,73 @@ impl Summary {
     where
         K: Borrow<str> + Ord + Display,
     {
+        Summary::with_platform_features(
+            pkg_id,
+            dependencies,
+            features,
+            &Vec::new(),
+            links,
+            namespaced_features,
+        )
+    }
+
+    /// Like `new`, but also adds the values of `platform_features` to the
+    /// features, which are only enabled on their platform. Apart from
+    /// `default`, their features have to be declared in `features` too.
+    pub fn with_platform_features<K>(
+        pkg_id: PackageId,
+        dependencies: Vec<Dependency>,
+        features: &BTreeMap<K, Vec<impl AsRef<str>>>,
+        platform_features: &PlatformFeatures,
+        links: Option<impl AsRef<str>>,
+        namespaced_features: bool,
+    ) -> CargoResult<Summary>
+    where
+        K: Borrow<str> + Ord + Display,
+    {
+        // `features()` lists the values of every platform, like the resolver
+        // includes the dependencies of every platform.
+        let mut all_features = features
+            .iter()
+            .map(|(feature, values)| {
+                let values = values.iter().map(|v| v.as_ref().to_string()).collect();
+                (feature.borrow().to_string(), values)
+            })
+            .collect::<BTreeMap<String, Vec<String>>>();
+        for &(ref platform, ref platform_features) in platform_features.iter() {
+            for (feature, values) in platform_features.iter() {
+                if feature != "default" && !all_features.contains_key(feature) {
+                    failure::bail!(
+                        "feature `{}` of `[target.'{}'.features]` is not declared \
+                         in `[features]`",
+                        feature,
+                        platform
+                    )
+                }
+                let all_values = all_features.entry(feature.clone()).or_insert_with(Vec::new);
+                for value in values {
+                    if !all_values.contains(value) {
+                        all_values.push(value.clone());
+                    }
+                }
+            }
+        }
+        let base_features = features;
+        let features = &all_features;
+
         for dep in dependencies.iter() {
             let feature = dep.name_in_toml();
-            if !namespaced_features && features.get(&*feature).is_some() {
+            // A dependency enabled with `dep:` has no implicit feature, so a
+            // feature may have the same name.
+            let explicit_value = format!("dep:{}", feature);
+            let explicit = features
+                .values()
+                .flat_map(|values| values.iter())
+                .any(|value| {
+                    let value: &str = value.as_ref();
+                    value == explicit_value
+                });
+            if !namespaced_features && !explicit && features.get(&*feature).is_some() {
                 failure::bail!(
                     "Features and dependencies cannot have the \
                      same name: `{}`",
@@ -59,6 +132,31 @@ impl Summary {
             }
         }
         let feature_map = build_feature_map(features, &dependencies, namespaced_features)?;
+
+        let mut conditional = Vec::new();
+        for &(ref platform, ref platform_features) in platform_features.iter() {
+            for (feature, values) in platform_features.iter() {
+                for value in values {
+                    // Values which are also listed in `[features]` apply to
+                    // every platform anyway.
+                    if base_features.get(feature.as_str()).map_or(false, |base| {
+                        base.iter().any(|v| {
+                            let v: &str = v.as_ref();
+                            v == value
+                        })
+                    }) {
+                        continue;
+                    }
+                    let value = FeatureValue::build(
+                        InternedString::new(value),
+                        |fs| features.contains_key(fs.as_str()),
+                        namespaced_features,
+                    );
+                    conditional.push((InternedString::new(feature), value, platform.clone()));
+                }
+            }
+        }
+
         Ok(Summary {
             inner: Rc::new(Inner {
                 package_id: pkg_id,
@@ -67,6 +165,8 @@ impl Summary {
                 checksum: None,
                 links: links.map(|l| InternedString::new(l.as_ref())),
                 namespaced_features,
+                rust_version: None,
+                platform_features: conditional,
             }),
         })
     }
@@ -98,6 +198,67 @@ impl Summary {
     pub fn namespaced_features(&self) -> bool {
         self.inner.namespaced_features
     }
+    /// The minimum version of rustc the package supports, if it declares one.
+    pub fn rust_version(&self) -> Option<&Version> {
+        self.inner.rust_version.as_ref()
+    }
+
+    /// The values of `feature` on the platforms for which
+    /// `platform_activated` returns true, leaving out the ones only added by
+    /// the `[target.'cfg(..)'.features]` of other platforms.
+    pub fn feature_values(
+        &self,
+        feature: InternedString,
+        platform_activated: &dyn Fn(&Platform) -> bool,
+    ) -> Vec<&FeatureValue> {
+        let values = match self.features().get(&feature) {
+            Some(values) => values,
+            None => return Vec::new(),
+        };
+        values
+            .iter()
+            .filter(|&value| {
+                let mut platforms = self
+                    .inner
+                    .platform_features
+                    .iter()
+                    .filter(|&&(f, ref v, _)| f == feature && v == value)
+                    .peekable();
+                platforms.peek().is_none() || platforms.any(|&(_, _, ref p)| platform_activated(p))
+            })
+            .collect()
+    }
+
+    /// The feature values which are only enabled on some platforms, as
+    /// `(feature, value, platform)`.
+    pub fn platform_features(&self) -> &[(InternedString, FeatureValue, Platform)] {
+        &self.inner.platform_features
+    }
+
+    /// Whether the optional dependency `name` is enabled with the `dep:`
+    /// syntax somewhere in the features table. Such a dependency doesn't
+    /// have an implicit feature of the same name.
+    pub fn is_explicit_dep(&self, name: &str) -> bool {
+        self.features()
+            .values()
+            .flat_map(|values| values.iter())
+            .any(|value| match value {
+                FeatureValue::Dep(dep) => dep.as_str() == name,
+                _ => false,
+            })
+    }
+
+    /// Whether a feature enables a feature of a dependency with the
+    /// `dep?/feature` syntax.
+    pub fn has_weak_dep_features(&self) -> bool {
+        self.features()
+            .values()
+            .flat_map(|values| values.iter())
+            .any(|value| match value {
+                FeatureValue::WeakCrateFeature(..) => true,
+                _ => false,
+            })
+    }
 
     pub fn override_id(mut self, id: PackageId) -> Summary {
         Rc::make_mut(&mut self.inner).package_id = id;
@@ -109,6 +270,11 @@ impl Summary {
         self
     }
 
+    pub fn set_rust_version(mut self, rust_version: Option<Version>) -> Summary {
+        Rc::make_mut(&mut self.inner).rust_version = rust_version;
+        self
+    }
+
     pub fn map_dependencies<F>(mut self, f: F) -> Summary
     where
         F: FnMut(Dependency) -> Dependency,
@@ -203,21 +369,26 @@ where
             // Find data for the referenced dependency...
             let dep_data = {
                 match val {
-                    Feature(ref dep_name) | Crate(ref dep_name) | CrateFeature(ref dep_name, _) => {
-                        dep_map.get(dep_name.as_str())
-                    }
+                    Feature(ref dep_name)
+                    | Crate(ref dep_name)
+                    | Dep(ref dep_name)
+                    | CrateFeature(ref dep_name, _)
+                    | WeakCrateFeature(ref dep_name, _) => dep_map.get(dep_name.as_str()),
                 }
             };
             let is_optional_dep = dep_data
                 .iter()
                 .flat_map(|d| d.iter())
                 .any(|d| d.is_optional());
-            if let FeatureValue::Crate(ref dep_name) = val {
-                // If we have a dependency value, check if this is the dependency named
-                // the same as the feature that we were looking for.
-                if !dependency_found && feature.borrow() == dep_name.as_str() {
-                    dependency_found = true;
+            match val {
+                Crate(ref dep_name) | Dep(ref dep_name) => {
+                    // If we have a dependency value, check if this is the dependency named
+                    // the same as the feature that we were looking for.
+                    if !dependency_found && feature.borrow() == dep_name.as_str() {
+                        dependency_found = true;
+                    }
                 }
+                _ => {}
             }
 
             match (&val, dep_data.is_some(), is_optional_dep) {
@@ -303,15 +474,45 @@ where
                     }
                 }
                 (&Crate(_), true, true) => {}
+                // The value explicitly enables a dependency with `dep:`, which has to
+                // be an optional one.
+                (&Dep(ref dep), true, false) => failure::bail!(
+                    "Feature `{}` includes `dep:{}`, but `{}` is not an optional \
+                     dependency.\nConsider adding `optional = true` to the dependency",
+                    feature,
+                    dep,
+                    dep
+                ),
+                (&Dep(ref dep), false, _) => failure::bail!(
+                    "Feature `{}` includes `dep:{}`, but `{}` is not listed as a \
+                     dependency",
+                    feature,
+                    dep,
+                    dep
+                ),
+                (&Dep(_), true, true) => {}
                 // If the value is a feature for one of the dependencies, bail out if no such
                 // dependency is actually defined in the manifest.
-                (&CrateFeature(ref dep, _), false, _) => failure::bail!(
+                (&CrateFeature(ref dep, _), false, _)
+                | (&WeakCrateFeature(ref dep, _), false, _) => failure::bail!(
                     "Feature `{}` requires a feature of `{}` which is not a \
                      dependency",
                     feature,
                     dep
                 ),
                 (&CrateFeature(_, _), true, _) => {}
+                // A weak dependency feature only makes sense for an optional dependency,
+                // a non-optional one is always enabled.
+                (&WeakCrateFeature(ref dep, ref dep_feat), true, false) => failure::bail!(
+                    "Feature `{}` includes `{}?/{}` with a `?`, but `{}` is not an \
+                     optional dependency.\nA non-optional dependency is always enabled, \
+                     consider removing the `?`",
+                    feature,
+                    dep,
+                    dep_feat,
+                    dep
+                ),
+                (&WeakCrateFeature(_, _), true, true) => {}
             }
             values.push(val);
         }
@@ -337,15 +538,21 @@ where
 /// FeatureValue represents the types of dependencies a feature can have:
 ///
 /// * Another feature
-/// * An optional dependency
-/// * A feature in a dependency
+/// * An optional dependency, either by name (`foo`) or with `dep:foo`
+/// * A feature in a dependency, either `foo/bar` or `foo?/bar`
 ///
-/// The selection between these 3 things happens as part of the construction of the FeatureValue.
-#[derive(Clone, Debug)]
+/// The selection between these things happens as part of the construction of the FeatureValue.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum FeatureValue {
     Feature(InternedString),
     Crate(InternedString),
+    /// An optional dependency enabled with `dep:`, which doesn't get an
+    /// implicit feature of the same name.
+    Dep(InternedString),
     CrateFeature(InternedString, InternedString),
+    /// A feature of a dependency written `foo?/bar`, which is only enabled if
+    /// the optional dependency is enabled by something else.
+    WeakCrateFeature(InternedString, InternedString),
 }
 
 impl FeatureValue {
@@ -353,6 +560,17 @@ impl FeatureValue {
     where
         T: Fn(InternedString) -> bool,
     {
+        if let Some(pos) = feature.find("?/") {
+            let (dep, dep_feat) = feature.split_at(pos);
+            let dep_feat = &dep_feat[2..];
+            return FeatureValue::WeakCrateFeature(
+                InternedString::new(dep),
+                InternedString::new(dep_feat),
+            );
+        }
+        if feature.starts_with("dep:") {
+            return FeatureValue::Dep(InternedString::new(&feature[4..]));
+        }
         match (feature.find('/'), namespaced) {
             (Some(pos), _) => {
                 let (dep, dep_feat) = feature.split_at(pos);
@@ -387,7 +605,9 @@ impl FeatureValue {
                     c.to_string()
                 }
             }
+            Dep(ref c) => format!("dep:{}", c),
             CrateFeature(ref c, ref f) => [c.as_ref(), f.as_ref()].join("/"),
+            WeakCrateFeature(ref c, ref f) => [c.as_ref(), f.as_ref()].join("?/"),
         }
     }
 }
@@ -401,9 +621,13 @@ impl Serialize for FeatureValue {
         match *self {
             Feature(ref f) => serializer.serialize_str(f),
             Crate(ref c) => serializer.serialize_str(c),
+            Dep(ref c) => serializer.serialize_str(&format!("dep:{}", c)),
             CrateFeature(ref c, ref f) => {
                 serializer.serialize_str(&[c.as_ref(), f.as_ref()].join("/"))
             }
+            WeakCrateFeature(ref c, ref f) => {
+                serializer.serialize_str(&[c.as_ref(), f.as_ref()].join("?/"))
+            }
         }
     }
 }
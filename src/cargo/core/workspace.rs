@@ -6,10 +6,12 @@ use std::slice;
 
 use glob::glob;
 use log::debug;
+use semver::VersionReq;
 use url::Url;
 
 use crate::core::profiles::Profiles;
 use crate::core::registry::PackageRegistry;
+use crate::core::resolver::ResolveBehavior;
 use crate::core::{Dependency, PackageIdSpec};
 use crate::core::{EitherManifest, Package, SourceId, VirtualManifest};
 use crate::ops;
@@ -115,6 +117,47 @@ pub struct WorkspaceRootConfig {
     members: Option<Vec<String>>,
     default_members: Option<Vec<String>>,
     exclude: Vec<String>,
+    policy: Option<WorkspacePolicy>,
+}
+
+/// The `[workspace.policy]` table, rules which the resolved dependency graph
+/// of the workspace has to follow.
+#[derive(Debug, Clone)]
+pub struct WorkspacePolicy {
+    /// What to do about packages used in several semver incompatible
+    /// versions.
+    pub duplicates: PolicyLevel,
+    /// Packages which may be used in several versions regardless.
+    pub allow_duplicates: Vec<String>,
+    /// What to do about banned packages.
+    pub bans: PolicyLevel,
+    /// The banned packages, by name and optionally version requirement.
+    pub banned: Vec<(String, Option<VersionReq>)>,
+    /// Packages which may depend on banned packages regardless, such as
+    /// wrappers around them.
+    pub allow_banned_in: Vec<String>,
+}
+
+/// How a rule of a `WorkspacePolicy` is enforced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl PolicyLevel {
+    pub fn from_manifest(level: &str) -> CargoResult<PolicyLevel> {
+        match level {
+            "allow" => Ok(PolicyLevel::Allow),
+            "warn" => Ok(PolicyLevel::Warn),
+            "deny" => Ok(PolicyLevel::Deny),
+            s => failure::bail!(
+                "policy level `{}` is not valid, valid options are \"allow\", \"warn\" or \"deny\"",
+                s
+            ),
+        }
+    }
 }
 
 /// An iterator over the member packages of a workspace, returned by
@@ -243,6 +286,24 @@ impl<'cfg> Workspace<'cfg> {
         }
     }
 
+    /// Returns the `[workspace.policy]` of the root manifest, if any.
+    pub fn policy(&self) -> Option<&WorkspacePolicy> {
+        match *self.root_maybe().workspace_config() {
+            WorkspaceConfig::Root(ref root) => root.policy.as_ref(),
+            WorkspaceConfig::Member { .. } => None,
+        }
+    }
+
+    /// Returns the feature resolver used by this workspace, as picked by the
+    /// `resolver` key of the root manifest.
+    pub fn resolve_behavior(&self) -> ResolveBehavior {
+        let behavior = match self.root_maybe() {
+            MaybePackage::Package(p) => p.manifest().resolve_behavior(),
+            MaybePackage::Virtual(vm) => vm.resolve_behavior(),
+        };
+        behavior.unwrap_or(ResolveBehavior::V1)
+    }
+
     /// Returns the root path of this workspace.
     ///
     /// That is, this returns the path of the directory containing the
@@ -845,19 +906,21 @@ impl WorkspaceRootConfig {
         members: &Option<Vec<String>>,
         default_members: &Option<Vec<String>>,
         exclude: &Option<Vec<String>>,
+        policy: Option<WorkspacePolicy>,
     ) -> WorkspaceRootConfig {
         WorkspaceRootConfig {
             root_dir: root_dir.to_path_buf(),
             members: members.clone(),
             default_members: default_members.clone(),
             exclude: exclude.clone().unwrap_or_default(),
+            policy,
         }
     }
 
     /// Checks the path against the `excluded` list.
     ///
     /// This method does **not** consider the `members` list.
-    fn is_excluded(&self, manifest_path: &Path) -> bool {
+    pub fn is_excluded(&self, manifest_path: &Path) -> bool {
         let excluded = self
             .exclude
             .iter()
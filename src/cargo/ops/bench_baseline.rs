@@ -0,0 +1,213 @@
+//! Saving the results of `cargo bench` as named baselines, and comparing
+//! later runs against them.
+//!
+//! Baselines are stored as JSON in `target/bench-baselines/<name>.json`,
+//! keyed by package, target and benchmark name.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::Workspace;
+use crate::ops::test_results::{TestOutcome, TestResults};
+use crate::util::errors::{CargoResult, CargoResultExt};
+use crate::util::{machine_message, paths};
+
+/// The regression threshold used unless `--regression-threshold` is passed,
+/// in percent.
+pub const DEFAULT_REGRESSION_THRESHOLD: f64 = 5.0;
+
+/// How `cargo bench` should use baselines, see `--save-baseline` and
+/// `--baseline`.
+#[derive(Clone, Debug, Default)]
+pub struct BaselineOptions {
+    /// The name under which to save the results.
+    pub save: Option<String>,
+    /// The name of the baseline to compare the results with.
+    pub compare: Option<String>,
+    /// A benchmark which got slower than the baseline by more than this
+    /// percentage is a regression.
+    pub regression_threshold: Option<f64>,
+}
+
+impl BaselineOptions {
+    pub fn is_empty(&self) -> bool {
+        self.save.is_none() && self.compare.is_none()
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Baseline {
+    /// Keyed by `<package>::<target> <benchmark>`.
+    benchmarks: BTreeMap<String, BenchResult>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+struct BenchResult {
+    ns_per_iter: u64,
+    deviation: u64,
+}
+
+impl Baseline {
+    fn from_results(results: &TestResults) -> Baseline {
+        let mut baseline = Baseline::default();
+        for binary in results.binaries.iter() {
+            for case in binary.cases.iter() {
+                if let TestOutcome::Bench {
+                    ns_per_iter,
+                    deviation,
+                } = case.outcome
+                {
+                    baseline.benchmarks.insert(
+                        format!("{} {}", binary.display_name(), case.name),
+                        BenchResult {
+                            ns_per_iter,
+                            deviation,
+                        },
+                    );
+                }
+            }
+        }
+        baseline
+    }
+}
+
+/// Checks that the baseline `name` can be used as a file name in the
+/// baselines directory.
+fn validate_baseline_name(name: &str) -> CargoResult<()> {
+    if name.is_empty() {
+        failure::bail!("the baseline name cannot be empty");
+    }
+    if name.contains(|c| c == '/' || c == '\\') {
+        failure::bail!(
+            "invalid baseline name `{}`, it cannot contain path separators",
+            name
+        );
+    }
+    Ok(())
+}
+
+fn baseline_path(ws: &Workspace<'_>, name: &str) -> CargoResult<PathBuf> {
+    validate_baseline_name(name)?;
+    Ok(ws
+        .target_dir()
+        .join("bench-baselines")
+        .join(format!("{}.json", name))
+        .into_path_unlocked())
+}
+
+/// Saves the benchmark results as the baseline `name`, replacing any
+/// previous baseline of that name.
+pub fn save(ws: &Workspace<'_>, name: &str, results: &TestResults) -> CargoResult<()> {
+    let path = baseline_path(ws, name)?;
+    let baseline = Baseline::from_results(results);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .chain_err(|| format!("failed to create directory `{}`", parent.display()))?;
+    }
+    let json = serde_json::to_string_pretty(&baseline)?;
+    paths::write(&path, json.as_bytes())?;
+    ws.config().shell().status(
+        "Saved",
+        format!(
+            "{} benchmark result{} as baseline `{}`",
+            baseline.benchmarks.len(),
+            if baseline.benchmarks.len() == 1 {
+                ""
+            } else {
+                "s"
+            },
+            name
+        ),
+    )
+}
+
+/// Compares the benchmark results with the baseline `name`, printing the
+/// change of every benchmark. Fails if any benchmark regressed by more than
+/// the threshold.
+pub fn compare(
+    ws: &Workspace<'_>,
+    options: &BaselineOptions,
+    name: &str,
+    results: &TestResults,
+    json_messages: bool,
+) -> CargoResult<()> {
+    let config = ws.config();
+    let path = baseline_path(ws, name)?;
+    if !path.exists() {
+        failure::bail!(
+            "no baseline named `{}` was found, save one first with `--save-baseline {}`",
+            name,
+            name
+        );
+    }
+    let contents = paths::read(&path)?;
+    let old: Baseline = serde_json::from_str(&contents)
+        .chain_err(|| format!("failed to parse baseline `{}`", path.display()))?;
+    let new = Baseline::from_results(results);
+    let threshold = options
+        .regression_threshold
+        .unwrap_or(DEFAULT_REGRESSION_THRESHOLD);
+
+    config
+        .shell()
+        .status("Comparing", format!("benchmarks with baseline `{}`", name))?;
+    let width = new.benchmarks.keys().map(|k| k.len()).max().unwrap_or(0);
+    let mut regressions = 0;
+    for (key, result) in new.benchmarks.iter() {
+        let base = old.benchmarks.get(key);
+        let change = base.map(|base| percent_change(base.ns_per_iter, result.ns_per_iter));
+        let regressed = change.map_or(false, |c| c > threshold);
+        if regressed {
+            regressions += 1;
+        }
+        if json_messages {
+            machine_message::emit(&machine_message::BenchComparison {
+                name: key,
+                baseline: name,
+                ns_per_iter: result.ns_per_iter,
+                deviation: result.deviation,
+                baseline_ns_per_iter: base.map(|b| b.ns_per_iter),
+                baseline_deviation: base.map(|b| b.deviation),
+                change_percent: change,
+                regressed,
+            });
+        }
+        let comparison = match (base, change) {
+            (Some(base), Some(change)) => format!(
+                "{:>12} -> {:>12} ns/iter {:>+8.2}%{}",
+                base.ns_per_iter,
+                result.ns_per_iter,
+                change,
+                if regressed { "  REGRESSED" } else { "" }
+            ),
+            _ => format!("{:>12} ns/iter (not in baseline)", result.ns_per_iter),
+        };
+        writeln!(
+            config.shell().err(),
+            "    {:width$}  {}",
+            key,
+            comparison,
+            width = width
+        )?;
+    }
+
+    if regressions > 0 {
+        failure::bail!(
+            "{} benchmark{} regressed by more than {}% compared with baseline `{}`",
+            regressions,
+            if regressions == 1 { "" } else { "s" },
+            threshold,
+            name
+        );
+    }
+    Ok(())
+}
+
+fn percent_change(old: u64, new: u64) -> f64 {
+    if old == 0 {
+        return 0.0;
+    }
+    (new as f64 - old as f64) / old as f64 * 100.0
+}
@@ -0,0 +1,557 @@
+//! Implementation of `cargo add` and `cargo remove`, which edit the
+//! dependency tables of a manifest while keeping its formatting, comments
+//! and ordering intact.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use semver::{Version, VersionReq};
+use toml_edit::{Array, Document, InlineTable, Item, Table, Value};
+
+use crate::core::dependency::Kind;
+use crate::core::{Dependency, Package, SourceId, Summary, Workspace};
+use crate::ops;
+use crate::sources::SourceConfigMap;
+use crate::util::errors::{CargoResult, CargoResultExt};
+use crate::util::toml::read_manifest;
+use crate::util::{paths, validate_package_name, Config};
+
+pub struct AddOptions {
+    /// The dependencies to add, each either `name` or `name@requirement`.
+    pub crates: Vec<String>,
+    /// The kind of dependency, see `--dev` and `--build`.
+    pub kind: Kind,
+    /// Add the dependencies to the table of this platform, see `--target`.
+    pub target: Option<String>,
+    pub features: Vec<String>,
+    pub optional: bool,
+    /// The name the dependency is available under, see `--rename`.
+    pub rename: Option<String>,
+    pub path: Option<PathBuf>,
+    pub git: Option<String>,
+    /// The alternative registry the dependencies come from.
+    pub registry: Option<String>,
+}
+
+pub struct RemoveOptions {
+    /// The names of the dependencies to remove, as used in the manifest.
+    pub crates: Vec<String>,
+    pub kind: Kind,
+    pub target: Option<String>,
+}
+
+/// Adds dependencies to the manifest of the current package, or modifies them
+/// if they are already listed.
+pub fn add(ws: &Workspace<'_>, opts: &AddOptions) -> CargoResult<()> {
+    let config = ws.config();
+    let pkg = ws.current()?;
+
+    if opts.path.is_some() && opts.git.is_some() {
+        failure::bail!("`--path` and `--git` cannot be used together");
+    }
+    if (opts.path.is_some() || opts.git.is_some()) && opts.registry.is_some() {
+        failure::bail!("`--registry` cannot be used with `--path` or `--git`");
+    }
+    let single = opts.rename.is_some() || opts.path.is_some() || opts.git.is_some();
+    if single && opts.crates.len() > 1 {
+        failure::bail!(
+            "`--rename`, `--path` and `--git` can only be used when adding a single dependency"
+        );
+    }
+
+    let mut specs = opts.crates.clone();
+    if specs.is_empty() {
+        match opts.path {
+            Some(ref path) => specs.push(path_package(config, path)?.name().to_string()),
+            None => failure::bail!("no dependencies were given to add"),
+        }
+    }
+
+    let mut manifest = LocalManifest::open(pkg.manifest_path())?;
+    for spec in specs.iter() {
+        let (name, req) = parse_spec(spec)?;
+        let key = opts.rename.as_ref().map(|s| s.as_str()).unwrap_or(name);
+        validate_package_name(key, "dependency name", "")?;
+
+        let mut fields = Vec::new();
+        let mut status = name.to_string();
+        if let Some(ref path) = opts.path {
+            let path = config.cwd().join(path);
+            let base = pkg.manifest_path().parent().unwrap();
+            let relative = paths::relative_path(base, &path);
+            let relative = relative.to_str().ok_or_else(|| {
+                failure::format_err!("path `{}` is not valid UTF-8", relative.display())
+            })?;
+            let relative = relative.replace('\\', "/");
+            status.push_str(&format!(" (path {})", relative));
+            fields.push(("path", Value::from(relative)));
+        } else if let Some(ref git) = opts.git {
+            status.push_str(&format!(" (git {})", git));
+            fields.push(("git", Value::from(git.as_str())));
+        }
+
+        // Without a requirement, an existing dependency keeps its version
+        // while a new one from a registry gets the newest version there.
+        let exists = manifest
+            .existing_dep_table(opts.kind, opts.target.as_ref().map(|s| s.as_str()))?
+            .map_or(false, |table| table.contains_key(key));
+        let req = match req {
+            Some(req) => Some(req.to_string()),
+            None if !exists && opts.path.is_none() && opts.git.is_none() => {
+                let source_id = match opts.registry {
+                    Some(ref r) => SourceId::alt_registry(config, r)?,
+                    None => SourceId::crates_io(config)?,
+                };
+                let version =
+                    latest_version(config, source_id, name, pkg.manifest().rust_version())?;
+                Some(version.to_string())
+            }
+            None => None,
+        };
+        if let Some(ref req) = req {
+            status.push_str(&format!(" {}", req));
+            fields.push(("version", Value::from(req.as_str())));
+        }
+        if let Some(ref registry) = opts.registry {
+            fields.push(("registry", Value::from(registry.as_str())));
+        }
+        if opts.rename.is_some() {
+            fields.push(("package", Value::from(name)));
+        }
+        if opts.optional {
+            fields.push(("optional", Value::from(true)));
+        }
+
+        let table_name = table_name(opts.kind, opts.target.as_ref().map(|s| s.as_str()));
+        config
+            .shell()
+            .status("Adding", format!("{} to {}", status, table_name))?;
+
+        let table = manifest.dep_table(opts.kind, opts.target.as_ref().map(|s| s.as_str()))?;
+        let dep = table.entry(key);
+        let source_given = opts.path.is_some() || opts.git.is_some() || opts.registry.is_some();
+        set_dependency(dep, &fields, &opts.features, source_given);
+    }
+
+    manifest.write(config, pkg)
+}
+
+/// Removes dependencies from the manifest of the current package.
+pub fn remove(ws: &Workspace<'_>, opts: &RemoveOptions) -> CargoResult<()> {
+    let config = ws.config();
+    let pkg = ws.current()?;
+
+    let target = opts.target.as_ref().map(|s| s.as_str());
+    let table_name = table_name(opts.kind, target);
+    let mut manifest = LocalManifest::open(pkg.manifest_path())?;
+    for name in opts.crates.iter() {
+        {
+            let table = match manifest.existing_dep_table(opts.kind, target)? {
+                Some(table) => table,
+                None => failure::bail!("the manifest has no `{}` table", table_name),
+            };
+            if !table.contains_key(name) {
+                failure::bail!(
+                    "the dependency `{}` could not be found in `{}`",
+                    name,
+                    table_name
+                );
+            }
+            table.remove(name);
+        }
+        config
+            .shell()
+            .status("Removing", format!("{} from {}", name, table_name))?;
+
+        // An optional dependency may still be referred to by features, which
+        // would no longer be valid once nothing declares the dependency.
+        if !manifest.declares_dependency(name) {
+            manifest.remove_from_features(name);
+        }
+    }
+
+    manifest.write(config, pkg)
+}
+
+/// A manifest being edited, keeping the formatting of the original file.
+pub(super) struct LocalManifest {
+    path: PathBuf,
+    original: String,
+    doc: Document,
+}
+
+impl LocalManifest {
+    pub(super) fn open(path: &Path) -> CargoResult<LocalManifest> {
+        let original = paths::read(path)?;
+        let doc = original
+            .parse::<Document>()
+            .map_err(|e| failure::format_err!("{}", e))
+            .chain_err(|| format!("failed to parse manifest at `{}`", path.display()))?;
+        Ok(LocalManifest {
+            path: path.to_path_buf(),
+            original,
+            doc,
+        })
+    }
+
+    /// Returns the dependency table for `kind` and `target`, creating it if
+    /// necessary.
+    fn dep_table(&mut self, kind: Kind, target: Option<&str>) -> CargoResult<&mut Table> {
+        let mut table = self.doc.as_table_mut();
+        for key in table_path(kind, target) {
+            let item = table.entry(&key_repr(&key));
+            if item.is_none() {
+                let mut new = Table::new();
+                // Only the innermost table gets a header, there's no need for
+                // a `[target]` one for example.
+                new.set_implicit(true);
+                *item = Item::Table(new);
+            }
+            table = match item.as_table_mut() {
+                Some(table) => table,
+                None => failure::bail!("`{}` in the manifest is not a table", key),
+            };
+        }
+        table.set_implicit(false);
+        Ok(table)
+    }
+
+    /// Returns the kinds and targets of all the dependency tables of the
+    /// manifest.
+    pub(super) fn dep_tables(&self) -> Vec<(Kind, Option<String>)> {
+        let kinds = [Kind::Normal, Kind::Development, Kind::Build];
+        let mut tables = Vec::new();
+        let root = self.doc.as_table();
+        for (key, &kind) in DEP_TABLES.iter().zip(kinds.iter()) {
+            if let Some(&Item::Table(_)) = root.get(key) {
+                tables.push((kind, None));
+            }
+        }
+        if let Some(&Item::Table(ref targets)) = root.get("target") {
+            for (target, item) in targets.iter() {
+                let item = match *item {
+                    Item::Table(ref item) => item,
+                    _ => continue,
+                };
+                for (key, &kind) in DEP_TABLES.iter().zip(kinds.iter()) {
+                    if let Some(&Item::Table(_)) = item.get(key) {
+                        tables.push((kind, Some(target.to_string())));
+                    }
+                }
+            }
+        }
+        tables
+    }
+
+    /// Returns the dependency table for `kind` and `target` if the manifest
+    /// has one.
+    pub(super) fn existing_dep_table(
+        &mut self,
+        kind: Kind,
+        target: Option<&str>,
+    ) -> CargoResult<Option<&mut Table>> {
+        let mut table = self.doc.as_table_mut();
+        for key in table_path(kind, target) {
+            if !table.contains_key(&key) {
+                return Ok(None);
+            }
+            table = match table.entry(&key_repr(&key)).as_table_mut() {
+                Some(table) => table,
+                None => failure::bail!("`{}` in the manifest is not a table", key),
+            };
+        }
+        Ok(Some(table))
+    }
+
+    /// Returns whether any of the dependency tables still declares `name`.
+    fn declares_dependency(&self, name: &str) -> bool {
+        let root = self.doc.as_table();
+        let declares = |table: &Table| {
+            DEP_TABLES.iter().any(|key| match table.get(key) {
+                Some(&Item::Table(ref deps)) => deps.contains_key(name),
+                _ => false,
+            })
+        };
+        if declares(root) {
+            return true;
+        }
+        match root.get("target") {
+            Some(&Item::Table(ref targets)) => targets.iter().any(|(_, item)| match *item {
+                Item::Table(ref t) => declares(t),
+                _ => false,
+            }),
+            _ => false,
+        }
+    }
+
+    /// Removes every mention of the dependency `name` from the `[features]`
+    /// table.
+    fn remove_from_features(&mut self, name: &str) {
+        let root = self.doc.as_table_mut();
+        if !root.contains_key("features") {
+            return;
+        }
+        let features = match root.entry("features").as_table_mut() {
+            Some(features) => features,
+            None => return,
+        };
+        let keys = features
+            .iter()
+            .map(|(key, _)| key.to_string())
+            .collect::<Vec<_>>();
+        for key in keys {
+            let item = features.entry(&key_repr(&key));
+            let values = match item.as_value().and_then(|v| v.as_array()) {
+                Some(values) => values,
+                None => continue,
+            };
+            let mentions = |value: &str| {
+                value == name
+                    || value == format!("dep:{}", name)
+                    || value.starts_with(&format!("{}/", name))
+                    || value.starts_with(&format!("{}?/", name))
+            };
+            if !values.iter().any(|v| v.as_str().map_or(false, &mentions)) {
+                continue;
+            }
+            let mut kept = Array::default();
+            for value in values.iter() {
+                if let Some(value) = value.as_str() {
+                    if !mentions(value) {
+                        kept.push(value);
+                    }
+                }
+            }
+            *item = toml_edit::value(kept);
+        }
+    }
+
+    /// Writes the edited manifest back to disk, restoring the original one
+    /// if the result can't be loaded.
+    pub(super) fn write(&self, config: &Config, pkg: &Package) -> CargoResult<()> {
+        paths::write(&self.path, self.doc.to_string().as_bytes())?;
+        let result = read_manifest(&self.path, pkg.package_id().source_id(), config);
+        if result.is_err() {
+            paths::write(&self.path, self.original.as_bytes())?;
+        }
+        result.chain_err(|| "the edited manifest is invalid, so it was left unchanged")?;
+        Ok(())
+    }
+}
+
+const DEP_TABLES: &[&str] = &["dependencies", "dev-dependencies", "build-dependencies"];
+
+fn table_path(kind: Kind, target: Option<&str>) -> Vec<String> {
+    let deps = match kind {
+        Kind::Normal => DEP_TABLES[0],
+        Kind::Development => DEP_TABLES[1],
+        Kind::Build => DEP_TABLES[2],
+    };
+    match target {
+        Some(target) => vec!["target".to_string(), target.to_string(), deps.to_string()],
+        None => vec![deps.to_string()],
+    }
+}
+
+/// Returns the name of a dependency table as used in messages.
+pub(super) fn table_name(kind: Kind, target: Option<&str>) -> String {
+    table_path(kind, target)
+        .iter()
+        .map(|key| key_repr(key))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Returns `key` as it must be written in TOML, quoting it unless it's a
+/// bare key.
+fn key_repr(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if bare {
+        key.to_string()
+    } else {
+        format!("'{}'", key)
+    }
+}
+
+/// Splits a `name@requirement` argument.
+fn parse_spec(spec: &str) -> CargoResult<(&str, Option<&str>)> {
+    let mut parts = spec.splitn(2, '@');
+    let name = parts.next().unwrap();
+    let req = parts.next();
+    if let Some(req) = req {
+        if let Err(e) = VersionReq::parse(req) {
+            failure::bail!(
+                "the version requirement `{}` of `{}` is invalid: {}",
+                req,
+                name,
+                e
+            );
+        }
+    }
+    Ok((name, req))
+}
+
+/// Reads the package at `path`, used when `cargo add --path` isn't given a
+/// name.
+fn path_package(config: &Config, path: &Path) -> CargoResult<Package> {
+    let path = config.cwd().join(path);
+    let source_id = SourceId::for_path(&path)?;
+    let (pkg, _) = ops::read_package(&path.join("Cargo.toml"), source_id, config)?;
+    Ok(pkg)
+}
+
+/// Finds the newest version of `name` in a registry, looking at the cached
+/// index when the registry can't be updated. Versions which can be built
+/// with the package's `rust-version` are preferred, as are releases over
+/// pre-releases.
+fn latest_version(
+    config: &Config,
+    source_id: SourceId,
+    name: &str,
+    rust_version: Option<&Version>,
+) -> CargoResult<Version> {
+    let map = SourceConfigMap::new(config)?;
+    let mut source = map.load(source_id, &HashSet::new())?;
+    if !config.cli_unstable().offline {
+        if let Err(e) = source.update() {
+            config.shell().warn(format!(
+                "failed to update {}, using the cached index instead: {}",
+                source_id, e
+            ))?;
+        }
+    }
+
+    let dep = Dependency::parse_no_deprecated(name, None, source_id)?;
+    let summaries = source.query_vec(&dep)?;
+    let compatible = |s: &&Summary| match (rust_version, s.rust_version()) {
+        (Some(ours), Some(theirs)) => theirs <= ours,
+        _ => true,
+    };
+    let newest = |releases_only: bool, compatible_only: bool| {
+        summaries
+            .iter()
+            .filter(|s| !releases_only || !s.version().is_prerelease())
+            .filter(|s| !compatible_only || compatible(s))
+            .map(|s| s.version())
+            .max()
+            .cloned()
+    };
+    newest(true, true)
+        .or_else(|| newest(true, false))
+        .or_else(|| newest(false, true))
+        .or_else(|| newest(false, false))
+        .ok_or_else(|| {
+            failure::format_err!(
+                "no versions of `{}` were found in {}",
+                name,
+                source.describe()
+            )
+        })
+}
+
+/// Sets `fields` on the dependency `item`, adding `features` to the ones it
+/// already has. If `item` is a new dependency, it's written as a plain
+/// version requirement when possible. `source_given` means the source of the
+/// dependency has been given, replacing its current one.
+fn set_dependency(
+    item: &mut Item,
+    fields: &[(&str, Value)],
+    features: &[String],
+    source_given: bool,
+) {
+    let plain = match *item {
+        Item::None | Item::Value(Value::String(_)) => true,
+        _ => false,
+    };
+    if plain && features.is_empty() && fields.len() == 1 && fields[0].0 == "version" {
+        *item = Item::Value(fields[0].1.clone());
+        return;
+    }
+
+    // A dependency which is just a version requirement needs to become a
+    // table to hold anything else.
+    let version = match *item {
+        Item::Value(Value::String(_)) => item
+            .as_value()
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+        _ => None,
+    };
+    if item.is_none() || version.is_some() {
+        let mut table = InlineTable::default();
+        if let Some(version) = version {
+            table.get_or_insert("version", version);
+        }
+        *item = Item::Value(Value::InlineTable(table));
+    }
+
+    if source_given {
+        for key in &["path", "git", "branch", "tag", "rev", "registry"] {
+            remove_field(item, key);
+        }
+    }
+    for &(key, ref value) in fields {
+        set_field(item, key, value.clone());
+    }
+    if !features.is_empty() {
+        let mut all = Vec::new();
+        if let Some(existing) = get_field(item, "features").and_then(|v| v.as_array()) {
+            all.extend(
+                existing
+                    .iter()
+                    .filter_map(|v| v.as_str())
+                    .map(|s| s.to_string()),
+            );
+        }
+        for feature in features {
+            if !all.contains(feature) {
+                all.push(feature.clone());
+            }
+        }
+        let mut array = Array::default();
+        for feature in all {
+            array.push(feature);
+        }
+        set_field(item, "features", Value::Array(array));
+    }
+    if let Item::Value(Value::InlineTable(ref mut table)) = *item {
+        table.fmt();
+    }
+}
+
+pub(super) fn get_field<'a>(item: &'a Item, key: &str) -> Option<&'a Value> {
+    match *item {
+        Item::Table(ref table) => table.get(key).and_then(|i| i.as_value()),
+        Item::Value(Value::InlineTable(ref table)) => table.get(key),
+        _ => None,
+    }
+}
+
+fn set_field(item: &mut Item, key: &str, value: Value) {
+    match *item {
+        Item::Table(_) | Item::Value(Value::InlineTable(_)) => item[key] = toml_edit::value(value),
+        _ => {}
+    }
+}
+
+fn remove_field(item: &mut Item, key: &str) {
+    match *item {
+        Item::Table(ref mut table) => {
+            table.remove(key);
+        }
+        Item::Value(Value::InlineTable(ref mut table)) => {
+            table.remove(key);
+        }
+        _ => {}
+    }
+}
+
+/// Replaces `value` with `new`, keeping the whitespace and comments around
+/// it.
+pub(super) fn replace_value(value: &mut Value, new: Value) {
+    let new = toml_edit::decorated(new, value.decor().prefix(), value.decor().suffix());
+    *value = new;
+}
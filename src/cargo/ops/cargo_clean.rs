@@ -4,6 +4,7 @@ use std::path::Path;
 
 use crate::core::compiler::{BuildConfig, BuildContext, CompileMode, Context, Kind, Unit};
 use crate::core::profiles::UnitFor;
+use crate::core::resolver::FeaturesFor;
 use crate::core::Workspace;
 use crate::ops;
 use crate::util::errors::{CargoResult, CargoResultExt};
@@ -85,6 +86,7 @@ pub fn clean(ws: &Workspace<'_>, opts: &CleanOptions<'_>) -> CargoResult<()> {
                             profile,
                             kind: *kind,
                             mode: *mode,
+                            features_for: FeaturesFor::Normal,
                         });
                     }
                 }
@@ -29,8 +29,11 @@ use crate::core::compiler::{
     BuildConfig, BuildContext, Compilation, Context, DefaultExecutor, Executor,
 };
 use crate::core::compiler::{CompileMode, Kind, Unit};
+use crate::core::dependency::Platform;
 use crate::core::profiles::{Profiles, UnitFor};
-use crate::core::resolver::{Method, Resolve};
+use crate::core::resolver::{
+    resolve_features, FeaturesFor, Method, Resolve, ResolveBehavior, ResolvedFeatures,
+};
 use crate::core::{Package, Source, Target};
 use crate::core::{PackageId, PackageIdSpec, TargetKind, Workspace};
 use crate::ops;
@@ -299,8 +302,9 @@ pub fn compile_ws<'a>(
 
     let specs = spec.to_package_id_specs(ws)?;
     let features = Method::split_features(features);
+    let need_dev_deps = filter.need_dev_deps(build_config.mode);
     let method = Method::Required {
-        dev_deps: ws.require_optional_deps() || filter.need_dev_deps(build_config.mode),
+        dev_deps: ws.require_optional_deps() || need_dev_deps,
         features: &features,
         all_features,
         uses_default_features: !no_default_features,
@@ -312,7 +316,7 @@ pub fn compile_ws<'a>(
         .iter()
         .map(|s| s.query(resolve_with_overrides.iter()))
         .collect::<CargoResult<Vec<_>>>()?;
-    let mut to_builds = packages.get_many(to_build_ids)?;
+    let mut to_builds = packages.get_many(to_build_ids.iter().cloned())?;
 
     // The ordering here affects some error messages coming out of cargo, so
     // let's be test and CLI friendly by always printing in the same order if
@@ -350,6 +354,42 @@ pub fn compile_ws<'a>(
     let profiles = ws.profiles();
     profiles.validate_packages(&mut config.shell(), &packages)?;
 
+    let mut bcx = BuildContext::new(
+        ws,
+        &resolve_with_overrides,
+        &packages,
+        config,
+        build_config,
+        profiles,
+        HashMap::new(),
+    )?;
+    if ws.resolve_behavior() == ResolveBehavior::V2 {
+        // Unlike the resolve above, dev-dependencies only count when
+        // something using them is built.
+        let method = Method::Required {
+            dev_deps: need_dev_deps,
+            features: &features,
+            all_features,
+            uses_default_features: !no_default_features,
+        };
+        let platform_activated = |platform: Option<&Platform>, features_for| {
+            let kind = match features_for {
+                FeaturesFor::Normal => Kind::Target,
+                FeaturesFor::Host => Kind::Host,
+            };
+            bcx.platform_activated(platform, kind)
+        };
+        let resolved = resolve_features(
+            ws,
+            &resolve_with_overrides,
+            &packages,
+            &to_build_ids,
+            method,
+            &platform_activated,
+        )?;
+        bcx.features = Some(resolved);
+    }
+
     let units = generate_targets(
         ws,
         profiles,
@@ -357,6 +397,7 @@ pub fn compile_ws<'a>(
         filter,
         default_arch_kind,
         &resolve_with_overrides,
+        bcx.features.as_ref(),
         build_config,
     )?;
 
@@ -380,17 +421,10 @@ pub fn compile_ws<'a>(
         }
     }
 
+    bcx.extra_compiler_args = extra_compiler_args;
+
     let ret = {
         let _p = profile::start("compiling");
-        let bcx = BuildContext::new(
-            ws,
-            &resolve_with_overrides,
-            &packages,
-            config,
-            build_config,
-            profiles,
-            extra_compiler_args,
-        )?;
         let cx = Context::new(config, &bcx)?;
         cx.compile(&units, export_dir.clone(), exec)?
     };
@@ -582,6 +616,7 @@ fn generate_targets<'a>(
     filter: &CompileFilter,
     default_arch_kind: Kind,
     resolve: &Resolve,
+    resolved_features: Option<&ResolvedFeatures>,
     build_config: &BuildConfig,
 ) -> CargoResult<Vec<Unit<'a>>> {
     // Helper for creating a `Unit` struct.
@@ -659,6 +694,8 @@ fn generate_targets<'a>(
             profile,
             kind,
             mode: target_mode,
+            // The packages being built always use their normal features.
+            features_for: FeaturesFor::Normal,
         }
     };
 
@@ -796,9 +833,9 @@ fn generate_targets<'a>(
     {
         let unavailable_features = match target.required_features() {
             Some(rf) => {
-                let features = features_map
-                    .entry(pkg)
-                    .or_insert_with(|| resolve_all_features(resolve, pkg.package_id()));
+                let features = features_map.entry(pkg).or_insert_with(|| {
+                    resolve_all_features(resolve, resolved_features, pkg.package_id())
+                });
                 rf.iter().filter(|f| !features.contains(*f)).collect()
             }
             None => Vec::new(),
@@ -828,15 +865,32 @@ fn generate_targets<'a>(
 
 fn resolve_all_features(
     resolve_with_overrides: &Resolve,
+    resolved_features: Option<&ResolvedFeatures>,
     package_id: PackageId,
 ) -> HashSet<String> {
-    let mut features = resolve_with_overrides.features(package_id).clone();
+    // With `resolver = "2"` the features a target is built with can differ
+    // from the unified ones in the resolve, so check against those instead.
+    let activated = |pkg_id: PackageId, features_for: FeaturesFor| -> HashSet<String> {
+        match resolved_features {
+            Some(r) => r
+                .activated(pkg_id, features_for)
+                .into_iter()
+                .map(|f| f.to_string())
+                .collect(),
+            None => resolve_with_overrides.features(pkg_id).clone(),
+        }
+    };
+    let mut features = activated(package_id, FeaturesFor::Normal);
 
     // Include features enabled for use by dependencies so targets can also use them with the
     // required-features field when deciding whether to be built or skipped.
-    for (dep, _) in resolve_with_overrides.deps(package_id) {
-        for feature in resolve_with_overrides.features(dep) {
-            features.insert(dep.name().to_string() + "/" + feature);
+    for (dep, deps) in resolve_with_overrides.deps(package_id) {
+        let features_for = match resolved_features {
+            Some(r) => r.features_for(dep, deps.iter().all(|d| d.is_build())),
+            None => FeaturesFor::Normal,
+        };
+        for feature in activated(dep, features_for) {
+            features.insert(dep.name().to_string() + "/" + &feature);
         }
     }
 
@@ -1,15 +1,17 @@
 use std::collections::{BTreeMap, HashSet};
 
 use log::debug;
+use serde::Serialize;
 use termcolor::Color::{self, Cyan, Green, Red};
 
 use crate::core::registry::PackageRegistry;
 use crate::core::resolver::Method;
-use crate::core::PackageId;
+use crate::core::{Dependency, PackageId};
 use crate::core::{Resolve, SourceId, Workspace};
 use crate::ops;
+use crate::print_json;
 use crate::util::config::Config;
-use crate::util::CargoResult;
+use crate::util::{semver_compatibility, CargoResult};
 
 pub struct UpdateOptions<'a> {
     pub config: &'a Config,
@@ -17,11 +19,24 @@ pub struct UpdateOptions<'a> {
     pub precise: Option<&'a str>,
     pub aggressive: bool,
     pub dry_run: bool,
+    /// Print the changes to the lock file as JSON rather than as text.
+    pub json: bool,
 }
 
 pub fn generate_lockfile(ws: &Workspace<'_>) -> CargoResult<()> {
+    let resolve = resolve_from_scratch(ws)?;
+    if let Some(ordering) = VersionOrdering::from_config(ws.config()) {
+        ws.config()
+            .shell()
+            .status("Resolved", ordering.describe())?;
+    }
+    ops::write_pkg_lockfile(ws, &resolve)?;
+    Ok(())
+}
+
+fn resolve_from_scratch(ws: &Workspace<'_>) -> CargoResult<Resolve> {
     let mut registry = PackageRegistry::new(ws.config())?;
-    let resolve = ops::resolve_with_previous(
+    ops::resolve_with_previous(
         &mut registry,
         ws,
         Method::Everything,
@@ -30,9 +45,7 @@ pub fn generate_lockfile(ws: &Workspace<'_>) -> CargoResult<()> {
         &[],
         true,
         true,
-    )?;
-    ops::write_pkg_lockfile(ws, &resolve)?;
-    Ok(())
+    )
 }
 
 pub fn update_lockfile(ws: &Workspace<'_>, opts: &UpdateOptions<'_>) -> CargoResult<()> {
@@ -50,6 +63,15 @@ pub fn update_lockfile(ws: &Workspace<'_>, opts: &UpdateOptions<'_>) -> CargoRes
 
     let previous_resolve = match ops::load_pkg_lockfile(ws)? {
         Some(resolve) => resolve,
+        None if opts.dry_run => {
+            // Everything would be added to the new lock file.
+            let resolve = resolve_from_scratch(ws)?;
+            report_changes(opts, compare_dependency_graphs(None, &resolve))?;
+            opts.config
+                .shell()
+                .warn("not updating lockfile due to dry run")?;
+            return Ok(());
+        }
         None => return generate_lockfile(ws),
     };
     let mut registry = PackageRegistry::new(opts.config)?;
@@ -95,31 +117,9 @@ pub fn update_lockfile(ws: &Workspace<'_>, opts: &UpdateOptions<'_>) -> CargoRes
         true,
     )?;
 
-    // Summarize what is changing for the user.
-    let print_change = |status: &str, msg: String, color: Color| {
-        opts.config.shell().status_with_color(status, msg, color)
-    };
-    for (removed, added) in compare_dependency_graphs(&previous_resolve, &resolve) {
-        if removed.len() == 1 && added.len() == 1 {
-            let msg = if removed[0].source_id().is_git() {
-                format!(
-                    "{} -> #{}",
-                    removed[0],
-                    &added[0].source_id().precise().unwrap()[..8]
-                )
-            } else {
-                format!("{} -> v{}", removed[0], added[0].version())
-            };
-            print_change("Updating", msg, Green)?;
-        } else {
-            for package in removed.iter() {
-                print_change("Removing", format!("{}", package), Red)?;
-            }
-            for package in added.iter() {
-                print_change("Adding", format!("{}", package), Cyan)?;
-            }
-        }
-    }
+    let changes = compare_dependency_graphs(Some(&previous_resolve), &resolve);
+    report_changes(opts, changes)?;
+    report_yanked(opts, &mut registry, &resolve)?;
     if opts.dry_run {
         opts.config
             .shell()
@@ -145,7 +145,7 @@ pub fn update_lockfile(ws: &Workspace<'_>, opts: &UpdateOptions<'_>) -> CargoRes
     }
 
     fn compare_dependency_graphs(
-        previous_resolve: &Resolve,
+        previous_resolve: Option<&Resolve>,
         resolve: &Resolve,
     ) -> Vec<(Vec<PackageId>, Vec<PackageId>)> {
         fn key(dep: PackageId) -> (&'static str, SourceId) {
@@ -189,7 +189,7 @@ pub fn update_lockfile(ws: &Workspace<'_>, opts: &UpdateOptions<'_>) -> CargoRes
         // Map `(package name, package source)` to `(removed versions, added versions)`.
         let mut changes = BTreeMap::new();
         let empty = (Vec::new(), Vec::new());
-        for dep in previous_resolve.iter() {
+        for dep in previous_resolve.into_iter().flat_map(|r| r.iter()) {
             changes
                 .entry(key(dep))
                 .or_insert_with(|| empty.clone())
@@ -218,3 +218,198 @@ pub fn update_lockfile(ws: &Workspace<'_>, opts: &UpdateOptions<'_>) -> CargoRes
         changes.into_iter().map(|(_, v)| v).collect()
     }
 }
+
+/// How a package changes in the lock file.
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum ChangeKind {
+    Added,
+    Removed,
+    Upgraded,
+    Downgraded,
+    /// The version stays the same, but comes from another revision of a git
+    /// repository.
+    Updated,
+}
+
+#[derive(Serialize)]
+struct PackageChange {
+    name: String,
+    source: SourceId,
+    kind: ChangeKind,
+    old_version: Option<String>,
+    new_version: Option<String>,
+    /// Whether the new version is semver compatible with the old one, only
+    /// set for upgrades and downgrades.
+    compatible: Option<bool>,
+}
+
+impl PackageChange {
+    fn new(
+        kind: ChangeKind,
+        old: Option<PackageId>,
+        new: Option<PackageId>,
+        compatible: Option<bool>,
+    ) -> PackageChange {
+        let id = new.or(old).unwrap();
+        PackageChange {
+            name: id.name().to_string(),
+            source: id.source_id(),
+            kind,
+            old_version: old.map(|id| id.version().to_string()),
+            new_version: new.map(|id| id.version().to_string()),
+            compatible,
+        }
+    }
+}
+
+/// How the versions were picked, if not the newest ones.
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+enum VersionOrdering {
+    /// `-Z minimal-versions`
+    Minimal,
+    /// `-Z direct-minimal-versions`
+    DirectMinimal,
+}
+
+impl VersionOrdering {
+    fn from_config(config: &Config) -> Option<VersionOrdering> {
+        if config.cli_unstable().minimal_versions {
+            Some(VersionOrdering::Minimal)
+        } else if config.cli_unstable().direct_minimal_versions {
+            Some(VersionOrdering::DirectMinimal)
+        } else {
+            None
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            VersionOrdering::Minimal => "dependencies to their minimal versions",
+            VersionOrdering::DirectMinimal => {
+                "direct dependencies of the workspace members to their minimal versions, \
+                 and the others to their newest versions"
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct UpdateReport {
+    changes: Vec<PackageChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version_ordering: Option<VersionOrdering>,
+}
+
+/// Reports the packages which are added to, removed from or changed in the
+/// lock file, as status messages or as JSON.
+fn report_changes(
+    opts: &UpdateOptions<'_>,
+    changes: Vec<(Vec<PackageId>, Vec<PackageId>)>,
+) -> CargoResult<()> {
+    let print_change = |status: &str, msg: String, color: Color| {
+        if opts.json {
+            return Ok(());
+        }
+        opts.config.shell().status_with_color(status, msg, color)
+    };
+    let mut report = Vec::new();
+    for (removed, added) in changes {
+        if removed.len() == 1 && added.len() == 1 {
+            let (old, new) = (removed[0], added[0]);
+            let compatible =
+                semver_compatibility(old.version()) == semver_compatibility(new.version());
+            let (kind, compatible) = if old.version() < new.version() {
+                (ChangeKind::Upgraded, Some(compatible))
+            } else if old.version() > new.version() {
+                (ChangeKind::Downgraded, Some(compatible))
+            } else {
+                (ChangeKind::Updated, None)
+            };
+            let mut msg = if old.source_id().is_git() {
+                format!("{} -> #{}", old, &new.source_id().precise().unwrap()[..8])
+            } else {
+                format!("{} -> v{}", old, new.version())
+            };
+            if opts.dry_run && compatible == Some(false) {
+                msg.push_str(" (semver-breaking)");
+            }
+            print_change("Updating", msg, Green)?;
+            report.push(PackageChange::new(kind, Some(old), Some(new), compatible));
+        } else {
+            for package in removed {
+                print_change("Removing", format!("{}", package), Red)?;
+                report.push(PackageChange::new(
+                    ChangeKind::Removed,
+                    Some(package),
+                    None,
+                    None,
+                ));
+            }
+            for package in added {
+                print_change("Adding", format!("{}", package), Cyan)?;
+                report.push(PackageChange::new(
+                    ChangeKind::Added,
+                    None,
+                    Some(package),
+                    None,
+                ));
+            }
+        }
+    }
+    let version_ordering = VersionOrdering::from_config(opts.config);
+    if opts.json {
+        print_json(&UpdateReport {
+            changes: report,
+            version_ordering,
+        });
+    } else if let Some(ordering) = version_ordering {
+        opts.config
+            .shell()
+            .status("Resolved", ordering.describe())?;
+    }
+    Ok(())
+}
+
+/// Warns about the yanked packages which stay in the lock file, such as the
+/// ones which weren't part of a partial update, naming the version updating
+/// them would pick.
+fn report_yanked(
+    opts: &UpdateOptions<'_>,
+    registry: &mut PackageRegistry<'_>,
+    resolve: &Resolve,
+) -> CargoResult<()> {
+    let mut pkgids = resolve.iter().collect::<Vec<_>>();
+    pkgids.sort();
+    for pkgid in pkgids {
+        if !pkgid.source_id().is_registry() || !registry.is_yanked(pkgid)? {
+            continue;
+        }
+        let req = format!("^{}", pkgid.version());
+        let dep = Dependency::parse_no_deprecated(&pkgid.name(), Some(&req), pkgid.source_id())?;
+        let mut newest: Option<PackageId> = None;
+        for summary in registry.query_loaded(&dep)? {
+            let id = summary.package_id();
+            if !registry.is_yanked(id)? && newest.map_or(true, |n| n.version() < id.version()) {
+                newest = Some(id);
+            }
+        }
+        let msg = match newest {
+            Some(newest) => format!(
+                "package `{}` in Cargo.lock has been yanked from its registry, \
+                 `cargo update -p {}` would update it to v{}",
+                pkgid,
+                pkgid.name(),
+                newest.version()
+            ),
+            None => format!(
+                "package `{}` in Cargo.lock has been yanked from its registry, \
+                 and no semver compatible version is available to update it to",
+                pkgid
+            ),
+        };
+        opts.config.shell().warn(msg)?;
+    }
+    Ok(())
+}
@@ -1,15 +1,36 @@
 use std::iter;
 use std::path::Path;
 
+use crate::core::compiler::Compilation;
 use crate::core::{nightly_features_allowed, TargetKind, Workspace};
 use crate::ops;
-use crate::util::{CargoResult, ProcessError};
+use crate::util::{CargoResult, ProcessBuilder, ProcessError};
 
 pub fn run(
     ws: &Workspace<'_>,
     options: &ops::CompileOptions<'_>,
     args: &[String],
 ) -> CargoResult<Option<ProcessError>> {
+    let (_, process) = compile_run(ws, options, args)?;
+
+    let result = process.exec_replace();
+
+    match result {
+        Ok(()) => Ok(None),
+        Err(e) => {
+            let err = e.downcast::<ProcessError>()?;
+            Ok(Some(err))
+        }
+    }
+}
+
+/// Builds the binary `cargo run` would run, returning the compilation and
+/// the process to run it with, without starting it.
+pub fn compile_run<'a>(
+    ws: &Workspace<'a>,
+    options: &ops::CompileOptions<'a>,
+    args: &[String],
+) -> CargoResult<(Compilation<'a>, ProcessBuilder)> {
     let config = ws.config();
 
     // We compute the `bins` here *just for diagnosis*. The actual set of
@@ -92,13 +113,5 @@ pub fn run(
 
     config.shell().status("Running", process.to_string())?;
 
-    let result = process.exec_replace();
-
-    match result {
-        Ok(()) => Ok(None),
-        Err(e) => {
-            let err = e.downcast::<ProcessError>()?;
-            Ok(Some(err))
-        }
-    }
+    Ok((compile, process))
 }
@@ -1,16 +1,118 @@
 use std::ffi::OsString;
+use std::fmt;
+use std::hash::Hash;
+use std::mem;
+use std::time::{Duration, Instant};
 
 use crate::core::compiler::{Compilation, Doctest};
 use crate::core::shell::Verbosity;
 use crate::core::Workspace;
 use crate::ops;
+use crate::ops::bench_baseline::{self, BaselineOptions};
+use crate::ops::test_results::{
+    BinaryResults, FailedTest, LibtestParser, TestBinarySummary, TestReport, TestResults,
+    TestSummary,
+};
 use crate::util::errors::CargoResult;
-use crate::util::{CargoTestError, ProcessError, Test};
+use crate::util::{self, machine_message};
+use crate::util::{hash_u64, CargoTestError, Config, ProcessBuilder, ProcessError, Test};
 
 pub struct TestOptions<'a> {
     pub compile_opts: ops::CompileOptions<'a>,
     pub no_run: bool,
     pub no_fail_fast: bool,
+    /// Reports to write once the tests have run, see `--report`.
+    pub reports: Vec<TestReport>,
+    /// Only run the tests belonging to this shard, see `--shard`.
+    pub shard: Option<TestShard>,
+    /// How many times failed tests are rerun before they count as failures,
+    /// see `--retries`.
+    pub retries: u32,
+    /// How long a test binary may run for before it is killed, see
+    /// `--test-timeout`.
+    pub timeout: Option<Duration>,
+    /// Whether to print a summary of the results of all test binaries, see
+    /// `--summary`.
+    pub summary: bool,
+    /// Which benchmark baselines to save or compare with, see
+    /// `--save-baseline` and `--baseline`.
+    pub baselines: BaselineOptions,
+}
+
+impl<'a> TestOptions<'a> {
+    /// Whether the output of the test binaries needs to be parsed.
+    fn needs_results(&self) -> bool {
+        !self.reports.is_empty()
+            || self.retries > 0
+            || self.timeout.is_some()
+            || self.summary
+            || !self.baselines.is_empty()
+    }
+}
+
+/// Parses a test timeout, either a number of seconds or a number followed by
+/// one of the `s`, `m` or `h` units, as in `90s` or `10m`.
+pub fn parse_test_timeout(s: &str) -> CargoResult<Duration> {
+    let s = s.trim();
+    let (number, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => (&s[..i], s[i..].trim()),
+        None => (s, "s"),
+    };
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        _ => 0,
+    };
+    match number.parse::<u64>() {
+        Ok(n) if n > 0 && multiplier > 0 => Ok(Duration::from_secs(n * multiplier)),
+        _ => failure::bail!(
+            "invalid test timeout `{}`, expected a duration such as `90s`, `10m` or `1h`",
+            s
+        ),
+    }
+}
+
+/// One of `count` disjoint subsets of the tests, selected with `--shard K/N`.
+///
+/// Each test is assigned to a shard based on a hash of its package, target
+/// and name, so the assignment of a test never changes when other tests are
+/// added or removed. Binaries which can't list their tests (those with
+/// `harness = false`) are assigned as a whole.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TestShard {
+    /// Zero-based index of the shard.
+    index: u64,
+    count: u64,
+}
+
+impl TestShard {
+    /// Parses the one-based `K/N` form used on the command line.
+    pub fn parse(s: &str) -> CargoResult<TestShard> {
+        let mut parts = s.splitn(2, '/');
+        let index = parts.next().and_then(|k| k.trim().parse::<u64>().ok());
+        let count = parts.next().and_then(|n| n.trim().parse::<u64>().ok());
+        match (index, count) {
+            (Some(index), Some(count)) if index >= 1 && index <= count => Ok(TestShard {
+                index: index - 1,
+                count,
+            }),
+            _ => failure::bail!(
+                "invalid shard `{}`, expected `K/N` where 1 <= K <= N, for example `1/4`",
+                s
+            ),
+        }
+    }
+
+    fn contains<K: Hash>(&self, key: K) -> bool {
+        hash_u64(key) % self.count == self.index
+    }
+}
+
+impl fmt::Display for TestShard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index + 1, self.count)
+    }
 }
 
 pub fn run_tests(
@@ -19,18 +121,175 @@ pub fn run_tests(
     test_args: &[&str],
 ) -> CargoResult<Option<CargoTestError>> {
     let compilation = compile_tests(ws, options)?;
+    run_compiled_tests(options, test_args, &compilation)
+}
 
+/// Runs the tests built by `compile_tests`.
+pub fn run_compiled_tests(
+    options: &TestOptions<'_>,
+    test_args: &[&str],
+    compilation: &Compilation<'_>,
+) -> CargoResult<Option<CargoTestError>> {
     if options.no_run {
         return Ok(None);
     }
-    let (test, mut errors) = run_unit_tests(options, test_args, &compilation)?;
+    let mut results = TestResults::new();
+    let err = run_all_tests(options, test_args, compilation, &mut results)?;
+    report_flaky(options, &results)?;
+    if options.summary {
+        report_summary(options, &results)?;
+    }
+    for report in options.reports.iter() {
+        results.write_report(report)?;
+    }
+    Ok(err)
+}
+
+/// Tells the user about the tests which only passed after being retried.
+fn report_flaky(options: &TestOptions<'_>, results: &TestResults) -> CargoResult<()> {
+    let flaky = results.flaky().collect::<Vec<_>>();
+    if flaky.is_empty() {
+        return Ok(());
+    }
+    if options.compile_opts.build_config.json_messages() {
+        for (binary, case) in flaky.iter() {
+            machine_message::emit(&machine_message::FlakyTest {
+                package_id: binary.package_id,
+                target: &binary.target_name,
+                name: &case.name,
+                attempts: case.failed_attempts.len() + 1,
+            });
+        }
+    }
+    let mut msg = format!(
+        "{} test{} failed at first but passed when retried:",
+        flaky.len(),
+        if flaky.len() == 1 { "" } else { "s" }
+    );
+    for (binary, case) in flaky.iter() {
+        msg.push_str(&format!(
+            "\n    flaky: {} {} (passed on attempt {})",
+            binary.display_name(),
+            case.name,
+            case.failed_attempts.len() + 1
+        ));
+    }
+    options.compile_opts.config.shell().warn(msg)
+}
+
+/// Prints the results of every test binary, the totals and the failed tests.
+fn report_summary(options: &TestOptions<'_>, results: &TestResults) -> CargoResult<()> {
+    if options.compile_opts.build_config.json_messages() {
+        machine_message::emit(&TestSummary {
+            binaries: results
+                .binaries
+                .iter()
+                .map(|binary| TestBinarySummary {
+                    package_id: binary.package_id,
+                    target: &binary.target_name,
+                    binary: &binary.binary,
+                    counts: binary.counts(),
+                    duration: secs(binary.duration),
+                    error: binary.error.as_ref().map(|e| e.as_str()),
+                })
+                .collect(),
+            totals: results.totals(),
+            duration: secs(results.duration()),
+            failures: results
+                .failures()
+                .flat_map(|(binary, tests)| {
+                    let tests = if tests.is_empty() {
+                        vec![None]
+                    } else {
+                        tests.into_iter().map(Some).collect()
+                    };
+                    tests.into_iter().map(move |name| FailedTest {
+                        package_id: binary.package_id,
+                        target: &binary.target_name,
+                        name,
+                    })
+                })
+                .collect(),
+        });
+    }
+
+    let mut shell = options.compile_opts.config.shell();
+    shell.status(
+        "Summary",
+        format!(
+            "{} test binar{}: {}; finished in {}",
+            results.binaries.len(),
+            if results.binaries.len() == 1 {
+                "y"
+            } else {
+                "ies"
+            },
+            results.totals(),
+            util::elapsed(results.duration())
+        ),
+    )?;
+    let width = results
+        .binaries
+        .iter()
+        .map(|b| b.display_name().len() + b.binary.len() + 3)
+        .max()
+        .unwrap_or(0);
+    for binary in results.binaries.iter() {
+        let name = format!("{} ({})", binary.display_name(), binary.binary);
+        let status = if binary.error.is_some() {
+            "FAILED"
+        } else {
+            "ok"
+        };
+        writeln!(
+            shell.err(),
+            "    {:width$}  {:6} {}; {}",
+            name,
+            status,
+            binary.counts(),
+            util::elapsed(binary.duration),
+            width = width
+        )?;
+    }
+
+    let failures = results.failures().collect::<Vec<_>>();
+    if !failures.is_empty() {
+        writeln!(shell.err(), "\nfailures:")?;
+        for (binary, tests) in failures {
+            if tests.is_empty() {
+                writeln!(
+                    shell.err(),
+                    "    {} ({})",
+                    binary.display_name(),
+                    binary.binary
+                )?;
+            }
+            for test in tests {
+                writeln!(shell.err(), "    {} {}", binary.display_name(), test)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn secs(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) / 1e9
+}
+
+fn run_all_tests(
+    options: &TestOptions<'_>,
+    test_args: &[&str],
+    compilation: &Compilation<'_>,
+    results: &mut TestResults,
+) -> CargoResult<Option<CargoTestError>> {
+    let (test, mut errors) = run_unit_tests(options, test_args, compilation, results)?;
 
     // If we have an error and want to fail fast, then return.
     if !errors.is_empty() && !options.no_fail_fast {
         return Ok(Some(CargoTestError::new(test, errors)));
     }
 
-    let (doctest, docerrors) = run_doc_tests(options, test_args, &compilation)?;
+    let (doctest, docerrors) = run_doc_tests(options, test_args, compilation, results)?;
     let test = if docerrors.is_empty() { test } else { doctest };
     errors.extend(docerrors);
     if errors.is_empty() {
@@ -54,15 +313,25 @@ pub fn run_benches(
     let mut args = args.to_vec();
     args.push("--bench");
 
-    let (test, errors) = run_unit_tests(options, &args, &compilation)?;
+    let mut results = TestResults::new();
+    let (test, errors) = run_unit_tests(options, &args, &compilation, &mut results)?;
 
-    match errors.len() {
-        0 => Ok(None),
-        _ => Ok(Some(CargoTestError::new(test, errors))),
+    if !errors.is_empty() {
+        return Ok(Some(CargoTestError::new(test, errors)));
+    }
+    let baselines = &options.baselines;
+    if let Some(name) = &baselines.compare {
+        let json_messages = options.compile_opts.build_config.json_messages();
+        bench_baseline::compare(ws, baselines, name, &results, json_messages)?;
     }
+    if let Some(name) = &baselines.save {
+        bench_baseline::save(ws, name, &results)?;
+    }
+    Ok(None)
 }
 
-fn compile_tests<'a>(
+/// Builds the tests, benchmarks and doctests selected by `options`.
+pub fn compile_tests<'a>(
     ws: &Workspace<'a>,
     options: &TestOptions<'a>,
 ) -> CargoResult<Compilation<'a>> {
@@ -78,6 +347,7 @@ fn run_unit_tests(
     options: &TestOptions<'_>,
     test_args: &[&str],
     compilation: &Compilation<'_>,
+    results: &mut TestResults,
 ) -> CargoResult<(Test, Vec<ProcessError>)> {
     let config = options.compile_opts.config;
     let cwd = options.compile_opts.config.cwd();
@@ -89,18 +359,46 @@ fn run_unit_tests(
         let test = target.name().to_string();
         let exe_display = exe.strip_prefix(cwd).unwrap_or(exe).display();
         let mut cmd = compilation.target_process(exe, pkg)?;
-        cmd.args(test_args);
         if target.harness() && config.shell().verbosity() == Verbosity::Quiet {
             cmd.arg("--quiet");
         }
+        if target.harness() {
+            color_arg(options, &mut cmd, test_args, false);
+        }
+        if let Some(timeout) = options.timeout {
+            cmd.timeout(timeout);
+        }
+        let mut retry = cmd.clone();
+        cmd.args(test_args);
+        if target.harness() {
+            retry.args(&without_filters(test_args));
+        } else {
+            retry.args(test_args);
+        }
+        let mut cmds = vec![cmd];
+        if let Some(shard) = &options.shard {
+            let key = (pkg.name().as_str(), kind.description(), target.name());
+            match select_shard(shard, &cmds[0], &retry, key, target.harness(), false)? {
+                Some(batches) => cmds = batches,
+                None => {
+                    config.shell().verbose(|shell| {
+                        shell.status("Skipping", format!("{} (shard {})", exe_display, shard))
+                    })?;
+                    continue;
+                }
+            }
+        }
         config
             .shell()
             .concise(|shell| shell.status("Running", &exe_display))?;
-        config
-            .shell()
-            .verbose(|shell| shell.status("Running", &cmd))?;
+        for cmd in cmds.iter() {
+            config
+                .shell()
+                .verbose(|shell| shell.status("Running", cmd))?;
+        }
 
-        let result = cmd.exec();
+        let binary = BinaryResults::new(pkg.package_id(), target.name(), &exe_display.to_string());
+        let result = run_test_binary(options, &cmds, &retry, false, binary, results);
 
         match result {
             Err(e) => {
@@ -136,6 +434,7 @@ fn run_doc_tests(
     options: &TestOptions<'_>,
     test_args: &[&str],
     compilation: &Compilation<'_>,
+    results: &mut TestResults,
 ) -> CargoResult<(Test, Vec<ProcessError>)> {
     let mut errors = Vec::new();
     let config = options.compile_opts.config;
@@ -151,7 +450,6 @@ fn run_doc_tests(
             target,
             deps,
         } = doctest_info;
-        config.shell().status("Doc-tests", target.name())?;
         let mut p = compilation.rustdoc_process(package, target)?;
         p.arg("--test")
             .arg(target.src_path().path().unwrap())
@@ -174,10 +472,6 @@ fn run_doc_tests(
             p.arg("-L").arg(arg);
         }
 
-        for arg in test_args {
-            p.arg("--test-args").arg(arg);
-        }
-
         if let Some(cfgs) = compilation.cfgs.get(&package.package_id()) {
             for cfg in cfgs.iter() {
                 p.arg("--cfg").arg(cfg);
@@ -195,10 +489,34 @@ fn run_doc_tests(
             p.args(flags);
         }
 
-        config
-            .shell()
-            .verbose(|shell| shell.status("Running", p.to_string()))?;
-        if let Err(e) = p.exec() {
+        color_arg(options, &mut p, test_args, true);
+        if let Some(timeout) = options.timeout {
+            p.timeout(timeout);
+        }
+        let mut retry = p.clone();
+        for arg in test_args {
+            p.arg("--test-args").arg(arg);
+        }
+        for arg in without_filters(test_args) {
+            retry.arg("--test-args").arg(arg);
+        }
+        let mut cmds = vec![p];
+        if let Some(shard) = &options.shard {
+            let key = (package.name().as_str(), "doctest", target.name());
+            match select_shard(shard, &cmds[0], &retry, key, true, true)? {
+                Some(batches) => cmds = batches,
+                None => continue,
+            }
+        }
+
+        config.shell().status("Doc-tests", target.name())?;
+        for p in cmds.iter() {
+            config
+                .shell()
+                .verbose(|shell| shell.status("Running", p.to_string()))?;
+        }
+        let binary = BinaryResults::new(package.package_id(), target.name(), "doctests");
+        if let Err(e) = run_test_binary(options, &cmds, &retry, true, binary, results) {
             let e = e.downcast::<ProcessError>()?;
             errors.push(e);
             if !options.no_fail_fast {
@@ -208,3 +526,294 @@ fn run_doc_tests(
     }
     Ok((Test::Doc, errors))
 }
+
+/// Finds the tests of `list` which belong to `shard` with libtest's
+/// `--list`, and returns the commands which run them with `cmd`.
+///
+/// `list` is the command which would run all the tests, and `cmd` the same
+/// command without the user's filters, which `--list` has already applied.
+/// Returns `None` if none of the tests belong to the shard, in which case the
+/// binary shouldn't be run at all. `doctest` is set when the commands run
+/// `rustdoc --test`.
+fn select_shard<K: Hash>(
+    shard: &TestShard,
+    list: &ProcessBuilder,
+    cmd: &ProcessBuilder,
+    key: K,
+    harness: bool,
+    doctest: bool,
+) -> CargoResult<Option<Vec<ProcessBuilder>>> {
+    if !harness {
+        return Ok(if shard.contains(&key) {
+            Some(vec![list.clone()])
+        } else {
+            None
+        });
+    }
+    let mut list = list.clone();
+    test_arg(&mut list, "--list", doctest);
+    let output = list.exec_with_output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let tests = stdout
+        .lines()
+        .filter_map(|line| {
+            if line.ends_with(": test") {
+                Some(&line[..line.len() - ": test".len()])
+            } else if line.ends_with(": bench") {
+                Some(&line[..line.len() - ": bench".len()])
+            } else {
+                None
+            }
+        })
+        .filter(|name| shard.contains((&key, name)))
+        .collect::<Vec<_>>();
+    if tests.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(exact_tests(cmd, &tests, doctest)?))
+}
+
+/// A conservative limit on the length of a command line. Windows limits it to
+/// 32,767 characters, while Unix systems allow much more, but count the
+/// environment against the same limit.
+#[cfg(windows)]
+const MAX_COMMAND_LEN: usize = 30 * 1024;
+#[cfg(not(windows))]
+const MAX_COMMAND_LEN: usize = 128 * 1024;
+
+/// Returns the commands which run exactly the tests `names` with `cmd`.
+///
+/// The names are split into as many batches as needed to keep each command
+/// line under `MAX_COMMAND_LEN`.
+fn exact_tests<S: AsRef<str>>(
+    cmd: &ProcessBuilder,
+    names: &[S],
+    doctest: bool,
+) -> CargoResult<Vec<ProcessBuilder>> {
+    let mut base = cmd.clone();
+    if !cmd.get_args().iter().any(|arg| arg == "--exact") {
+        test_arg(&mut base, "--exact", doctest);
+    }
+    let base_len = base.get_program().len()
+        + base
+            .get_args()
+            .iter()
+            .map(|arg| arg.len() + 1)
+            .sum::<usize>();
+
+    let mut batches = Vec::new();
+    let mut batch = base.clone();
+    let mut len = base_len;
+    for name in names {
+        let name = name.as_ref();
+        let arg_len = name.len() + 1 + if doctest { "--test-args ".len() } else { 0 };
+        if base_len + arg_len > MAX_COMMAND_LEN {
+            failure::bail!(
+                "the command line to run test `{}` would be too long:\n{}",
+                name,
+                base
+            );
+        }
+        if len + arg_len > MAX_COMMAND_LEN {
+            batches.push(mem::replace(&mut batch, base.clone()));
+            len = base_len;
+        }
+        test_arg(&mut batch, name, doctest);
+        len += arg_len;
+    }
+    batches.push(batch);
+    Ok(batches)
+}
+
+/// The options of libtest which take a value as a separate argument.
+const LIBTEST_VALUE_OPTIONS: &[&str] = &[
+    "--color",
+    "--format",
+    "--logfile",
+    "--skip",
+    "--test-threads",
+    "-Z",
+];
+
+/// Removes the filters, which are the positional arguments, from the
+/// arguments of a test binary.
+fn without_filters<'a>(test_args: &[&'a str]) -> Vec<&'a str> {
+    let mut args = Vec::new();
+    let mut value = false;
+    for &arg in test_args {
+        if value || arg.starts_with('-') {
+            args.push(arg);
+            value = !value && LIBTEST_VALUE_OPTIONS.contains(&arg);
+        }
+    }
+    args
+}
+
+/// Asks libtest to color its output when Cargo parses it, since libtest
+/// doesn't color it by itself when stdout is piped.
+fn color_arg(
+    options: &TestOptions<'_>,
+    cmd: &mut ProcessBuilder,
+    test_args: &[&str],
+    doctest: bool,
+) {
+    let config = options.compile_opts.config;
+    let colored = test_args.iter().any(|arg| arg.starts_with("--color"));
+    if options.needs_results() && !colored && config.shell().stdout_supports_color() {
+        test_arg(cmd, "--color=always", doctest);
+    }
+}
+
+/// Passes `arg` to libtest, through `--test-args` if `cmd` runs doctests.
+fn test_arg(cmd: &mut ProcessBuilder, arg: &str, doctest: bool) {
+    if doctest {
+        cmd.arg("--test-args");
+    }
+    cmd.arg(arg);
+}
+
+/// Runs a test binary or `rustdoc --test`, with each of `cmds` in turn.
+///
+/// When the results are needed the output is parsed as it is relayed, and
+/// the results are added to `results`. Failed tests are then rerun by name
+/// with `retry`, which runs the binary without any test filter, up to
+/// `--retries` times.
+fn run_test_binary(
+    options: &TestOptions<'_>,
+    cmds: &[ProcessBuilder],
+    retry: &ProcessBuilder,
+    doctest: bool,
+    binary: BinaryResults,
+    results: &mut TestResults,
+) -> CargoResult<()> {
+    if !options.needs_results() {
+        let mut result = Ok(());
+        for cmd in cmds {
+            let batch = cmd.exec();
+            if result.is_ok() {
+                result = batch;
+            }
+        }
+        return result;
+    }
+    let config = options.compile_opts.config;
+    let (mut binary, mut result) = exec_test(config, cmds, binary);
+    report_timeout(config, &binary)?;
+
+    for attempt in 1..=options.retries {
+        let failed = binary.failed_tests();
+        if result.is_ok() || failed.is_empty() {
+            break;
+        }
+        config.shell().status(
+            "Retrying",
+            format!(
+                "{} failed test{} of {} (attempt {} of {})",
+                failed.len(),
+                if failed.len() == 1 { "" } else { "s" },
+                binary.display_name(),
+                attempt,
+                options.retries
+            ),
+        )?;
+        let rerun = exact_tests(retry, &failed, doctest)?;
+        let (rerun_binary, rerun_result) = exec_test(config, &rerun, binary.empty_copy());
+        report_timeout(config, &rerun_binary)?;
+        binary.merge_rerun(&failed, rerun_binary);
+        result = rerun_result;
+    }
+
+    binary.error = result.as_ref().err().map(|e| e.to_string());
+    results.push(binary);
+    result
+}
+
+/// Runs each of `cmds`, relaying and parsing their output into `binary`.
+fn exec_test(
+    config: &Config,
+    cmds: &[ProcessBuilder],
+    mut binary: BinaryResults,
+) -> (BinaryResults, CargoResult<()>) {
+    let start = Instant::now();
+    let mut result = Ok(());
+    let mut summaries = true;
+    for cmd in cmds {
+        let mut parser = LibtestParser::new();
+        let batch = cmd
+            .exec_with_streaming(
+                &mut |line| {
+                    parser.line(line);
+                    config
+                        .shell()
+                        .print_ansi_stdout(format!("{}\n", line).as_bytes())
+                },
+                &mut |line| {
+                    writeln!(config.shell().err(), "{}", line)?;
+                    Ok(())
+                },
+                false,
+            )
+            .map(drop);
+        let timed_out = match batch {
+            Err(ref e) => e
+                .downcast_ref::<ProcessError>()
+                .map_or(false, |e| e.timed_out),
+            Ok(()) => false,
+        };
+        if timed_out {
+            binary.timed_out = true;
+            binary.unfinished.extend(parser.unfinished());
+        }
+        if let Some(expected) = parser.expected() {
+            binary.expected = Some(binary.expected.unwrap_or(0) + expected);
+        }
+        match (&mut binary.summary, parser.summary()) {
+            (Some(summary), Some(counts)) => summary.add_batch(&counts),
+            (summary, Some(counts)) => *summary = Some(counts),
+            (_, None) => summaries = false,
+        }
+        binary.cases.extend(parser.finish());
+        if result.is_ok() {
+            result = batch;
+        }
+    }
+    // The counts are only complete if every batch got as far as its summary.
+    if !summaries {
+        binary.summary = None;
+    }
+    binary.duration = start.elapsed();
+    (binary, result)
+}
+
+/// Tells the user which tests were still running in a binary which was
+/// killed because it timed out.
+fn report_timeout(config: &Config, binary: &BinaryResults) -> CargoResult<()> {
+    if !binary.timed_out {
+        return Ok(());
+    }
+    let mut msg = format!(
+        "test binary `{}` of `{}` timed out after {}",
+        binary.binary,
+        binary.display_name(),
+        util::elapsed(binary.duration)
+    );
+    let finished = binary.cases.len();
+    match binary.expected {
+        Some(expected) => msg.push_str(&format!(
+            "\n{} of {} tests finished before it was killed",
+            finished, expected
+        )),
+        None => msg.push_str(&format!(
+            "\n{} tests finished before it was killed",
+            finished
+        )),
+    }
+    if !binary.unfinished.is_empty() {
+        msg.push_str("\ntests still running:");
+        for name in binary.unfinished.iter() {
+            msg.push_str("\n    ");
+            msg.push_str(name);
+        }
+    }
+    config.shell().error(msg)
+}
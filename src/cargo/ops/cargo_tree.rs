@@ -0,0 +1,422 @@
+//! Implementation of `cargo tree`, which prints the resolved dependency
+//! graph of the workspace.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use crate::core::compiler::{BuildConfig, CompileMode, Kind, TargetInfo};
+use crate::core::dependency::Kind as DepKind;
+use crate::core::{Package, PackageId, PackageIdSpec, Resolve, Workspace};
+use crate::ops::{self, Packages};
+use crate::print_json;
+use crate::util::CargoResult;
+
+pub struct TreeOptions {
+    pub features: Vec<String>,
+    pub all_features: bool,
+    pub no_default_features: bool,
+    /// The workspace members whose dependencies are printed.
+    pub packages: Packages,
+    /// Only follow dependencies which are used on this target, see
+    /// `--target`.
+    pub target: Option<String>,
+    /// Print the packages depending on this package instead, see `--invert`.
+    pub invert: Option<String>,
+    /// Print the packages depending on every package which is used in
+    /// several versions, see `--duplicates`.
+    pub duplicates: bool,
+    /// The kinds of dependencies to follow, see `--edges`.
+    pub edge_kinds: Vec<DepKind>,
+    /// How deep to print the tree, see `--depth`.
+    pub max_depth: Option<u32>,
+    /// How to print each package, see `--format`.
+    pub format: String,
+    /// Print the tree as JSON rather than as text.
+    pub json: bool,
+}
+
+/// Parses the comma separated list of dependency kinds of `--edges`.
+pub fn parse_edge_kinds(s: &str) -> CargoResult<Vec<DepKind>> {
+    let mut kinds = Vec::new();
+    for kind in s.split(',').map(|k| k.trim()) {
+        let kind = match kind {
+            "normal" => DepKind::Normal,
+            "build" => DepKind::Build,
+            "dev" => DepKind::Development,
+            _ => failure::bail!(
+                "unknown edge kind `{}`, expected one of `normal`, `build` or `dev`",
+                kind
+            ),
+        };
+        if !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+    }
+    Ok(kinds)
+}
+
+/// One piece of a `--format` template.
+enum Chunk {
+    Raw(String),
+    /// `{p}`: the name, version and (unless it's crates.io) source.
+    Package,
+    /// `{l}`: the license.
+    License,
+    /// `{r}`: the repository.
+    Repository,
+    /// `{f}`: the enabled features.
+    Features,
+}
+
+struct Pattern(Vec<Chunk>);
+
+impl Pattern {
+    fn parse(format: &str) -> CargoResult<Pattern> {
+        let mut chunks = Vec::new();
+        let mut rest = format;
+        while let Some(start) = rest.find('{') {
+            let end = match rest[start..].find('}') {
+                Some(end) => start + end,
+                None => failure::bail!("unterminated `{{` in format `{}`", format),
+            };
+            if start > 0 {
+                chunks.push(Chunk::Raw(rest[..start].to_string()));
+            }
+            chunks.push(match &rest[start + 1..end] {
+                "p" => Chunk::Package,
+                "l" => Chunk::License,
+                "r" => Chunk::Repository,
+                "f" => Chunk::Features,
+                other => failure::bail!(
+                    "unsupported pattern `{{{}}}` in format `{}`, \
+                     expected one of `{{p}}`, `{{l}}`, `{{r}}` or `{{f}}`",
+                    other,
+                    format
+                ),
+            });
+            rest = &rest[end + 1..];
+        }
+        if !rest.is_empty() {
+            chunks.push(Chunk::Raw(rest.to_string()));
+        }
+        Ok(Pattern(chunks))
+    }
+
+    fn display(&self, pkg: &Package, resolve: &Resolve) -> String {
+        let metadata = pkg.manifest().metadata();
+        let mut s = String::new();
+        for chunk in self.0.iter() {
+            match chunk {
+                Chunk::Raw(raw) => s.push_str(raw),
+                Chunk::Package => s.push_str(&pkg.package_id().to_string()),
+                Chunk::License => s.push_str(metadata.license.as_ref().map_or("", |l| l.as_str())),
+                Chunk::Repository => {
+                    s.push_str(metadata.repository.as_ref().map_or("", |r| r.as_str()))
+                }
+                Chunk::Features => s.push_str(&resolve.features_sorted(pkg.package_id()).join(",")),
+            }
+        }
+        s
+    }
+}
+
+/// The dependency graph as printed, pointing from dependents to dependencies
+/// unless it is inverted.
+struct Graph<'a> {
+    edges: HashMap<PackageId, BTreeSet<(DepKind, PackageId)>>,
+    packages: HashMap<PackageId, &'a Package>,
+    resolve: &'a Resolve,
+}
+
+impl<'a> Graph<'a> {
+    fn children(&self, id: PackageId, kind: DepKind) -> Vec<PackageId> {
+        self.edges
+            .get(&id)
+            .map(|edges| {
+                edges
+                    .iter()
+                    .filter(|&&(k, _)| k == kind)
+                    .map(|&(_, id)| id)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn invert(&self) -> HashMap<PackageId, BTreeSet<(DepKind, PackageId)>> {
+        let mut inverted = HashMap::new();
+        for (&from, edges) in self.edges.iter() {
+            for &(kind, to) in edges.iter() {
+                inverted
+                    .entry(to)
+                    .or_insert_with(BTreeSet::new)
+                    .insert((kind, from));
+            }
+        }
+        inverted
+    }
+}
+
+/// The order in which the kinds of dependencies are printed, and the heading
+/// they're printed under.
+const KINDS: &[(DepKind, Option<&str>)] = &[
+    (DepKind::Normal, None),
+    (DepKind::Build, Some("[build-dependencies]")),
+    (DepKind::Development, Some("[dev-dependencies]")),
+];
+
+/// Executes `cargo tree`.
+pub fn tree(ws: &Workspace<'_>, opts: &TreeOptions) -> CargoResult<()> {
+    let config = ws.config();
+    let pattern = Pattern::parse(&opts.format)?;
+    let specs = opts.packages.to_package_id_specs(ws)?;
+    let (package_set, resolve) = ops::resolve_ws_precisely(
+        ws,
+        None,
+        &opts.features,
+        opts.all_features,
+        opts.no_default_features,
+        &specs,
+    )?;
+
+    // The platforms platform-specific dependencies are checked against, the
+    // target and the host, which build dependencies are used on.
+    let platforms = match opts.target {
+        Some(ref target) => {
+            let build_config = BuildConfig::new(config, Some(1), &opts.target, CompileMode::Build)?;
+            let rustc = config.load_global_rustc(Some(ws))?;
+            let requested_target = &build_config.requested_target;
+            let target_info = TargetInfo::new(config, requested_target, &rustc, Kind::Target)?;
+            let host_info = TargetInfo::new(config, requested_target, &rustc, Kind::Host)?;
+            Some(((target.clone(), target_info), (rustc.host, host_info)))
+        }
+        None => None,
+    };
+
+    let roots = specs
+        .iter()
+        .map(|spec| spec.query(resolve.iter()))
+        .collect::<CargoResult<BTreeSet<_>>>()?;
+
+    // Walk the resolve from the roots, only following the selected kinds of
+    // dependencies which are used on the platform they're built for. Build
+    // dependencies and proc-macros, and everything they depend on, are built
+    // for the host.
+    let mut edges = HashMap::new();
+    let mut pending = roots.iter().map(|&id| (id, false)).collect::<Vec<_>>();
+    let mut visited = HashSet::new();
+    while let Some((id, for_host)) = pending.pop() {
+        if !visited.insert((id, for_host)) {
+            continue;
+        }
+        let deps = edges.entry(id).or_insert_with(BTreeSet::new);
+        for (dep_id, dependencies) in resolve.deps(id) {
+            // The platform only matters with `--target`, so don't download
+            // the package to find out otherwise.
+            let is_proc_macro = platforms.is_some()
+                && package_set
+                    .get_one(dep_id)?
+                    .targets()
+                    .iter()
+                    .any(|t| t.is_lib() && t.proc_macro());
+            for dep in dependencies {
+                if !opts.edge_kinds.contains(&dep.kind()) {
+                    continue;
+                }
+                let dep_for_host = for_host || dep.kind() == DepKind::Build;
+                let used = match (&platforms, dep.platform()) {
+                    (Some((target, host)), Some(platform)) => {
+                        let (name, info) = if dep_for_host { host } else { target };
+                        platform.matches(name, info.cfg())
+                    }
+                    _ => true,
+                };
+                if used {
+                    deps.insert((dep.kind(), dep_id));
+                    pending.push((dep_id, dep_for_host || is_proc_macro));
+                }
+            }
+        }
+    }
+    let reached = edges.keys().cloned().collect::<HashSet<_>>();
+    let packages = package_set
+        .get_many(reached.iter().cloned())?
+        .into_iter()
+        .map(|pkg| (pkg.package_id(), pkg))
+        .collect();
+    let mut graph = Graph {
+        edges,
+        packages,
+        resolve: &resolve,
+    };
+
+    let roots = if opts.duplicates {
+        graph.edges = graph.invert();
+        let mut by_name = BTreeMap::new();
+        for &id in reached.iter() {
+            by_name
+                .entry(id.name())
+                .or_insert_with(BTreeSet::new)
+                .insert(id);
+        }
+        by_name
+            .into_iter()
+            .filter(|(_, ids)| ids.len() > 1)
+            .flat_map(|(_, ids)| ids)
+            .collect()
+    } else if let Some(spec) = &opts.invert {
+        graph.edges = graph.invert();
+        let id = PackageIdSpec::query_str(spec, reached.iter().cloned())?;
+        vec![id]
+    } else {
+        roots.into_iter().collect::<Vec<_>>()
+    };
+
+    if opts.json {
+        let trees = roots
+            .iter()
+            .map(|&id| json_node(&graph, opts, id, None, 0, &mut HashSet::new()))
+            .collect::<Vec<_>>();
+        print_json(&JsonTree { roots: trees });
+        return Ok(());
+    }
+
+    let mut out = String::new();
+    for (i, &root) in roots.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        print_node(
+            &mut out,
+            &graph,
+            opts,
+            &pattern,
+            root,
+            &mut Vec::new(),
+            &mut HashSet::new(),
+        );
+    }
+    print!("{}", out);
+    Ok(())
+}
+
+/// Prints `id` and, the first time it's printed, its dependencies.
+///
+/// `levels` has an entry for every ancestor of `id`, telling whether more
+/// siblings follow it, which decides how the lines are drawn.
+fn print_node(
+    out: &mut String,
+    graph: &Graph<'_>,
+    opts: &TreeOptions,
+    pattern: &Pattern,
+    id: PackageId,
+    levels: &mut Vec<bool>,
+    visited: &mut HashSet<PackageId>,
+) {
+    let new = visited.insert(id);
+    let has_children = graph.edges.get(&id).map_or(false, |e| !e.is_empty());
+    if let Some((&more_siblings, ancestors)) = levels.split_last() {
+        for &more in ancestors {
+            out.push_str(if more { "│   " } else { "    " });
+        }
+        out.push_str(if more_siblings { "├── " } else { "└── " });
+    }
+    out.push_str(&pattern.display(graph.packages[&id], graph.resolve));
+    if !new && has_children {
+        out.push_str(" (*)");
+    }
+    out.push('\n');
+
+    let at_max_depth = opts
+        .max_depth
+        .map_or(false, |depth| levels.len() as u32 >= depth);
+    if !new || at_max_depth {
+        return;
+    }
+    for &(kind, heading) in KINDS {
+        let children = graph.children(id, kind);
+        if children.is_empty() {
+            continue;
+        }
+        if let Some(heading) = heading {
+            for &more in levels.iter() {
+                out.push_str(if more { "│   " } else { "    " });
+            }
+            writeln!(out, "{}", heading).unwrap();
+        }
+        for (i, &child) in children.iter().enumerate() {
+            levels.push(i + 1 < children.len());
+            print_node(out, graph, opts, pattern, child, levels, visited);
+            levels.pop();
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonTree {
+    roots: Vec<JsonNode>,
+}
+
+#[derive(Serialize)]
+struct JsonNode {
+    id: PackageId,
+    name: String,
+    version: String,
+    license: Option<String>,
+    repository: Option<String>,
+    features: Vec<String>,
+    /// The kind of the edge from the parent, absent for the roots.
+    kind: Option<&'static str>,
+    /// Whether the dependencies of this package were already listed earlier
+    /// in the tree, and are left out here.
+    repeated: bool,
+    dependencies: Vec<JsonNode>,
+}
+
+fn json_node(
+    graph: &Graph<'_>,
+    opts: &TreeOptions,
+    id: PackageId,
+    kind: Option<DepKind>,
+    depth: u32,
+    visited: &mut HashSet<PackageId>,
+) -> JsonNode {
+    let metadata = graph.packages[&id].manifest().metadata();
+    let new = visited.insert(id);
+    let mut dependencies = Vec::new();
+    if new && opts.max_depth.map_or(true, |max| depth < max) {
+        for &(kind, _) in KINDS {
+            for child in graph.children(id, kind) {
+                dependencies.push(json_node(
+                    graph,
+                    opts,
+                    child,
+                    Some(kind),
+                    depth + 1,
+                    visited,
+                ));
+            }
+        }
+    }
+    JsonNode {
+        id,
+        name: id.name().to_string(),
+        version: id.version().to_string(),
+        license: metadata.license.clone(),
+        repository: metadata.repository.clone(),
+        features: graph
+            .resolve
+            .features_sorted(id)
+            .into_iter()
+            .map(|f| f.to_string())
+            .collect(),
+        kind: kind.map(|kind| match kind {
+            DepKind::Normal => "normal",
+            DepKind::Build => "build",
+            DepKind::Development => "dev",
+        }),
+        repeated: !new && graph.edges.get(&id).map_or(false, |e| !e.is_empty()),
+        dependencies,
+    }
+}
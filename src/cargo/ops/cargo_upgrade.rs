@@ -0,0 +1,245 @@
+//! Implementation of `cargo upgrade`, which rewrites the version
+//! requirements in the manifests of the workspace members to the newest
+//! available versions.
+
+use std::collections::HashMap;
+
+use semver::{Version, VersionReq};
+use toml_edit::{Item, Value};
+
+use super::cargo_add::{get_field, replace_value, table_name, LocalManifest};
+use crate::core::registry::PackageRegistry;
+use crate::core::{Dependency, Registry, SourceId, Workspace};
+use crate::ops;
+use crate::util::errors::CargoResult;
+use crate::util::Config;
+
+pub struct UpgradeOptions {
+    /// Only upgrade the dependencies on these packages, all of them if empty.
+    pub to_upgrade: Vec<String>,
+    /// Upgrade requirements to newer versions they already allow, see
+    /// `--compatible`.
+    pub compatible: bool,
+    /// Upgrade requirements to newer versions they don't allow yet, see
+    /// `--incompatible`.
+    pub incompatible: bool,
+    pub dry_run: bool,
+}
+
+/// Where the versions a dependency may be upgraded to come from.
+enum Candidates {
+    Registry(SourceId),
+    /// A path dependency on a workspace member, which can only be upgraded
+    /// to the member's current version.
+    Member(Version),
+}
+
+/// A dependency whose requirement can be upgraded.
+struct Upgrade {
+    key: String,
+    name: String,
+    req: String,
+    candidates: Candidates,
+}
+
+pub fn upgrade(ws: &Workspace<'_>, opts: &UpgradeOptions) -> CargoResult<()> {
+    let config = ws.config();
+    let members: HashMap<_, _> = ws
+        .members()
+        .map(|pkg| (pkg.name().to_string(), pkg.version().clone()))
+        .collect();
+
+    let mut registry = PackageRegistry::new(config)?;
+    registry.lock_patches();
+
+    let mut changed = false;
+    for pkg in ws.members() {
+        let mut manifest = LocalManifest::open(pkg.manifest_path())?;
+        let mut pkg_changed = false;
+        for (kind, target) in manifest.dep_tables() {
+            let target = target.as_ref().map(|s| s.as_str());
+            let upgrades = {
+                let table = manifest.existing_dep_table(kind, target)?.unwrap();
+                let mut upgrades = Vec::new();
+                for (key, item) in table.iter() {
+                    if let Some(upgrade) = upgradable(config, key, item, &members)? {
+                        if opts.to_upgrade.is_empty() || opts.to_upgrade.contains(&upgrade.name) {
+                            upgrades.push(upgrade);
+                        }
+                    }
+                }
+                upgrades
+            };
+
+            for upgrade in upgrades {
+                let versions = match upgrade.candidates {
+                    Candidates::Member(ref version) => vec![version.clone()],
+                    Candidates::Registry(source_id) => {
+                        let dep = Dependency::parse_no_deprecated(&upgrade.name, None, source_id)?;
+                        registry
+                            .query_vec(&dep, false)?
+                            .iter()
+                            .map(|s| s.version().clone())
+                            .collect()
+                    }
+                };
+                let new_req = match upgraded_req(&upgrade.req, &versions, opts) {
+                    Some(new_req) => new_req,
+                    None => continue,
+                };
+                config.shell().status(
+                    "Upgrading",
+                    format!(
+                        "{} {} -> {} ({} of {})",
+                        upgrade.name,
+                        upgrade.req,
+                        new_req,
+                        table_name(kind, target),
+                        pkg.name()
+                    ),
+                )?;
+                let table = manifest.existing_dep_table(kind, target)?.unwrap();
+                set_version(table.entry(&upgrade.key), &new_req);
+                pkg_changed = true;
+            }
+        }
+
+        if pkg_changed && !opts.dry_run {
+            manifest.write(config, pkg)?;
+        }
+        changed |= pkg_changed;
+    }
+
+    if opts.dry_run {
+        config
+            .shell()
+            .warn("not updating the manifests due to dry run")?;
+    } else if changed {
+        // Update the lock file to the new requirements.
+        let ws = Workspace::new(&ws.root().join("Cargo.toml"), config)?;
+        ops::resolve_ws(&ws)?;
+    }
+    Ok(())
+}
+
+/// Returns how the dependency `key = item` could be upgraded, if its
+/// requirement can be rewritten at all.
+fn upgradable(
+    config: &Config,
+    key: &str,
+    item: &Item,
+    members: &HashMap<String, Version>,
+) -> CargoResult<Option<Upgrade>> {
+    let (req, name) = match *item {
+        Item::Value(Value::String(ref req)) => (req.value().to_string(), key.to_string()),
+        _ => {
+            // Git dependencies don't have versions to upgrade to.
+            if get_field(item, "git").is_some() {
+                return Ok(None);
+            }
+            let req = match get_field(item, "version").and_then(|v| v.as_str()) {
+                Some(req) => req.to_string(),
+                None => return Ok(None),
+            };
+            let name = get_field(item, "package")
+                .and_then(|v| v.as_str())
+                .unwrap_or(key)
+                .to_string();
+            (req, name)
+        }
+    };
+
+    let candidates = if get_field(item, "path").is_some() {
+        match members.get(&name) {
+            Some(version) => Candidates::Member(version.clone()),
+            None => return Ok(None),
+        }
+    } else {
+        match get_field(item, "registry").and_then(|v| v.as_str()) {
+            Some(registry) => Candidates::Registry(SourceId::alt_registry(config, registry)?),
+            None => Candidates::Registry(SourceId::crates_io(config)?),
+        }
+    };
+
+    Ok(Some(Upgrade {
+        key: key.to_string(),
+        name,
+        req,
+        candidates,
+    }))
+}
+
+/// Returns the requirement `req` should be upgraded to given the available
+/// `versions`, keeping its operator and precision. Only plain, caret and
+/// tilde requirements are upgraded, pinned (`=`) ones and ranges are left
+/// alone.
+fn upgraded_req(req: &str, versions: &[Version], opts: &UpgradeOptions) -> Option<String> {
+    let req = req.trim();
+    let (op, base) = if req.starts_with('^') || req.starts_with('~') {
+        let (op, base) = req.split_at(1);
+        (op, base.trim())
+    } else {
+        ("", req)
+    };
+    let release = base.splitn(2, |c| c == '-' || c == '+').next().unwrap();
+    let parts = release.split('.').collect::<Vec<_>>();
+    if parts.len() > 3
+        || parts
+            .iter()
+            .any(|p| p.is_empty() || !p.chars().all(|c| c.is_ascii_digit()))
+    {
+        return None;
+    }
+    let mut padded = parts.clone();
+    padded.resize(3, "0");
+    let base_version =
+        Version::parse(&format!("{}{}", padded.join("."), &base[release.len()..])).ok()?;
+    let parsed = VersionReq::parse(req).ok()?;
+
+    // Pre-releases are only upgraded to if the requirement already uses one.
+    let versions = versions
+        .iter()
+        .filter(|v| !v.is_prerelease() || base_version.is_prerelease())
+        .filter(|v| **v > base_version);
+    let newest_compatible = versions.clone().filter(|v| parsed.matches(v)).max();
+    let newest_incompatible = versions
+        .filter(|v| !parsed.matches(v))
+        .filter(|v| newest_compatible.map_or(true, |c| *v > c))
+        .max();
+
+    // Neither flag means both kinds of upgrades are wanted.
+    let both = !opts.compatible && !opts.incompatible;
+    let target = match (newest_compatible, newest_incompatible) {
+        (_, Some(v)) if opts.incompatible || both => v,
+        (Some(v), _) if opts.compatible || both => v,
+        _ => return None,
+    };
+
+    let new_base = if target.is_prerelease() || parts.len() == 3 {
+        target.to_string()
+    } else if parts.len() == 2 {
+        format!("{}.{}", target.major, target.minor)
+    } else {
+        target.major.to_string()
+    };
+    let new_req = format!("{}{}", op, new_base);
+    if new_req == req {
+        None
+    } else {
+        Some(new_req)
+    }
+}
+
+/// Sets the version requirement of the dependency `item`, keeping the
+/// formatting around it.
+fn set_version(item: &mut Item, version: &str) {
+    let value = match *item {
+        Item::Value(Value::InlineTable(ref mut table)) => table.get_mut("version"),
+        Item::Value(ref mut value) => Some(value),
+        Item::Table(ref mut table) => table.entry("version").as_value_mut(),
+        _ => None,
+    };
+    if let Some(value) = value {
+        replace_value(value, Value::from(version));
+    }
+}
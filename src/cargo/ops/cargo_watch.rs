@@ -0,0 +1,190 @@
+//! Re-running a command whenever a file it was built from changes, see
+//! `--watch`.
+//!
+//! The watched files are the manifests and source files (as listed by
+//! `PathSource`) of the workspace members and their path dependencies, plus
+//! the inputs of the last build: the rustc dep-info and the
+//! `rerun-if-changed` paths of build scripts. Files in the target directory
+//! are never watched, so the output of a build can't trigger another one.
+//! The inputs of the build are refreshed after every run of the command, the
+//! workspace's sources only when a manifest or the lock file changed.
+//!
+//! Changes are found by polling modification times, which works the same on
+//! every platform and file system.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use log::debug;
+
+use crate::core::Workspace;
+use crate::ops;
+use crate::sources::PathSource;
+use crate::util::errors::CargoResult;
+use crate::util::process_builder::ProcessGroup;
+use crate::util::Config;
+
+/// How often the watched files are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How long the watched files have to stay unchanged before the command is
+/// re-run, so that saving several files at once only re-runs it once.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// What one run of a watched command left behind.
+#[derive(Default)]
+pub struct WatchRun {
+    /// The files the build read, see `Compilation::input_files`.
+    pub input_files: BTreeSet<PathBuf>,
+    /// A process started by the command, such as the binary of `cargo run`,
+    /// which is killed along with its process group before the command is
+    /// re-run.
+    pub child: Option<ProcessGroup>,
+}
+
+/// Calls `run`, and calls it again every time one of the files the
+/// workspace is built from changes.
+///
+/// Errors returned by `run` are printed and the watching goes on; this only
+/// returns if the files can't be watched.
+pub fn watch<F>(ws: &Workspace<'_>, mut run: F) -> CargoResult<()>
+where
+    F: FnMut() -> CargoResult<WatchRun>,
+{
+    let config = ws.config();
+    let root_manifest = ws.root().join("Cargo.toml");
+    let target_dir = ws.target_dir().into_path_unlocked();
+    let mut input_files = BTreeSet::new();
+    let mut sources = BTreeSet::new();
+    let mut refresh_sources = true;
+    loop {
+        let mut child = match run() {
+            Ok(run) => {
+                input_files = run.input_files;
+                run.child
+            }
+            Err(e) => {
+                crate::handle_error(&e, &mut config.shell());
+                None
+            }
+        };
+
+        // Keep watching the previous sources if the workspace is broken, so
+        // fixing it triggers a re-run.
+        if refresh_sources {
+            match local_sources(&root_manifest, config) {
+                Ok(files) => {
+                    sources = files;
+                    refresh_sources = false;
+                }
+                Err(e) => crate::handle_error(&e, &mut config.shell()),
+            }
+        }
+        let files = sources
+            .iter()
+            .chain(input_files.iter())
+            .filter(|path| !path.starts_with(&target_dir))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        config.shell().status(
+            "Watching",
+            format!(
+                "{} file{} for changes",
+                files.len(),
+                if files.len() == 1 { "" } else { "s" }
+            ),
+        )?;
+        let changed = wait_for_change(config, &files, &mut child)?;
+        if let Some(mut child) = child {
+            debug!("killing process group of {}", child.id());
+            child.kill();
+        }
+        // Only a manifest or the lock file can change which packages and
+        // files make up the workspace.
+        if let Some(name) = changed.file_name() {
+            refresh_sources |= name == "Cargo.toml" || name == "Cargo.lock";
+        }
+        let changed = changed.strip_prefix(config.cwd()).unwrap_or(&changed);
+        config.shell().status("Changed", changed.display())?;
+    }
+}
+
+/// Lists the manifests and source files of the workspace members and their
+/// path dependencies.
+fn local_sources(root_manifest: &Path, config: &Config) -> CargoResult<BTreeSet<PathBuf>> {
+    let ws = Workspace::new(root_manifest, config)?;
+    let mut files = BTreeSet::new();
+    files.insert(root_manifest.to_path_buf());
+    files.insert(ws.root().join("Cargo.lock"));
+
+    let (packages, resolve) = ops::resolve_ws(&ws)?;
+    for id in resolve.iter().filter(|id| id.source_id().is_path()) {
+        let pkg = packages.get_one(id)?;
+        files.insert(pkg.manifest_path().to_path_buf());
+        let src = PathSource::new(pkg.root(), id.source_id(), config);
+        files.extend(src.list_files(pkg)?);
+    }
+    Ok(files)
+}
+
+/// Blocks until one of `files` is modified, created or removed, and returns
+/// the first one which changed.
+///
+/// Meanwhile, reports when `child` exits.
+fn wait_for_change(
+    config: &Config,
+    files: &[PathBuf],
+    child: &mut Option<ProcessGroup>,
+) -> CargoResult<PathBuf> {
+    let before = mtimes(files);
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        report_exit(config, child)?;
+
+        let mut now = mtimes(files);
+        if now == before {
+            continue;
+        }
+        loop {
+            thread::sleep(DEBOUNCE);
+            let settled = mtimes(files);
+            if settled == now {
+                break;
+            }
+            now = settled;
+        }
+        if let Some(i) = (0..files.len()).find(|&i| before[i] != now[i]) {
+            return Ok(files[i].clone());
+        }
+    }
+}
+
+fn mtimes(files: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    files
+        .iter()
+        .map(|file| fs::metadata(file).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+/// Tells the user if `child` exited unsuccessfully, forgetting about it once
+/// it has exited.
+fn report_exit(config: &Config, child: &mut Option<ProcessGroup>) -> CargoResult<()> {
+    let status = match child {
+        Some(process) => match process.try_wait()? {
+            Some(status) => status,
+            None => return Ok(()),
+        },
+        None => return Ok(()),
+    };
+    *child = None;
+    if !status.success() {
+        config
+            .shell()
+            .warn(format!("process didn't exit successfully ({})", status))?;
+    }
+    Ok(())
+}
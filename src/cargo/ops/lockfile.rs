@@ -1,12 +1,15 @@
 use std::io::prelude::*;
+use std::path::Path;
 
 use toml;
 
-use crate::core::resolver::WorkspaceResolve;
+use crate::core::registry::PackageRegistry;
+use crate::core::resolver::{Method, WorkspaceResolve};
 use crate::core::{resolver, Resolve, Workspace};
+use crate::ops;
 use crate::util::errors::{CargoResult, CargoResultExt};
 use crate::util::toml as cargo_toml;
-use crate::util::Filesystem;
+use crate::util::{paths, Filesystem};
 
 pub fn load_pkg_lockfile(ws: &Workspace<'_>) -> CargoResult<Option<Resolve>> {
     if !ws.root().join("Cargo.lock").exists() {
@@ -29,15 +32,110 @@ pub fn load_pkg_lockfile(ws: &Workspace<'_>) -> CargoResult<Option<Resolve>> {
     Ok(resolve)
 }
 
+/// Merges the lock files at `ours` and `theirs`, which both derive from the
+/// one at `base`, and writes the result to `ours`. This is meant to be used
+/// as a git merge driver: the merged lock file is resolved again against the
+/// manifests of the workspace, so it's consistent with them.
+pub fn merge_lockfiles(
+    ws: &Workspace<'_>,
+    base: &Path,
+    ours: &Path,
+    theirs: &Path,
+) -> CargoResult<()> {
+    let parse = |path: &Path| {
+        (|| -> CargoResult<resolver::EncodableResolve> {
+            let contents = paths::read(path)?;
+            let resolve: toml::Value = cargo_toml::parse(&contents, path, ws.config())?;
+            Ok(resolve.try_into()?)
+        })()
+        .chain_err(|| format!("failed to parse lock file at: {}", path.display()))
+    };
+    let merged = resolver::EncodableResolve::merge(parse(base)?, parse(ours)?, parse(theirs)?)
+        .chain_err(|| "failed to merge the lock files, the conflict must be resolved by hand")?;
+    let previous = merged.into_resolve(ws)?;
+
+    let mut registry = PackageRegistry::new(ws.config())?;
+    let resolve = ops::resolve_with_previous(
+        &mut registry,
+        ws,
+        Method::Everything,
+        Some(&previous),
+        None,
+        &[],
+        true,
+        true,
+    )
+    .chain_err(|| "failed to resolve the dependencies of the merged lock file")?;
+
+    let out = resolve_to_string(ws, &resolve)?;
+    paths::write(ours, out.as_bytes())
+}
+
 pub fn write_pkg_lockfile(ws: &Workspace<'_>, resolve: &Resolve) -> CargoResult<()> {
+    let (orig, out) = resolve_to_string_orig(ws, resolve)?;
+
+    // If the lock file contents haven't changed so don't rewrite it. This is
+    // helpful on read-only filesystems.
+    if let Some(orig) = orig {
+        if are_equal_lockfiles(orig, &out, ws) {
+            return Ok(());
+        }
+    }
+
+    if !ws.config().lock_update_allowed() {
+        if ws.config().cli_unstable().offline {
+            failure::bail!("can't update in the offline mode");
+        }
+
+        let flag = if ws.config().network_allowed() {
+            "--locked"
+        } else {
+            "--frozen"
+        };
+        failure::bail!(
+            "the lock file {} needs to be updated but {} was passed to \
+             prevent this",
+            ws.root().to_path_buf().join("Cargo.lock").display(),
+            flag
+        );
+    }
+
+    // Ok, if that didn't work just write it out
+    let ws_root = Filesystem::new(ws.root().to_path_buf());
+    ws_root
+        .open_rw("Cargo.lock", ws.config(), "Cargo.lock file")
+        .and_then(|mut f| {
+            f.file().set_len(0)?;
+            f.write_all(out.as_bytes())?;
+            Ok(())
+        })
+        .chain_err(|| format!("failed to write {}", ws.root().join("Cargo.lock").display()))?;
+    Ok(())
+}
+
+/// Serializes `resolve` the way it's written to `Cargo.lock`, keeping the
+/// comments at the top of the current lock file of the workspace.
+pub fn resolve_to_string(ws: &Workspace<'_>, resolve: &Resolve) -> CargoResult<String> {
+    let (_, out) = resolve_to_string_orig(ws, resolve)?;
+    Ok(out)
+}
+
+/// Returns the current lock file of the workspace, if any, along with the
+/// serialized `resolve`.
+fn resolve_to_string_orig(
+    ws: &Workspace<'_>,
+    resolve: &Resolve,
+) -> CargoResult<(Option<String>, String)> {
     // Load the original lock file if it exists.
     let ws_root = Filesystem::new(ws.root().to_path_buf());
     let orig = ws_root.open_ro("Cargo.lock", ws.config(), "Cargo.lock file");
-    let orig = orig.and_then(|mut f| {
-        let mut s = String::new();
-        f.read_to_string(&mut s)?;
-        Ok(s)
-    });
+    let orig = orig
+        .and_then(|mut f| {
+            let mut s = String::new();
+            f.read_to_string(&mut s)?;
+            Ok(s)
+        })
+        .ok();
 
     let toml = toml::Value::try_from(WorkspaceResolve { ws, resolve }).unwrap();
 
@@ -52,7 +150,7 @@ pub fn write_pkg_lockfile(ws: &Workspace<'_>, resolve: &Resolve) -> CargoResult<
     out.push_str(extra_line);
     out.push('\n');
     // and preserve any other top comments
-    if let Ok(orig) = &orig {
+    if let Some(orig) = &orig {
         let mut comments = orig.lines().take_while(|line| line.starts_with('#'));
         if let Some(first) = comments.next() {
             if first != marker_line {
@@ -72,6 +170,10 @@ pub fn write_pkg_lockfile(ws: &Workspace<'_>, resolve: &Resolve) -> CargoResult<
         }
     }
 
+    if let Some(version) = toml.get("version") {
+        out.push_str(&format!("version = {}\n\n", version));
+    }
+
     let deps = toml["package"].as_array().unwrap();
     for dep in deps.iter() {
         let dep = dep.as_table().unwrap();
@@ -94,42 +196,7 @@ pub fn write_pkg_lockfile(ws: &Workspace<'_>, resolve: &Resolve) -> CargoResult<
         out.push_str(&meta.to_string());
     }
 
-    // If the lock file contents haven't changed so don't rewrite it. This is
-    // helpful on read-only filesystems.
-    if let Ok(orig) = orig {
-        if are_equal_lockfiles(orig, &out, ws) {
-            return Ok(());
-        }
-    }
-
-    if !ws.config().lock_update_allowed() {
-        if ws.config().cli_unstable().offline {
-            failure::bail!("can't update in the offline mode");
-        }
-
-        let flag = if ws.config().network_allowed() {
-            "--locked"
-        } else {
-            "--frozen"
-        };
-        failure::bail!(
-            "the lock file {} needs to be updated but {} was passed to \
-             prevent this",
-            ws.root().to_path_buf().join("Cargo.lock").display(),
-            flag
-        );
-    }
-
-    // Ok, if that didn't work just write it out
-    ws_root
-        .open_rw("Cargo.lock", ws.config(), "Cargo.lock file")
-        .and_then(|mut f| {
-            f.file().set_len(0)?;
-            f.write_all(out.as_bytes())?;
-            Ok(())
-        })
-        .chain_err(|| format!("failed to write {}", ws.root().join("Cargo.lock").display()))?;
-    Ok(())
+    Ok((orig, out))
 }
 
 fn are_equal_lockfiles(mut orig: String, current: &str, ws: &Workspace<'_>) -> bool {
@@ -170,6 +237,9 @@ fn emit_package(dep: &toml::value::Table, out: &mut String) {
     if dep.contains_key("source") {
         out.push_str(&format!("source = {}\n", &dep["source"]));
     }
+    if dep.contains_key("checksum") {
+        out.push_str(&format!("checksum = {}\n", &dep["checksum"]));
+    }
 
     if let Some(s) = dep.get("dependencies") {
         let slice = s.as_array().unwrap();
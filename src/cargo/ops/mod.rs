@@ -1,3 +1,5 @@
+pub use self::bench_baseline::BaselineOptions;
+pub use self::cargo_add::{add, remove, AddOptions, RemoveOptions};
 pub use self::cargo_clean::{clean, CleanOptions};
 pub use self::cargo_compile::{compile, compile_with_exec, compile_ws, CompileOptions};
 pub use self::cargo_compile::{CompileFilter, FilterRule, LibRule, Packages};
@@ -12,11 +14,17 @@ pub use self::cargo_output_metadata::{output_metadata, ExportInfo, OutputMetadat
 pub use self::cargo_package::{package, PackageOpts};
 pub use self::cargo_pkgid::pkgid;
 pub use self::cargo_read_manifest::{read_package, read_packages};
-pub use self::cargo_run::run;
-pub use self::cargo_test::{run_benches, run_tests, TestOptions};
+pub use self::cargo_run::{compile_run, run};
+pub use self::cargo_test::{compile_tests, run_compiled_tests};
+pub use self::cargo_test::{parse_test_timeout, run_benches, run_tests, TestOptions, TestShard};
+pub use self::cargo_tree::{parse_edge_kinds, tree, TreeOptions};
 pub use self::cargo_uninstall::uninstall;
+pub use self::cargo_upgrade::{upgrade, UpgradeOptions};
+pub use self::cargo_watch::{watch, WatchRun};
 pub use self::fix::{fix, fix_maybe_exec_rustc, FixOptions};
-pub use self::lockfile::{load_pkg_lockfile, write_pkg_lockfile};
+pub use self::lockfile::{
+    load_pkg_lockfile, merge_lockfiles, resolve_to_string, write_pkg_lockfile,
+};
 pub use self::registry::HttpTimeout;
 pub use self::registry::{configure_http_handle, http_handle_and_timeout};
 pub use self::registry::{http_handle, needs_custom_http_transport, registry_login, search};
@@ -26,7 +34,10 @@ pub use self::resolve::{
     add_overrides, get_resolved_packages, resolve_with_previous, resolve_ws, resolve_ws_precisely,
     resolve_ws_with_method,
 };
+pub use self::test_results::{TestCounts, TestReport, TestResults};
 
+mod bench_baseline;
+mod cargo_add;
 mod cargo_clean;
 mod cargo_compile;
 mod cargo_doc;
@@ -40,9 +51,13 @@ mod cargo_pkgid;
 mod cargo_read_manifest;
 mod cargo_run;
 mod cargo_test;
+mod cargo_tree;
 mod cargo_uninstall;
+mod cargo_upgrade;
+mod cargo_watch;
 mod common_for_install_and_uninstall;
 mod fix;
 mod lockfile;
 mod registry;
 mod resolve;
+mod test_results;
@@ -235,16 +235,45 @@ fn transmit(
     }
 
     let summary = pkg.summary();
+    // The values only enabled on some platforms are sent separately, so that
+    // they are left out of the features older versions of Cargo see.
+    let platform_features = summary.platform_features();
     let string_features = summary
         .features()
         .iter()
-        .map(|(feat, values)| {
+        .map(|(&feat, values)| {
             (
                 feat.to_string(),
-                values.iter().map(|fv| fv.to_string(summary)).collect(),
+                values
+                    .iter()
+                    .filter(|&fv| {
+                        !platform_features
+                            .iter()
+                            .any(|&(f, ref v, _)| f == feat && v == fv)
+                    })
+                    .map(|fv| fv.to_string(summary))
+                    .collect(),
             )
         })
         .collect::<BTreeMap<String, Vec<String>>>();
+    // Like in the index, the features using the `dep:` or `dep?/feature`
+    // syntax are sent separately, so that older versions of Cargo only ignore
+    // these features rather than the whole package.
+    let (features2, string_features): (BTreeMap<_, _>, BTreeMap<_, _>) =
+        string_features.into_iter().partition(|&(_, ref values)| {
+            values
+                .iter()
+                .any(|value: &String| value.starts_with("dep:") || value.contains("?/"))
+        });
+    let mut target_features = BTreeMap::new();
+    for &(feat, ref value, ref platform) in platform_features {
+        target_features
+            .entry(platform.to_string())
+            .or_insert_with(BTreeMap::new)
+            .entry(feat.to_string())
+            .or_insert_with(Vec::new)
+            .push(value.to_string(summary));
+    }
 
     let publish = registry.publish(
         &NewCrate {
@@ -252,6 +281,8 @@ fn transmit(
             vers: pkg.version().to_string(),
             deps,
             features: string_features,
+            features2,
+            target_features,
             authors: authors.clone(),
             description: description.clone(),
             homepage: homepage.clone(),
@@ -265,6 +296,7 @@ fn transmit(
             license_file: license_file.clone(),
             badges: badges.clone(),
             links: links.clone(),
+            rust_version: manifest.rust_version().map(|v| v.to_string()),
         },
         tarball,
     );
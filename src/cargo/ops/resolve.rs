@@ -1,14 +1,18 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
 
 use log::{debug, trace};
 
 use crate::core::registry::PackageRegistry;
 use crate::core::resolver::{self, Method, Resolve};
-use crate::core::{PackageId, PackageIdSpec, PackageSet, Source, SourceId, Workspace};
+use crate::core::{Feature, PackageId, PackageIdSpec, PackageSet, Source, SourceId, Workspace};
+use crate::core::{PolicyLevel, WorkspacePolicy};
 use crate::ops;
 use crate::sources::PathSource;
 use crate::util::errors::{CargoResult, CargoResultExt};
-use crate::util::profile;
+use crate::util::{hash_u64, paths, profile, semver_compatibility};
 
 const UNUSED_PATCH_WARNING: &str = "\
 Check that the patched package version and available features are compatible
@@ -126,12 +130,248 @@ fn resolve_with_registry<'cfg>(
         warn,
     )?;
 
+    check_yanked(ws, registry, &resolve)?;
     if !ws.is_ephemeral() {
         ops::write_pkg_lockfile(ws, &resolve)?;
     }
     Ok(resolve)
 }
 
+/// Warns about the packages in the lock file which have been yanked from
+/// their registries since they were locked, or rejects them if the
+/// `registry.yanked` config is `"deny"`.
+///
+/// Each yanked package is only warned about once per state of the index it
+/// comes from, which is recorded next to the registry indexes in the Cargo
+/// home, so that builds don't repeat the warning until the index is updated
+/// again.
+fn check_yanked(
+    ws: &Workspace<'_>,
+    registry: &mut PackageRegistry<'_>,
+    resolve: &Resolve,
+) -> CargoResult<()> {
+    let config = ws.config();
+    let deny = match config.get_string("registry.yanked")? {
+        None => false,
+        Some(ref v) if v.val == "warn" => false,
+        Some(ref v) if v.val == "deny" => true,
+        Some(v) => failure::bail!(
+            "invalid configuration for key `registry.yanked`, expected \
+             `warn` or `deny`, found `{}` (in {})",
+            v.val,
+            v.definition
+        ),
+    };
+
+    let mut pkgids = resolve
+        .iter()
+        .filter(|id| id.source_id().is_registry())
+        .collect::<Vec<_>>();
+    if pkgids.is_empty() {
+        return Ok(());
+    }
+    pkgids.sort();
+    let mut indexes = HashMap::new();
+    for pkgid in pkgids.iter() {
+        let url = pkgid.source_id().url().to_string();
+        if !indexes.contains_key(&url) {
+            let index = registry.index_version(pkgid.source_id())?;
+            indexes.insert(url, index.unwrap_or_else(|| "none".to_string()));
+        }
+    }
+
+    // Whether a locked package is yanked only changes when its index is
+    // updated, so the packages are only checked again once the lock file or
+    // one of the indexes changed. The most recent states checked are
+    // recorded, as the record is shared by every workspace.
+    let dir = config.registry_index_path();
+    let checked_record = dir.clone().into_path_unlocked().join(".yanked-checked");
+    let state = pkgids
+        .iter()
+        .map(|id| format!("{} {}", id, indexes[&id.source_id().url().to_string()]))
+        .collect::<Vec<_>>();
+    let state = format!(
+        "{} {:016x}",
+        if deny { "deny" } else { "warn" },
+        hash_u64(&state)
+    );
+    let known = indexes.values().all(|index| index != "none");
+    if known && paths::read(&checked_record).map_or(false, |r| r.lines().any(|l| l == state)) {
+        return Ok(());
+    }
+
+    let mut yanked = Vec::new();
+    for pkgid in pkgids {
+        if registry.is_yanked(pkgid)? {
+            yanked.push(pkgid);
+        }
+    }
+
+    if deny && !yanked.is_empty() {
+        let mut msg = String::from(
+            "the lock file contains packages which have been yanked from their registries:\n",
+        );
+        for pkgid in yanked.iter() {
+            write!(msg, "\n  {}", pkgid).unwrap();
+        }
+        msg.push_str(
+            "\n\nupdate them with `cargo update -p <name>`, or set the \
+             `registry.yanked` config to \"warn\" to allow them",
+        );
+        failure::bail!("{}", msg);
+    }
+
+    dir.create_dir()?;
+    let _lock = dir.open_rw(".yanked-lock", config, "the record of yanked packages")?;
+
+    // The packages are only warned about once for each version of their
+    // index, so only the entries of an index which has since been updated
+    // are dropped.
+    let record = dir.clone().into_path_unlocked().join(".yanked-warnings");
+    let previous = paths::read(&record).unwrap_or_default();
+    let mut current = previous
+        .lines()
+        .filter(|line| {
+            let mut parts = line.rsplitn(3, ' ');
+            match (parts.next(), parts.next()) {
+                (Some(index), Some(url)) => indexes.get(url).map_or(true, |i| i == index),
+                _ => false,
+            }
+        })
+        .map(|line| line.to_string())
+        .collect::<BTreeSet<_>>();
+    for pkgid in yanked {
+        let line = format!(
+            "{} {} {} {}",
+            pkgid.name(),
+            pkgid.version(),
+            pkgid.source_id().url(),
+            indexes[&pkgid.source_id().url().to_string()]
+        );
+        if current.insert(line) {
+            config.shell().warn(format!(
+                "package `{}` in Cargo.lock has been yanked from its registry, \
+                 consider updating it with `cargo update -p {}`",
+                pkgid,
+                pkgid.name()
+            ))?;
+        }
+    }
+    let current = current.into_iter().map(|l| l + "\n").collect::<String>();
+    if current != previous {
+        replace_record(&record, &current)?;
+    }
+
+    if known {
+        let previous = paths::read(&checked_record).unwrap_or_default();
+        let mut checked = previous.lines().collect::<Vec<_>>();
+        checked.push(&state);
+        let start = checked.len().saturating_sub(MAX_CHECKED_STATES);
+        let checked = checked[start..]
+            .iter()
+            .map(|l| format!("{}\n", l))
+            .collect::<String>();
+        replace_record(&checked_record, &checked)?;
+    }
+    Ok(())
+}
+
+/// How many checked states of the lock files and indexes are recorded by
+/// `check_yanked`.
+const MAX_CHECKED_STATES: usize = 100;
+
+/// Replaces the contents of a record shared by concurrent Cargo processes,
+/// through a temporary file so that the record is never read half written.
+fn replace_record(path: &Path, contents: &str) -> CargoResult<()> {
+    let tmp = path.with_extension("tmp");
+    paths::write(&tmp, contents.as_bytes())?;
+    fs::rename(&tmp, path).chain_err(|| format!("failed to replace `{}`", path.display()))?;
+    Ok(())
+}
+
+/// Checks the resolved graph against the `[workspace.policy]` of the root
+/// manifest, warning about or rejecting the packages which are used in
+/// several semver incompatible versions or are banned.
+fn check_policy(
+    ws: &Workspace<'_>,
+    resolve: &Resolve,
+    policy: &WorkspacePolicy,
+) -> CargoResult<()> {
+    let describe_path = |pkgid: PackageId| resolver::describe_path(&resolve.path_to_top(&pkgid));
+    let mut errors = Vec::new();
+    let mut report = |level: PolicyLevel, msg: String| -> CargoResult<()> {
+        match level {
+            PolicyLevel::Allow => {}
+            PolicyLevel::Warn => ws.config().shell().warn(msg)?,
+            PolicyLevel::Deny => errors.push(msg),
+        }
+        Ok(())
+    };
+
+    let mut pkgids = resolve.iter().collect::<Vec<_>>();
+    pkgids.sort();
+
+    if policy.duplicates != PolicyLevel::Allow {
+        let mut by_name = BTreeMap::new();
+        for &pkgid in pkgids.iter() {
+            if policy.allow_duplicates.iter().any(|n| *n == *pkgid.name()) {
+                continue;
+            }
+            by_name
+                .entry(pkgid.name())
+                .or_insert_with(BTreeMap::new)
+                .entry(semver_compatibility(pkgid.version()))
+                .or_insert_with(Vec::new)
+                .push(pkgid);
+        }
+        for (name, versions) in by_name {
+            if versions.len() < 2 {
+                continue;
+            }
+            let mut msg = format!(
+                "package `{}` is used in several semver incompatible versions",
+                name
+            );
+            for &pkgid in versions.values().flat_map(|v| v.iter()) {
+                msg.push_str("\n\n");
+                msg.push_str(&describe_path(pkgid));
+            }
+            report(policy.duplicates, msg)?;
+        }
+    }
+
+    if policy.bans != PolicyLevel::Allow {
+        for &pkgid in pkgids.iter() {
+            let banned = policy.banned.iter().any(|&(ref name, ref req)| {
+                *name == *pkgid.name() && req.as_ref().map_or(true, |r| r.matches(pkgid.version()))
+            });
+            if !banned {
+                continue;
+            }
+            let mut dependents = pkgids
+                .iter()
+                .filter(|&&p| resolve.deps_not_replaced(p).any(|d| d == pkgid))
+                .peekable();
+            let allowed = dependents.peek().is_some()
+                && dependents.all(|p| policy.allow_banned_in.iter().any(|n| *n == *p.name()));
+            if allowed {
+                continue;
+            }
+            let msg = format!(
+                "package `{}` is banned by the workspace policy\n\n{}",
+                pkgid,
+                describe_path(pkgid)
+            );
+            report(policy.bans, msg)?;
+        }
+    }
+
+    if !errors.is_empty() {
+        failure::bail!("{}", errors.join("\n\n"));
+    }
+    Ok(())
+}
+
 /// Resolves all dependencies for a package using an optional previous instance.
 /// of resolve to guide the resolution process.
 ///
@@ -330,15 +570,26 @@ pub fn resolve_with_previous<'cfg>(
         None => root_replace.to_vec(),
     };
 
+    // Public dependencies are only checked to be unique when a member opts
+    // into specifying them.
+    let check_public_dependencies = ws.members().any(|pkg| {
+        pkg.manifest()
+            .features()
+            .is_enabled(Feature::public_dependency())
+    });
+
+    let members = ws.members().map(|m| m.package_id()).collect::<HashSet<_>>();
+
     ws.preload(registry);
     let mut resolved = resolver::resolve(
         &summaries,
         &replace,
         registry,
         &try_to_use,
+        &members,
         Some(ws.config()),
         warn,
-        false, // TODO: use "public and private dependencies" feature flag
+        check_public_dependencies,
     )?;
     resolved.register_used_patches(registry.patches());
     if register_patches {
@@ -358,6 +609,12 @@ pub fn resolve_with_previous<'cfg>(
                 UNUSED_PATCH_WARNING
             ))?;
         }
+
+        // The policy applies to the graph which the lock file is generated
+        // from, so it's checked before any caller gets to write it.
+        if let Some(policy) = ws.policy() {
+            check_policy(ws, &resolved, policy)?;
+        }
     }
     if let Some(previous) = previous {
         resolved.merge_from(previous)?;
@@ -0,0 +1,909 @@
+//! Collection of the results of the test binaries run by `cargo test`.
+//!
+//! Cargo doesn't get any structured information back from libtest, so when a
+//! report is requested the output of every test binary (and of `rustdoc
+//! --test`) is streamed through a `LibtestParser` while still being relayed
+//! to the user. The parsed results are gathered in `TestResults`, from which
+//! the requested reports are written once all binaries have run.
+
+use std::borrow::Cow;
+use std::fmt::{self, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::core::PackageId;
+use crate::util::errors::{CargoResult, CargoResultExt};
+use crate::util::machine_message::Message;
+use crate::util::paths;
+
+/// An external report to write once all test binaries have run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TestReport {
+    /// A JUnit XML report, written with `--report junit=<path>`.
+    Junit(PathBuf),
+}
+
+impl TestReport {
+    /// Parses the value of the `--report` flag, which has the form
+    /// `<format>=<path>`. Relative paths are relative to `cwd`.
+    pub fn parse(s: &str, cwd: &Path) -> CargoResult<TestReport> {
+        let mut parts = s.splitn(2, '=');
+        let format = parts.next().unwrap();
+        let path = match parts.next() {
+            Some(path) if !path.is_empty() => cwd.join(path),
+            _ => failure::bail!(
+                "invalid report `{}`, expected the form `<format>=<path>`, \
+                 for example `junit=report.xml`",
+                s
+            ),
+        };
+        match format {
+            "junit" => Ok(TestReport::Junit(path)),
+            _ => failure::bail!(
+                "unsupported report format `{}`, the only supported format is `junit`",
+                format
+            ),
+        }
+    }
+}
+
+/// The outcome of a single test, as reported by libtest.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TestOutcome {
+    Passed,
+    Failed,
+    Ignored,
+    /// A benchmark, with its median time and deviation in nanoseconds.
+    Bench {
+        ns_per_iter: u64,
+        deviation: u64,
+    },
+}
+
+/// The number of tests of each outcome, as in libtest's `test result:` line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct TestCounts {
+    pub passed: usize,
+    pub failed: usize,
+    pub ignored: usize,
+    pub measured: usize,
+    pub filtered_out: usize,
+}
+
+impl TestCounts {
+    fn add(&mut self, other: &TestCounts) {
+        self.passed += other.passed;
+        self.failed += other.failed;
+        self.ignored += other.ignored;
+        self.measured += other.measured;
+        self.filtered_out += other.filtered_out;
+    }
+
+    /// Adds the counts of a run of another batch of the tests of the same
+    /// binary, which counts the tests of this one as filtered out.
+    pub fn add_batch(&mut self, other: &TestCounts) {
+        let run = other.passed + other.failed + other.ignored + other.measured;
+        let filtered_out = self.filtered_out.saturating_sub(run);
+        self.add(other);
+        self.filtered_out = filtered_out;
+    }
+}
+
+impl fmt::Display for TestCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} passed; {} failed; {} ignored; {} measured",
+            self.passed, self.failed, self.ignored, self.measured
+        )
+    }
+}
+
+/// The `test-summary` message printed by `cargo test --summary` with
+/// `--message-format=json`.
+#[derive(Serialize)]
+pub struct TestSummary<'a> {
+    pub binaries: Vec<TestBinarySummary<'a>>,
+    pub totals: TestCounts,
+    pub duration: f64,
+    pub failures: Vec<FailedTest<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct TestBinarySummary<'a> {
+    pub package_id: PackageId,
+    pub target: &'a str,
+    pub binary: &'a str,
+    #[serde(flatten)]
+    pub counts: TestCounts,
+    pub duration: f64,
+    pub error: Option<&'a str>,
+}
+
+/// A failed test, or a whole test binary which failed if `name` is `None`.
+#[derive(Serialize)]
+pub struct FailedTest<'a> {
+    pub package_id: PackageId,
+    pub target: &'a str,
+    pub name: Option<String>,
+}
+
+impl<'a> Message for TestSummary<'a> {
+    fn reason(&self) -> &str {
+        "test-summary"
+    }
+}
+
+/// A single test case run by a test binary.
+#[derive(Clone, Debug)]
+pub struct TestCase {
+    /// The name of the test, as printed by libtest.
+    pub name: String,
+    pub outcome: TestOutcome,
+    /// Only available when libtest was asked to report timings with
+    /// `--report-time`.
+    pub duration: Option<Duration>,
+    /// The captured output of a failed test.
+    pub output: String,
+    /// The output of each earlier attempt which failed, when failed tests
+    /// are retried. A test which passed in the end is flaky.
+    pub failed_attempts: Vec<String>,
+}
+
+impl TestCase {
+    fn new(name: &str, outcome: TestOutcome) -> TestCase {
+        TestCase {
+            name: name.to_string(),
+            outcome,
+            duration: None,
+            output: String::new(),
+            failed_attempts: Vec::new(),
+        }
+    }
+
+    pub fn is_flaky(&self) -> bool {
+        self.outcome == TestOutcome::Passed && !self.failed_attempts.is_empty()
+    }
+}
+
+/// The results of running one test binary, or the doctests of one library.
+#[derive(Debug)]
+pub struct BinaryResults {
+    /// The package the binary belongs to.
+    pub package_id: PackageId,
+    /// The name of the target the binary was built from.
+    pub target_name: String,
+    /// A human readable description of the binary, for example
+    /// `target/debug/deps/foo-0123456789abcdef` or `doctests`.
+    pub binary: String,
+    /// How long the binary ran for in total.
+    pub duration: Duration,
+    pub cases: Vec<TestCase>,
+    /// The number of tests libtest announced it would run.
+    pub expected: Option<usize>,
+    /// The counts from libtest's final `test result:` line, if it got that
+    /// far.
+    pub summary: Option<TestCounts>,
+    /// Whether the binary was killed because it ran for too long.
+    pub timed_out: bool,
+    /// The tests known to be still running when the binary was killed.
+    pub unfinished: Vec<String>,
+    /// The error the binary failed with, if it failed.
+    pub error: Option<String>,
+}
+
+impl BinaryResults {
+    pub fn new(package_id: PackageId, target_name: &str, binary: &str) -> BinaryResults {
+        BinaryResults {
+            package_id,
+            target_name: target_name.to_string(),
+            binary: binary.to_string(),
+            duration: Duration::from_secs(0),
+            cases: Vec::new(),
+            expected: None,
+            summary: None,
+            timed_out: false,
+            unfinished: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Name under which this binary is shown in reports.
+    pub fn display_name(&self) -> String {
+        format!("{}::{}", self.package_id.name(), self.target_name)
+    }
+
+    /// The number of tests of each outcome.
+    ///
+    /// These are counted from the individual results, which also account
+    /// for retried tests. With `--quiet` libtest only names the tests which
+    /// failed, so the others are taken from its summary.
+    pub fn counts(&self) -> TestCounts {
+        let mut counts = TestCounts::default();
+        let mut first_run = TestCounts::default();
+        for case in self.cases.iter() {
+            let count = |counts: &mut TestCounts, outcome: &TestOutcome| match outcome {
+                TestOutcome::Passed => counts.passed += 1,
+                TestOutcome::Failed => counts.failed += 1,
+                TestOutcome::Ignored => counts.ignored += 1,
+                TestOutcome::Bench { .. } => counts.measured += 1,
+            };
+            count(&mut counts, &case.outcome);
+            if case.failed_attempts.is_empty() {
+                count(&mut first_run, &case.outcome);
+            } else {
+                first_run.failed += 1;
+            }
+        }
+        if let Some(summary) = self.summary {
+            counts.passed += summary.passed.saturating_sub(first_run.passed);
+            counts.failed += summary.failed.saturating_sub(first_run.failed);
+            counts.ignored += summary.ignored.saturating_sub(first_run.ignored);
+            counts.measured += summary.measured.saturating_sub(first_run.measured);
+            counts.filtered_out = summary.filtered_out;
+        }
+        counts
+    }
+
+    /// An empty set of results for another run of the same binary.
+    pub fn empty_copy(&self) -> BinaryResults {
+        BinaryResults::new(self.package_id, &self.target_name, &self.binary)
+    }
+
+    /// The names of the tests which failed.
+    pub fn failed_tests(&self) -> Vec<String> {
+        self.cases
+            .iter()
+            .filter(|c| c.outcome == TestOutcome::Failed)
+            .map(|c| c.name.clone())
+            .collect()
+    }
+
+    /// Updates the results with those of a rerun of the tests named
+    /// `rerun_names`, the earlier failures being kept in `failed_attempts`.
+    pub fn merge_rerun(&mut self, rerun_names: &[String], mut rerun: BinaryResults) {
+        // With `--quiet` only the failed tests are named, so the others
+        // passed if the rerun got as far as its summary.
+        if rerun.summary.is_some() {
+            for name in rerun_names {
+                if !rerun.cases.iter().any(|c| &c.name == name) {
+                    rerun.cases.push(TestCase::new(name, TestOutcome::Passed));
+                }
+            }
+        }
+        self.duration += rerun.duration;
+        for new in rerun.cases {
+            match self.cases.iter_mut().find(|c| c.name == new.name) {
+                Some(case) => {
+                    let output = mem::replace(&mut case.output, new.output);
+                    case.failed_attempts.push(output);
+                    case.outcome = new.outcome;
+                    case.duration = new.duration;
+                }
+                None => self.cases.push(new),
+            }
+        }
+    }
+
+    fn count(&self, f: impl Fn(&TestOutcome) -> bool) -> usize {
+        self.cases.iter().filter(|c| f(&c.outcome)).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.count(|o| *o == TestOutcome::Failed)
+    }
+}
+
+/// The results of every test binary run during one `cargo test` invocation.
+#[derive(Debug, Default)]
+pub struct TestResults {
+    pub binaries: Vec<BinaryResults>,
+}
+
+impl TestResults {
+    pub fn new() -> TestResults {
+        TestResults::default()
+    }
+
+    pub fn push(&mut self, results: BinaryResults) {
+        self.binaries.push(results);
+    }
+
+    /// The number of tests of each outcome, across all binaries.
+    pub fn totals(&self) -> TestCounts {
+        let mut totals = TestCounts::default();
+        for binary in self.binaries.iter() {
+            totals.add(&binary.counts());
+        }
+        totals
+    }
+
+    /// How long all the binaries ran for.
+    pub fn duration(&self) -> Duration {
+        self.binaries.iter().map(|b| b.duration).sum()
+    }
+
+    /// The binaries which failed along with the tests which failed in them,
+    /// which is empty when a binary failed without any failing test.
+    pub fn failures(&self) -> impl Iterator<Item = (&BinaryResults, Vec<String>)> {
+        self.binaries
+            .iter()
+            .filter(|b| b.error.is_some() || b.failed() > 0)
+            .map(|b| (b, b.failed_tests()))
+    }
+
+    /// The tests which only passed after being retried.
+    pub fn flaky(&self) -> impl Iterator<Item = (&BinaryResults, &TestCase)> {
+        self.binaries
+            .iter()
+            .flat_map(|b| b.cases.iter().map(move |c| (b, c)))
+            .filter(|(_, c)| c.is_flaky())
+    }
+
+    /// Writes `report` to disk.
+    pub fn write_report(&self, report: &TestReport) -> CargoResult<()> {
+        match report {
+            TestReport::Junit(path) => {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent).chain_err(|| {
+                        format!("failed to create directory `{}`", parent.display())
+                    })?;
+                }
+                paths::write(path, self.to_junit().as_bytes())
+                    .chain_err(|| format!("failed to write test report `{}`", path.display()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Renders the results as a JUnit XML document, with one `testsuite` per
+    /// test binary.
+    pub fn to_junit(&self) -> String {
+        let mut xml = String::new();
+        let tests: usize = self.binaries.iter().map(junit_cases).sum();
+        let failures: usize = self.binaries.iter().map(junit_failures).sum();
+        let time: Duration = self.binaries.iter().map(|b| b.duration).sum();
+        xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        writeln!(
+            xml,
+            "<testsuites name=\"cargo test\" tests=\"{}\" failures=\"{}\" errors=\"0\" time=\"{}\">",
+            tests,
+            failures,
+            secs(time),
+        )
+        .unwrap();
+        for binary in &self.binaries {
+            let name = escape_xml(&binary.display_name());
+            writeln!(
+                xml,
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" \
+                 skipped=\"{}\" time=\"{}\">",
+                name,
+                junit_cases(binary),
+                junit_failures(binary),
+                binary.counts().ignored,
+                secs(binary.duration),
+            )
+            .unwrap();
+            xml.push_str("    <properties>\n");
+            for (key, value) in &[
+                ("package", binary.package_id.name().as_str()),
+                ("target", binary.target_name.as_str()),
+                ("binary", binary.binary.as_str()),
+            ] {
+                writeln!(
+                    xml,
+                    "      <property name=\"{}\" value=\"{}\"/>",
+                    key,
+                    escape_xml(value)
+                )
+                .unwrap();
+            }
+            xml.push_str("    </properties>\n");
+            for case in &binary.cases {
+                write!(
+                    xml,
+                    "    <testcase name=\"{}\" classname=\"{}\"",
+                    escape_xml(&case.name),
+                    name
+                )
+                .unwrap();
+                if let Some(duration) = case.duration {
+                    write!(xml, " time=\"{}\"", secs(duration)).unwrap();
+                }
+                match case.outcome {
+                    TestOutcome::Passed if case.is_flaky() => {
+                        xml.push_str(">\n");
+                        for output in case.failed_attempts.iter() {
+                            writeln!(
+                                xml,
+                                "      <flakyFailure message=\"test failed\">{}</flakyFailure>",
+                                escape_xml(output)
+                            )
+                            .unwrap();
+                        }
+                        xml.push_str("    </testcase>\n");
+                    }
+                    TestOutcome::Passed | TestOutcome::Bench { .. } => xml.push_str("/>\n"),
+                    TestOutcome::Ignored => xml.push_str(">\n      <skipped/>\n    </testcase>\n"),
+                    TestOutcome::Failed => {
+                        writeln!(
+                            xml,
+                            ">\n      <failure message=\"test failed\">{}</failure>\n    </testcase>",
+                            escape_xml(&case.output)
+                        ).unwrap();
+                    }
+                }
+            }
+            // A binary which failed without any of its tests failing (for
+            // example because it crashed) is reported as a failing test case
+            // of its own so it isn't lost.
+            if let Some(error) = binary.error.as_ref().filter(|_| binary.failed() == 0) {
+                writeln!(
+                    xml,
+                    "    <testcase name=\"{}\" classname=\"{}\">\n      \
+                     <failure message=\"{}\"/>\n    </testcase>",
+                    escape_xml(&binary.binary),
+                    name,
+                    escape_xml(error)
+                )
+                .unwrap();
+            }
+            xml.push_str("  </testsuite>\n");
+        }
+        xml.push_str("</testsuites>\n");
+        xml
+    }
+}
+
+/// The number of tests of a binary, which with `--quiet` includes the
+/// tests only counted in libtest's summary.
+fn junit_cases(binary: &BinaryResults) -> usize {
+    let counts = binary.counts();
+    counts.passed + counts.failed + counts.ignored + counts.measured + junit_failures(binary)
+        - binary.failed()
+}
+
+fn junit_failures(binary: &BinaryResults) -> usize {
+    match binary.error {
+        Some(_) if binary.failed() == 0 => 1,
+        _ => binary.failed(),
+    }
+}
+
+fn secs(duration: Duration) -> String {
+    format!("{}.{:03}", duration.as_secs(), duration.subsec_millis())
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters aren't allowed in XML 1.0 documents.
+            '\n' | '\r' | '\t' => escaped.push(c),
+            c if (c as u32) < 0x20 => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Incrementally parses the human readable output of a libtest binary or of
+/// `rustdoc --test`, one line at a time.
+#[derive(Default)]
+pub struct LibtestParser {
+    cases: Vec<TestCase>,
+    /// The number of tests from the `running N tests` header.
+    expected: Option<usize>,
+    /// The counts from the `test result:` line.
+    summary: Option<TestCounts>,
+    /// Tests which were announced without a result yet, either by libtest's
+    /// warning about long running tests or, when tests run on one thread, by
+    /// the name of the test being printed before it runs.
+    started: Vec<String>,
+    /// The test whose captured output is currently being read, from the
+    /// `---- name stdout ----` sections printed after a failure.
+    capturing: Option<(String, Vec<String>)>,
+    /// Whether the last section started was libtest's `failures:` rather
+    /// than `successes:`.
+    in_failures: bool,
+    /// The failed tests named in the `failures:` sections, with their
+    /// output. These are the only names printed with `--quiet`.
+    failures: Vec<(String, String)>,
+}
+
+impl LibtestParser {
+    pub fn new() -> LibtestParser {
+        LibtestParser::default()
+    }
+
+    pub fn line(&mut self, line: &str) {
+        let line = strip_ansi(line);
+        let line = &*line;
+        if line.starts_with("---- ") && line.ends_with(" ----") {
+            self.finish_capture();
+            let header = &line[5..line.len() - 5];
+            let name = match header.rfind(' ') {
+                Some(i) => &header[..i],
+                None => header,
+            };
+            self.capturing = Some((name.to_string(), Vec::new()));
+            return;
+        }
+        if let Some((_, lines)) = &mut self.capturing {
+            if line == "failures:" || line == "successes:" || line.starts_with("test result: ") {
+                self.finish_capture();
+            } else {
+                lines.push(line.to_string());
+                return;
+            }
+        }
+        if line == "failures:" || line == "successes:" {
+            self.in_failures = line == "failures:";
+        } else if self.in_failures && line.starts_with("    ") {
+            // The list of the names of the failed tests.
+            self.failed(line.trim(), None);
+        } else if let Some(case) = parse_test_line(line) {
+            self.cases.push(case);
+        } else if let Some(name) = parse_started_line(line) {
+            self.started.push(name.to_string());
+        } else if line.starts_with("test result: ") {
+            self.summary = parse_summary_line(line);
+        } else if line.starts_with("running ") {
+            let count = line["running ".len()..]
+                .trim_end_matches(" tests")
+                .trim_end_matches(" test");
+            if let Ok(count) = count.parse() {
+                self.expected = Some(count);
+            }
+        }
+    }
+
+    /// The number of tests libtest announced it would run.
+    pub fn expected(&self) -> Option<usize> {
+        self.expected
+    }
+
+    /// The counts from libtest's `test result:` line.
+    pub fn summary(&self) -> Option<TestCounts> {
+        self.summary
+    }
+
+    /// The tests which are known to have started but haven't finished yet.
+    pub fn unfinished(&self) -> Vec<String> {
+        let mut unfinished = Vec::new();
+        for name in self.started.iter() {
+            if !self.cases.iter().any(|c| &c.name == name) && !unfinished.contains(name) {
+                unfinished.push(name.clone());
+            }
+        }
+        unfinished
+    }
+
+    fn finish_capture(&mut self) {
+        let (name, mut lines) = match self.capturing.take() {
+            Some(capture) => capture,
+            None => return,
+        };
+        while lines.last().map_or(false, |l| l.trim().is_empty()) {
+            lines.pop();
+        }
+        let output = lines.join("\n");
+        if self.in_failures {
+            self.failed(&name, Some(output.clone()));
+        }
+        if let Some(case) = self.cases.iter_mut().rev().find(|c| c.name == name) {
+            case.output = output;
+        }
+    }
+
+    /// Records a test named in a `failures:` section.
+    fn failed(&mut self, name: &str, output: Option<String>) {
+        match self.failures.iter_mut().find(|(n, _)| n == name) {
+            Some((_, o)) => {
+                if let Some(output) = output {
+                    *o = output;
+                }
+            }
+            None => self
+                .failures
+                .push((name.to_string(), output.unwrap_or_default())),
+        }
+    }
+
+    /// Returns the results of the individual tests, including the failed
+    /// tests which were only named in the `failures:` sections.
+    pub fn finish(mut self) -> Vec<TestCase> {
+        self.finish_capture();
+        for (name, output) in mem::replace(&mut self.failures, Vec::new()) {
+            if !self.cases.iter().any(|c| c.name == name) {
+                let mut case = TestCase::new(&name, TestOutcome::Failed);
+                case.output = output;
+                self.cases.push(case);
+            }
+        }
+        self.cases
+    }
+}
+
+/// Removes the ANSI escape codes of libtest's colored output.
+fn strip_ansi(line: &str) -> Cow<'_, str> {
+    if !line.contains('\x1b') {
+        return Cow::Borrowed(line);
+    }
+    let mut stripped = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // An escape code like `ESC[32m` ends with a letter.
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            stripped.push(c);
+        }
+    }
+    Cow::Owned(stripped)
+}
+
+/// Parses lines of the form `test <name> ... <result>`.
+fn parse_test_line(line: &str) -> Option<TestCase> {
+    if !line.starts_with("test ") {
+        return None;
+    }
+    let line = &line[5..];
+    let sep = line.rfind(" ... ")?;
+    let mut name = &line[..sep];
+    if name.ends_with(" - should panic") {
+        name = &name[..name.len() - " - should panic".len()];
+    }
+    let result = line[sep + 5..].trim();
+
+    // `--report-time` appends the duration, as in `ok <0.012s>`.
+    let (result, duration) = match result.find(" <") {
+        Some(i) if result.ends_with("s>") => {
+            let secs = result[i + 2..result.len() - 2].parse::<f64>().ok();
+            (&result[..i], secs.map(Duration::from_secs_f64))
+        }
+        _ => (result, None),
+    };
+
+    let outcome = if result == "ok" {
+        TestOutcome::Passed
+    } else if result == "FAILED" {
+        TestOutcome::Failed
+    } else if result == "ignored" || result.starts_with("ignored, ") {
+        TestOutcome::Ignored
+    } else if result.starts_with("bench:") {
+        parse_bench(&result["bench:".len()..])?
+    } else {
+        return None;
+    };
+    let mut case = TestCase::new(name, outcome);
+    case.duration = duration;
+    Some(case)
+}
+
+/// Parses the `test result: ok. 1 passed; 0 failed; ...` line printed by
+/// libtest once all tests have run.
+fn parse_summary_line(line: &str) -> Option<TestCounts> {
+    let counts = &line[line.find(". ")? + 2..];
+    let mut summary = TestCounts::default();
+    for part in counts.split(';') {
+        let mut words = part.split_whitespace();
+        let count = match words.next().and_then(|n| n.parse::<usize>().ok()) {
+            Some(count) => count,
+            None => continue,
+        };
+        match words.next() {
+            Some("passed") => summary.passed = count,
+            Some("failed") => summary.failed = count,
+            Some("ignored") => summary.ignored = count,
+            Some("measured") => summary.measured = count,
+            Some("filtered") => summary.filtered_out = count,
+            _ => {}
+        }
+    }
+    Some(summary)
+}
+
+/// Parses the lines announcing a test without its result: `test <name> ...`
+/// printed before a test runs when tests run on one thread, and libtest's
+/// `test <name> has been running for over 60 seconds` warning.
+fn parse_started_line(line: &str) -> Option<&str> {
+    let line = line.trim_end();
+    if !line.starts_with("test ") {
+        return None;
+    }
+    let name = if line.ends_with(" ...") {
+        &line[5..line.len() - " ...".len()]
+    } else if line.ends_with(" has been running for over 60 seconds") {
+        &line[5..line.len() - " has been running for over 60 seconds".len()]
+    } else {
+        return None;
+    };
+    Some(name.trim_end_matches(" - should panic"))
+}
+
+/// Parses the `   1,234 ns/iter (+/- 56)` part of a benchmark result.
+fn parse_bench(s: &str) -> Option<TestOutcome> {
+    let mut parts = s.split_whitespace();
+    let ns = parts.next()?;
+    if parts.next()? != "ns/iter" || parts.next()? != "(+/-" {
+        return None;
+    }
+    let deviation = parts.next()?.trim_end_matches(')');
+    let number = |s: &str| s.replace(',', "").parse::<u64>().ok();
+    Some(TestOutcome::Bench {
+        ns_per_iter: number(ns)?,
+        deviation: number(deviation)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::SourceId;
+    use crate::sources::CRATES_IO_INDEX;
+    use crate::util::ToUrl;
+
+    #[test]
+    fn parses_libtest_output() {
+        let output = "\
+running 4 tests
+test a ... ok
+test b::c ... FAILED
+test d ... ignored
+test e - should panic ... ok <0.250s>
+
+failures:
+
+---- b::c stdout ----
+thread 'b::c' panicked at 'assertion failed', src/lib.rs:3:5
+
+
+failures:
+    b::c
+
+test result: FAILED. 2 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out
+";
+        let mut parser = LibtestParser::new();
+        for line in output.lines() {
+            parser.line(line);
+        }
+        let cases = parser.finish();
+        assert_eq!(cases.len(), 4);
+        assert_eq!(cases[0].outcome, TestOutcome::Passed);
+        assert_eq!(cases[1].name, "b::c");
+        assert_eq!(cases[1].outcome, TestOutcome::Failed);
+        assert_eq!(
+            cases[1].output,
+            "thread 'b::c' panicked at 'assertion failed', src/lib.rs:3:5"
+        );
+        assert_eq!(cases[2].outcome, TestOutcome::Ignored);
+        assert_eq!(cases[3].name, "e");
+        assert_eq!(cases[3].duration, Some(Duration::from_millis(250)));
+    }
+
+    #[test]
+    fn parses_doctests_and_benches() {
+        let case = parse_test_line("test src/lib.rs - foo (line 3) ... ok").unwrap();
+        assert_eq!(case.name, "src/lib.rs - foo (line 3)");
+        let case = parse_test_line("test bench_a ... bench:       1,234 ns/iter (+/- 56)").unwrap();
+        assert_eq!(
+            case.outcome,
+            TestOutcome::Bench {
+                ns_per_iter: 1234,
+                deviation: 56
+            }
+        );
+    }
+
+    #[test]
+    fn parses_summary() {
+        let line = "test result: FAILED. 2 passed; 1 failed; 3 ignored; 0 measured; \
+                    4 filtered out; finished in 0.01s";
+        assert_eq!(
+            parse_summary_line(line),
+            Some(TestCounts {
+                passed: 2,
+                failed: 1,
+                ignored: 3,
+                measured: 0,
+                filtered_out: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn tracks_unfinished_tests() {
+        let mut parser = LibtestParser::new();
+        parser.line("running 3 tests");
+        parser.line("test a ... ok");
+        parser.line("test b has been running for over 60 seconds");
+        parser.line("test c ... ");
+        assert_eq!(parser.expected(), Some(3));
+        assert_eq!(parser.unfinished(), vec!["b".to_string(), "c".to_string()]);
+    }
+
+    #[test]
+    fn parses_colored_output() {
+        let mut parser = LibtestParser::new();
+        parser.line("test a ... \x1b[32mok\x1b[0m");
+        parser.line("test result: \x1b[31mFAILED\x1b[0m. 1 passed; 0 failed");
+        assert_eq!(parser.summary().map(|s| s.passed), Some(1));
+        let cases = parser.finish();
+        assert_eq!(cases[0].name, "a");
+        assert_eq!(cases[0].outcome, TestOutcome::Passed);
+    }
+
+    #[test]
+    fn parses_quiet_output() {
+        let output = "\
+running 3 tests
+.Fi
+failures:
+
+---- b::c stdout ----
+thread 'b::c' panicked at 'assertion failed', src/lib.rs:3:5
+
+
+failures:
+    b::c
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 2 filtered out
+";
+        let mut parser = LibtestParser::new();
+        for line in output.lines() {
+            parser.line(line);
+        }
+        let source = SourceId::for_registry(&CRATES_IO_INDEX.to_url().unwrap()).unwrap();
+        let mut binary = BinaryResults::new(
+            PackageId::new("foo", "0.1.0", source).unwrap(),
+            "foo",
+            "foo",
+        );
+        binary.summary = parser.summary();
+        binary.cases = parser.finish();
+        assert_eq!(binary.cases.len(), 1);
+        assert_eq!(binary.cases[0].name, "b::c");
+        assert_eq!(binary.cases[0].outcome, TestOutcome::Failed);
+        assert_eq!(
+            binary.cases[0].output,
+            "thread 'b::c' panicked at 'assertion failed', src/lib.rs:3:5"
+        );
+        assert_eq!(binary.failed_tests(), vec!["b::c".to_string()]);
+        assert_eq!(
+            binary.counts(),
+            TestCounts {
+                passed: 1,
+                failed: 1,
+                ignored: 1,
+                measured: 0,
+                filtered_out: 2,
+            }
+        );
+
+        // A quiet rerun which passed only prints its summary.
+        let mut rerun = binary.empty_copy();
+        rerun.summary = parse_summary_line("test result: ok. 1 passed; 0 failed");
+        binary.merge_rerun(&["b::c".to_string()], rerun);
+        assert!(binary.cases[0].is_flaky());
+        assert_eq!(binary.counts().passed, 2);
+        assert_eq!(binary.counts().failed, 0);
+    }
+
+    #[test]
+    fn escapes_xml() {
+        assert_eq!(
+            escape_xml("<a & \"b\">\u{1b}"),
+            "&lt;a &amp; &quot;b&quot;&gt;"
+        );
+    }
+}
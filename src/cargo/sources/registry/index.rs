@@ -1,15 +1,38 @@
+use std::cmp;
 use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::slice;
 use std::str;
+use std::sync::Mutex;
 
-use log::{info, trace};
-use semver::Version;
+use log::{debug, info, trace};
+use semver::{Version, VersionReq};
 
 use crate::core::dependency::Dependency;
-use crate::core::{PackageId, SourceId, Summary};
+use crate::core::{InternedString, PackageId, PlatformFeatures, SourceId, Summary};
 use crate::sources::registry::RegistryData;
 use crate::sources::registry::{RegistryPackage, INDEX_LOCK};
-use crate::util::{internal, CargoResult, Config, Filesystem, ToSemver};
+use crate::util::{
+    internal, parse_rust_version, CargoResult, Config, FileLock, Filesystem, ToSemver,
+};
+
+/// The directory of the index the parsed index files are cached in, see
+/// `read_cache`.
+const CACHE_DIR: &str = ".cache";
+/// The start of the first line of a cached index file, which is followed by
+/// the version of Cargo which wrote it. Changes whenever the format of the
+/// cache changes.
+const CACHE_HEADER: &str = "cargo-index-cache-v2";
+/// The start of the names of the directories in `CACHE_DIR` which hold the
+/// index files of one commit of the index, which are followed by the commit.
+/// Anything else in `CACHE_DIR` is left alone.
+const CACHE_PREFIX: &str = "v2-";
+/// The most packages `RegistryIndex::prefetch` loads ahead of the resolver,
+/// so that the summaries of a huge graph which is never asked for in full
+/// aren't all kept around.
+const PREFETCH_LIMIT: usize = 512;
 
 /// Crates.io treats hyphen and underscores as interchangeable, but the index and old Cargo do not.
 /// Therefore, the index must store uncanonicalized version of the name so old Cargo's can find it.
@@ -100,6 +123,10 @@ pub struct RegistryIndex<'cfg> {
     source_id: SourceId,
     path: Filesystem,
     cache: HashMap<&'static str, Vec<(Summary, bool)>>,
+    // The packages `prefetch` has already walked, at most `PREFETCH_LIMIT`.
+    prefetch_walked: HashSet<String>,
+    // Whether the cached files of other commits of the index were removed.
+    cache_pruned: bool,
     // `(name, vers)` -> `checksum`
     hashes: HashMap<&'static str, HashMap<Version, String>>,
     config: &'cfg Config,
@@ -117,6 +144,8 @@ impl<'cfg> RegistryIndex<'cfg> {
             source_id,
             path: path.clone(),
             cache: HashMap::new(),
+            prefetch_walked: HashSet::new(),
+            cache_pruned: false,
             hashes: HashMap::new(),
             config,
             locked,
@@ -155,6 +184,97 @@ impl<'cfg> RegistryIndex<'cfg> {
         Ok(&self.cache[name])
     }
 
+    /// Loads the summaries of the package of `dep` and of the packages it
+    /// depends on, transitively, ahead of the resolver asking for them.
+    ///
+    /// The index files are read from the git repository of the index and
+    /// parsed on several threads at once, and cached on disk. Only the
+    /// dependencies of the newest version matching the requirement are
+    /// followed, which is the locked version if `dep` comes from the lock
+    /// file. Anything else is loaded when it's asked for as usual.
+    pub fn prefetch(&mut self, dep: &Dependency, load: &mut dyn RegistryData) -> CargoResult<()> {
+        let name = dep.package_name().as_str();
+        if self.cache.contains_key(name)
+            || self.prefetch_walked.len() >= PREFETCH_LIMIT
+            || !self.prefetch_walked.insert(name.to_string())
+        {
+            return Ok(());
+        }
+        load.prepare()?;
+        // Indexes which aren't git repositories are cheap to read anyway,
+        // and a repository which hasn't been fetched yet has nothing to
+        // prefetch.
+        let commit = match load.index_commit() {
+            Ok(Some(commit)) => commit,
+            _ => return Ok(()),
+        };
+        let (root, _lock) = match self.open_root() {
+            Some(root) => root,
+            None => return Ok(()),
+        };
+        let cache = self.cache_dir(&root, commit);
+
+        let mut queue = vec![(name.to_string(), dep.version_req().clone())];
+        while !queue.is_empty() {
+            let files = prefetch_files(&root, &cache, commit, &queue)?;
+            queue.clear();
+            for file in files {
+                for (dep, req) in file.deps {
+                    if self.prefetch_walked.len() < PREFETCH_LIMIT
+                        && !self.cache.contains_key(dep.as_str())
+                        && self.prefetch_walked.insert(dep.clone())
+                    {
+                        queue.push((dep, req));
+                    }
+                }
+                let summaries = self.to_summaries(&file.name, file.packages);
+                let name = InternedString::new(&file.name).as_str();
+                self.cache.insert(name, summaries);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the root directory of the index, along with the lock on it
+    /// if the index is locked, or `None` if the lock can't be acquired.
+    fn open_root(&self) -> Option<(PathBuf, Option<FileLock>)> {
+        if self.locked {
+            let lock = self
+                .path
+                .open_ro(Path::new(INDEX_LOCK), self.config, "the registry index")
+                .ok()?;
+            Some((lock.path().parent().unwrap().to_path_buf(), Some(lock)))
+        } else {
+            Some((self.path.clone().into_path_unlocked(), None))
+        }
+    }
+
+    /// Returns the directory the index files of the `commit` of the index at
+    /// `root` are cached in, removing the ones of other commits the first
+    /// time around. Only the directories named with `CACHE_PREFIX` are
+    /// removed.
+    ///
+    /// The index can only be updated while nobody holds the lock on it, so
+    /// the other commits aren't used by anyone anymore.
+    fn cache_dir(&mut self, root: &Path, commit: git2::Oid) -> PathBuf {
+        let commit = format!("{}{}", CACHE_PREFIX, commit);
+        if !self.cache_pruned {
+            self.cache_pruned = true;
+            let entries = fs::read_dir(root.join(CACHE_DIR)).into_iter().flatten();
+            for entry in entries.filter_map(|e| e.ok()) {
+                match entry.file_name().to_str() {
+                    Some(name) if name.starts_with(CACHE_PREFIX) && name != commit => {}
+                    _ => continue,
+                }
+                let path = entry.path();
+                if let Err(e) = fs::remove_dir_all(&path) {
+                    debug!("failed to remove `{}`: {}", path.display(), e);
+                }
+            }
+        }
+        root.join(CACHE_DIR).join(commit)
+    }
+
     fn load_summaries(
         &mut self,
         name: &str,
@@ -166,57 +286,26 @@ impl<'cfg> RegistryIndex<'cfg> {
         // function inside *also* wants to acquire a lock. See an instance of
         // this on #5551.
         load.prepare()?;
-        let (root, _lock) = if self.locked {
-            let lock = self
-                .path
-                .open_ro(Path::new(INDEX_LOCK), self.config, "the registry index");
-            match lock {
-                Ok(lock) => (lock.path().parent().unwrap().to_path_buf(), Some(lock)),
-                Err(_) => return Ok(Vec::new()),
-            }
-        } else {
-            (self.path.clone().into_path_unlocked(), None)
+        let (root, _lock) = match self.open_root() {
+            Some(root) => root,
+            None => return Ok(Vec::new()),
         };
+        let cache = load
+            .index_commit()
+            .unwrap_or(None)
+            .map(|commit| self.cache_dir(&root, commit));
 
-        let fs_name = name
-            .chars()
-            .flat_map(|c| c.to_lowercase())
-            .collect::<String>();
-
-        // See module comment for why this is structured the way it is.
-        let raw_path = match fs_name.len() {
-            1 => format!("1/{}", fs_name),
-            2 => format!("2/{}", fs_name),
-            3 => format!("3/{}/{}", &fs_name[..1], fs_name),
-            _ => format!("{}/{}/{}", &fs_name[0..2], &fs_name[2..4], fs_name),
-        };
-        let mut ret = Vec::new();
+        let raw_path = index_file_path(name);
         for path in UncanonicalizedIter::new(&raw_path).take(1024) {
+            if let Some(packages) = cache.as_ref().and_then(|c| read_cache(c, &path)) {
+                return Ok(self.to_summaries(name, packages));
+            }
+
+            let mut ret = Vec::new();
             let mut hit_closure = false;
             let err = load.load(&root, Path::new(&path), &mut |contents| {
                 hit_closure = true;
-                let contents = str::from_utf8(contents)
-                    .map_err(|_| failure::format_err!("registry index file was not valid utf-8"))?;
-                ret.reserve(contents.lines().count());
-                let lines = contents.lines().map(|s| s.trim()).filter(|l| !l.is_empty());
-
-                // Attempt forwards-compatibility on the index by ignoring
-                // everything that we ourselves don't understand, that should
-                // allow future cargo implementations to break the
-                // interpretation of each line here and older cargo will simply
-                // ignore the new lines.
-                ret.extend(lines.filter_map(|line| {
-                    let (summary, locked) = match self.parse_registry_package(line) {
-                        Ok(p) => p,
-                        Err(e) => {
-                            info!("failed to parse `{}` registry package: {}", name, e);
-                            trace!("line: {}", line);
-                            return None;
-                        }
-                    };
-                    Some((summary, locked))
-                }));
-
+                ret = parse_index_file(name, contents)?;
                 Ok(())
             });
 
@@ -225,36 +314,81 @@ impl<'cfg> RegistryIndex<'cfg> {
             // closure though then we care about those errors.
             if hit_closure {
                 err?;
+                if let Some(ref cache) = cache {
+                    write_cache(cache, &path, &ret);
+                }
                 // Crates.io ensures that there is only one hyphen and underscore equivalent
                 // result in the index so return when we find it.
-                return Ok(ret);
+                return Ok(self.to_summaries(name, ret));
             }
         }
 
-        Ok(ret)
+        Ok(Vec::new())
     }
 
-    /// Parses a line from the registry's index file into a `Summary` for a package.
+    /// Converts the parsed lines of the index file of the package `name`
+    /// into summaries, leaving out the ones which aren't valid.
+    fn to_summaries(
+        &mut self,
+        name: &str,
+        packages: Vec<RegistryPackage<'_>>,
+    ) -> Vec<(Summary, bool)> {
+        packages
+            .into_iter()
+            .filter_map(|package| match self.to_summary(package) {
+                Ok(p) => Some(p),
+                Err(e) => {
+                    info!("failed to parse `{}` registry package: {}", name, e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Converts a line from the registry's index file into a `Summary` for a
+    /// package.
     ///
     /// The returned boolean is whether or not the summary has been yanked.
-    fn parse_registry_package(&mut self, line: &str) -> CargoResult<(Summary, bool)> {
+    fn to_summary(&mut self, package: RegistryPackage<'_>) -> CargoResult<(Summary, bool)> {
         let RegistryPackage {
             name,
             vers,
             cksum,
             deps,
-            features,
+            mut features,
+            features2,
+            target_features,
             yanked,
             links,
-        } = serde_json::from_str(line)?;
+            rust_version,
+        } = package;
+        if let Some(features2) = features2 {
+            features.extend(features2);
+        }
         let pkgid = PackageId::new(&name, &vers, self.source_id)?;
         let name = pkgid.name();
         let deps = deps
             .into_iter()
             .map(|dep| dep.into_dep(self.source_id))
             .collect::<CargoResult<Vec<_>>>()?;
-        let summary = Summary::new(pkgid, deps, &features, links, false)?;
+        let platform_features = target_features
+            .into_iter()
+            .flatten()
+            .map(|(platform, features)| Ok((platform.parse()?, features)))
+            .collect::<CargoResult<PlatformFeatures>>()?;
+        let summary = Summary::with_platform_features(
+            pkgid,
+            deps,
+            &features,
+            &platform_features,
+            links,
+            false,
+        )?;
         let summary = summary.set_checksum(cksum.clone());
+        // A malformed `rust_version` shouldn't make the package unusable,
+        // it's then treated as if none was declared.
+        let rust_version = rust_version.and_then(|v| parse_rust_version(&v).ok());
+        let summary = summary.set_rust_version(rust_version);
         self.hashes
             .entry(name.as_str())
             .or_insert_with(HashMap::new)
@@ -286,6 +420,34 @@ impl<'cfg> RegistryIndex<'cfg> {
         Ok(())
     }
 
+    /// Like `query_inner`, but only passes the summaries matching `dep` which
+    /// have been yanked and aren't in the whitelist.
+    pub fn query_yanked(
+        &mut self,
+        dep: &Dependency,
+        load: &mut dyn RegistryData,
+        yanked_whitelist: &HashSet<PackageId>,
+        f: &mut dyn FnMut(Summary),
+    ) -> CargoResult<()> {
+        let name = dep.package_name().as_str();
+        let summaries = self.summaries(name, load)?;
+        for &(ref summary, yanked) in summaries.iter() {
+            if yanked && !yanked_whitelist.contains(&summary.package_id()) && dep.matches(summary) {
+                f(summary.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns whether the package `pkg` has been yanked, regardless of the
+    /// whitelist.
+    pub fn is_yanked(&mut self, pkg: PackageId, load: &mut dyn RegistryData) -> CargoResult<bool> {
+        let summaries = self.summaries(pkg.name().as_str(), load)?;
+        Ok(summaries
+            .iter()
+            .any(|&(ref summary, yanked)| yanked && summary.package_id() == pkg))
+    }
+
     fn query_inner_with_online(
         &mut self,
         dep: &Dependency,
@@ -337,3 +499,196 @@ impl<'cfg> RegistryIndex<'cfg> {
         Ok(count)
     }
 }
+
+/// Returns the path of the index file of the package `name` relative to the
+/// root of the index, with the name spelled as given.
+fn index_file_path(name: &str) -> String {
+    let fs_name = name
+        .chars()
+        .flat_map(|c| c.to_lowercase())
+        .collect::<String>();
+
+    // See module comment for why this is structured the way it is.
+    match fs_name.len() {
+        1 => format!("1/{}", fs_name),
+        2 => format!("2/{}", fs_name),
+        3 => format!("3/{}/{}", &fs_name[..1], fs_name),
+        _ => format!("{}/{}/{}", &fs_name[0..2], &fs_name[2..4], fs_name),
+    }
+}
+
+/// Parses the lines of the index file of the package `name`.
+fn parse_index_file(name: &str, contents: &[u8]) -> CargoResult<Vec<RegistryPackage<'static>>> {
+    let contents = str::from_utf8(contents)
+        .map_err(|_| failure::format_err!("registry index file was not valid utf-8"))?;
+    let lines = contents.lines().map(|s| s.trim()).filter(|l| !l.is_empty());
+
+    // Attempt forwards-compatibility on the index by ignoring
+    // everything that we ourselves don't understand, that should
+    // allow future cargo implementations to break the
+    // interpretation of each line here and older cargo will simply
+    // ignore the new lines.
+    Ok(lines
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(p) => Some(p),
+            Err(e) => {
+                info!("failed to parse `{}` registry package: {}", name, e);
+                trace!("line: {}", line);
+                None
+            }
+        })
+        .collect())
+}
+
+/// Returns the first line of the cached index files written by this version
+/// of Cargo. Other versions may understand other keys of the index, so they
+/// don't share the cache.
+fn cache_header() -> String {
+    format!("{} {}", CACHE_HEADER, crate::version())
+}
+
+/// Returns the parsed lines of the index file at `path`, if they were
+/// cached in `cache` by this version of Cargo.
+///
+/// Index files of git indexes are cached below `CACHE_DIR`, in a directory
+/// per commit of the index, as the lines Cargo understands with only the
+/// keys it knows about. That way they neither have to be looked up in and
+/// inflated from the git repository, nor checked line by line again until
+/// the index is updated.
+fn read_cache(cache: &Path, path: &str) -> Option<Vec<RegistryPackage<'static>>> {
+    let contents = fs::read_to_string(cache.join(path)).ok()?;
+    let mut lines = contents.lines();
+    if lines.next()? != cache_header() {
+        return None;
+    }
+    lines.map(|line| serde_json::from_str(line).ok()).collect()
+}
+
+/// Caches the parsed lines of the index file at `path` in `cache`. Failing
+/// to do so only costs reading the file from the git repository again, so
+/// errors are ignored.
+fn write_cache(cache: &Path, path: &str, packages: &[RegistryPackage<'_>]) {
+    let result = (|| -> CargoResult<()> {
+        let dst = cache.join(path);
+        let parent = dst.parent().unwrap();
+        fs::create_dir_all(parent)?;
+        // Other processes may read the cache at the same time, so the file
+        // is moved into place only once it's complete.
+        let mut tmp = tempfile::NamedTempFile::new_in(parent)?;
+        writeln!(tmp, "{}", cache_header())?;
+        for package in packages {
+            serde_json::to_writer(&mut tmp, package)?;
+            writeln!(tmp)?;
+        }
+        tmp.persist(&dst)?;
+        Ok(())
+    })();
+    if let Err(e) = result {
+        debug!("failed to cache index file `{}`: {}", path, e);
+    }
+}
+
+/// An index file loaded by `RegistryIndex::prefetch`.
+struct PrefetchedFile {
+    name: String,
+    packages: Vec<RegistryPackage<'static>>,
+    /// The packages to prefetch next, see `requested_deps`.
+    deps: Vec<(String, VersionReq)>,
+}
+
+/// Loads and parses the index files of the packages in the `queue` from
+/// the `commit` of the git repository at `root`, or from the `cache`, on
+/// up to one thread per CPU.
+fn prefetch_files(
+    root: &Path,
+    cache: &Path,
+    commit: git2::Oid,
+    queue: &[(String, VersionReq)],
+) -> CargoResult<Vec<PrefetchedFile>> {
+    let iter = Mutex::new(queue.iter());
+    let files = Mutex::new(Vec::new());
+    crossbeam_utils::thread::scope(|scope| {
+        for _ in 0..cmp::min(num_cpus::get(), queue.len()) {
+            let (iter, files) = (&iter, &files);
+            scope.spawn(move |_| {
+                // Anything which can't be prefetched is loaded later on as
+                // usual, reporting the error if it's still there.
+                if let Err(e) = prefetch_worker(root, cache, commit, iter, files) {
+                    debug!("failed to prefetch index files: {}", e);
+                }
+            });
+        }
+    })
+    .map_err(|_| internal("a thread prefetching index files panicked"))?;
+    Ok(files.into_inner().unwrap())
+}
+
+/// Loads the index files of the packages in the `queue` until it's empty,
+/// with a repository of its own as they can't be shared between threads.
+fn prefetch_worker(
+    root: &Path,
+    cache: &Path,
+    commit: git2::Oid,
+    queue: &Mutex<slice::Iter<'_, (String, VersionReq)>>,
+    files: &Mutex<Vec<PrefetchedFile>>,
+) -> CargoResult<()> {
+    let repo = git2::Repository::open(root)?;
+    let tree = repo.find_commit(commit)?.tree()?;
+    loop {
+        let (name, req) = match queue.lock().unwrap().next() {
+            Some(&(ref name, ref req)) => (name, req),
+            None => return Ok(()),
+        };
+        for path in UncanonicalizedIter::new(&index_file_path(name)).take(1024) {
+            let packages = match read_cache(cache, &path) {
+                Some(packages) => packages,
+                None => {
+                    let entry = match tree.get_path(Path::new(&path)) {
+                        Ok(entry) => entry,
+                        Err(_) => continue,
+                    };
+                    let object = entry.to_object(&repo)?;
+                    let packages = match object.as_blob() {
+                        Some(blob) => parse_index_file(name, blob.content())?,
+                        None => continue,
+                    };
+                    write_cache(cache, &path, &packages);
+                    packages
+                }
+            };
+            let deps = requested_deps(&packages, req);
+            files.lock().unwrap().push(PrefetchedFile {
+                name: name.clone(),
+                packages,
+                deps,
+            });
+            break;
+        }
+    }
+}
+
+/// Returns the packages of the same registry which the newest version of
+/// `packages` matching `req` depends on, with their requirements, leaving
+/// out optional dependencies and dev-dependencies, as those are the ones
+/// the resolver likely asks for next. A yanked version is only picked if
+/// nothing else matches, like a locked version which has since been yanked.
+fn requested_deps(packages: &[RegistryPackage<'_>], req: &VersionReq) -> Vec<(String, VersionReq)> {
+    let requested = packages
+        .iter()
+        .filter(|package| req.matches(&package.vers))
+        .max_by(|a, b| {
+            let a = (!a.yanked.unwrap_or(false), &a.vers);
+            a.cmp(&(!b.yanked.unwrap_or(false), &b.vers))
+        });
+    requested
+        .into_iter()
+        .flat_map(|package| package.deps.iter())
+        .filter(|dep| !dep.optional && dep.registry.is_none())
+        .filter(|dep| dep.kind.as_ref().map_or(true, |kind| kind != "dev"))
+        .filter_map(|dep| {
+            let req = VersionReq::parse(&dep.req).ok()?;
+            let name = dep.package.as_ref().unwrap_or(&dep.name);
+            Some((name.to_string(), req))
+        })
+        .collect()
+}
@@ -109,6 +109,17 @@
 //! modifications to this file that should happen over time are yanks of a
 //! particular version.
 //!
+//! ## Reading the Index
+//!
+//! Looking up files in the git repository of the index, inflating and parsing
+//! them is most of the cost of resolving a large dependency graph. To keep
+//! that off the critical path, the first query for a package loads the files
+//! of the packages the requested version depends on, transitively, on several
+//! threads at once. The parsed files are also cached in the `.cache` directory
+//! of the index, in a directory per commit of the index, so later runs only
+//! read them again once the index has been updated. The directories of older
+//! commits are removed then.
+//!
 //! # Downloading Packages
 //!
 //! The purpose of the Index was to provide an efficient method to resolve the
@@ -141,6 +152,7 @@
 //!     # support multiple registries simultaneously
 //!     index/
 //!         registry1-<hash>/
+//!             .cache/<commit>/...
 //!         registry2-<hash>/
 //!         ...
 //!
@@ -167,7 +179,7 @@ use std::path::{Path, PathBuf};
 use flate2::read::GzDecoder;
 use log::debug;
 use semver::Version;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tar::Archive;
 
 use crate::core::dependency::{Dependency, Kind};
@@ -218,15 +230,24 @@ pub struct RegistryConfig {
     pub api: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct RegistryPackage<'a> {
     name: Cow<'a, str>,
     vers: Version,
     deps: Vec<RegistryDependency<'a>>,
     features: BTreeMap<Cow<'a, str>, Vec<Cow<'a, str>>>,
+    /// Features using the `dep:` or `dep?/feature` syntax. They are kept
+    /// apart from `features` so that older versions of Cargo, which can't
+    /// parse them, ignore these features rather than the whole package.
+    features2: Option<BTreeMap<Cow<'a, str>, Vec<Cow<'a, str>>>>,
+    /// The features of `[target.'cfg(..)'.features]` tables, by platform.
+    /// Older versions of Cargo ignore them, and only use `features`.
+    target_features: Option<BTreeMap<Cow<'a, str>, BTreeMap<String, Vec<String>>>>,
     cksum: String,
     yanked: Option<bool>,
     links: Option<Cow<'a, str>>,
+    /// The minimum version of rustc the package supports.
+    rust_version: Option<Cow<'a, str>>,
 }
 
 #[test]
@@ -274,7 +295,7 @@ enum Field {
     Links,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 struct RegistryDependency<'a> {
     name: Cow<'a, str>,
     req: Cow<'a, str>,
@@ -369,6 +390,19 @@ pub trait RegistryData {
     fn is_crate_downloaded(&self, _pkg: PackageId) -> bool {
         true
     }
+
+    /// Returns an identifier of the current state of the index, see
+    /// `Source::index_version`.
+    fn current_version(&self) -> CargoResult<Option<String>> {
+        Ok(None)
+    }
+
+    /// Returns the commit of the git repository at `index_path` which the
+    /// index files are read from, if they come from one. Other threads open
+    /// the repository on their own to prefetch index files concurrently.
+    fn index_commit(&self) -> CargoResult<Option<git2::Oid>> {
+        Ok(None)
+    }
 }
 
 pub enum MaybeLock {
@@ -544,6 +578,10 @@ impl<'cfg> RegistrySource<'cfg> {
 
 impl<'cfg> Source for RegistrySource<'cfg> {
     fn query(&mut self, dep: &Dependency, f: &mut dyn FnMut(Summary)) -> CargoResult<()> {
+        // The resolver is likely to ask for the dependencies of this package
+        // next, so load them all at once.
+        self.index.prefetch(dep, &mut *self.ops)?;
+
         // If this is a precise dependency, then it came from a lock file and in
         // theory the registry is known to contain this version. If, however, we
         // come back with no summaries, then our registry may need to be
@@ -579,6 +617,19 @@ impl<'cfg> Source for RegistrySource<'cfg> {
             .query_inner(dep, &mut *self.ops, &self.yanked_whitelist, f)
     }
 
+    fn query_yanked(&mut self, dep: &Dependency, f: &mut dyn FnMut(Summary)) -> CargoResult<()> {
+        self.index
+            .query_yanked(dep, &mut *self.ops, &self.yanked_whitelist, f)
+    }
+
+    fn is_yanked(&mut self, pkg: PackageId) -> CargoResult<bool> {
+        self.index.is_yanked(pkg, &mut *self.ops)
+    }
+
+    fn index_version(&mut self) -> CargoResult<Option<String>> {
+        self.ops.current_version()
+    }
+
     fn supports_checksums(&self) -> bool {
         true
     }
@@ -211,6 +211,14 @@ impl<'cfg> RegistryData for RemoteRegistry<'cfg> {
         Ok(())
     }
 
+    fn current_version(&self) -> CargoResult<Option<String>> {
+        Ok(Some(self.head()?.to_string()))
+    }
+
+    fn index_commit(&self) -> CargoResult<Option<git2::Oid>> {
+        Ok(Some(self.head()?))
+    }
+
     fn download(&mut self, pkg: PackageId, _checksum: &str) -> CargoResult<MaybeLock> {
         let filename = self.filename(pkg);
 
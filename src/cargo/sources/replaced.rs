@@ -63,6 +63,30 @@ impl<'cfg> Source for ReplacedSource<'cfg> {
         Ok(())
     }
 
+    fn query_yanked(&mut self, dep: &Dependency, f: &mut dyn FnMut(Summary)) -> CargoResult<()> {
+        let (replace_with, to_replace) = (self.replace_with, self.to_replace);
+        let dep = dep.clone().map_source(to_replace, replace_with);
+
+        self.inner
+            .query_yanked(&dep, &mut |summary| {
+                f(summary.map_source(replace_with, to_replace))
+            })
+            .chain_err(|| format!("failed to query replaced source {}", self.to_replace))?;
+        Ok(())
+    }
+
+    fn is_yanked(&mut self, pkg: PackageId) -> CargoResult<bool> {
+        let pkg = pkg.with_source_id(self.replace_with);
+        Ok(self
+            .inner
+            .is_yanked(pkg)
+            .chain_err(|| format!("failed to query replaced source {}", self.to_replace))?)
+    }
+
+    fn index_version(&mut self) -> CargoResult<Option<String>> {
+        self.inner.index_version()
+    }
+
     fn update(&mut self) -> CargoResult<()> {
         self.inner
             .update()
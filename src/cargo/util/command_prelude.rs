@@ -140,6 +140,13 @@ pub trait AppExt: Sized {
         )
     }
 
+    fn arg_watch(self) -> Self {
+        self._arg(opt(
+            "watch",
+            "Run again whenever a file the packages are built from changes",
+        ))
+    }
+
     fn arg_build_plan(self) -> Self {
         self._arg(opt(
             "build-plan",
@@ -145,6 +145,9 @@ pub struct ProcessError {
     pub desc: String,
     pub exit: Option<ExitStatus>,
     pub output: Option<Output>,
+    /// Whether the process was killed because it ran for longer than the
+    /// timeout of its `ProcessBuilder`.
+    pub timed_out: bool,
 }
 
 // =============================================================================
@@ -304,6 +307,7 @@ pub fn process_error(
         desc,
         exit: status,
         output: output.cloned(),
+        timed_out: false,
     };
 
     #[cfg(unix)]
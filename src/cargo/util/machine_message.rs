@@ -73,3 +73,35 @@ impl<'a> Message for BuildScript<'a> {
         "build-script-executed"
     }
 }
+
+#[derive(Serialize)]
+pub struct FlakyTest<'a> {
+    pub package_id: PackageId,
+    pub target: &'a str,
+    pub name: &'a str,
+    pub attempts: usize,
+}
+
+impl<'a> Message for FlakyTest<'a> {
+    fn reason(&self) -> &str {
+        "flaky-test"
+    }
+}
+
+#[derive(Serialize)]
+pub struct BenchComparison<'a> {
+    pub name: &'a str,
+    pub baseline: &'a str,
+    pub ns_per_iter: u64,
+    pub deviation: u64,
+    pub baseline_ns_per_iter: Option<u64>,
+    pub baseline_deviation: Option<u64>,
+    pub change_percent: Option<f64>,
+    pub regressed: bool,
+}
+
+impl<'a> Message for BenchComparison<'a> {
+    fn reason(&self) -> &str {
+        "bench-comparison"
+    }
+}
@@ -19,7 +19,7 @@ pub use self::progress::{Progress, ProgressStyle};
 pub use self::read2::read2;
 pub use self::rustc::Rustc;
 pub use self::sha256::Sha256;
-pub use self::to_semver::ToSemver;
+pub use self::to_semver::{parse_rust_version, semver_compatibility, ToSemver};
 pub use self::to_url::ToUrl;
 pub use self::vcs::{existing_vcs_repo, FossilRepo, GitRepo, HgRepo, PijulRepo};
 pub use self::workspace::{
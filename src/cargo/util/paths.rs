@@ -89,6 +89,30 @@ pub fn normalize_path(path: &Path) -> PathBuf {
     ret
 }
 
+/// Returns `to` relative to the directory `from`, or `to` itself if the two
+/// paths have nothing in common, as with paths on different Windows drives.
+pub fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from = normalize_path(from);
+    let to = normalize_path(to);
+    let common = from
+        .components()
+        .zip(to.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if common == 0 {
+        return to;
+    }
+    let mut ret = PathBuf::new();
+    for _ in from.components().skip(common) {
+        ret.push("..");
+    }
+    ret.extend(to.components().skip(common));
+    if ret.as_os_str().is_empty() {
+        ret.push(".");
+    }
+    ret
+}
+
 pub fn resolve_executable(exec: &Path) -> CargoResult<PathBuf> {
     if exec.components().count() == 1 {
         let paths = env::var_os("PATH").ok_or_else(|| failure::format_err!("no PATH"))?;
@@ -2,14 +2,18 @@ use std::collections::HashMap;
 use std::env;
 use std::ffi::{OsStr, OsString};
 use std::fmt;
+use std::io;
 use std::path::Path;
-use std::process::{Command, Output, Stdio};
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
 
 use failure::Fail;
 use jobserver::Client;
 use shell_escape::escape;
 
-use crate::util::{process_error, read2, CargoResult, CargoResultExt};
+use crate::util::{self, process_error, read2, CargoResult, CargoResultExt};
 
 /// A builder object for an external process, similar to `std::process::Command`.
 #[derive(Clone, Debug)]
@@ -29,6 +33,8 @@ pub struct ProcessBuilder {
     jobserver: Option<Client>,
     /// `true` to include environment variable in display.
     display_env_vars: bool,
+    /// How long the process may run for, see `timeout`.
+    timeout: Option<Duration>,
 }
 
 impl fmt::Display for ProcessBuilder {
@@ -149,6 +155,16 @@ impl ProcessBuilder {
         self
     }
 
+    /// (chainable) Kills the process if it runs for longer than `timeout`.
+    ///
+    /// The process is started in a process group of its own, so that any
+    /// process it spawned is killed with it. This is only supported by
+    /// `exec_with_streaming`.
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     /// Runs the process, waiting for completion, and mapping non-success exit codes to an error.
     pub fn exec(&self) -> CargoResult<()> {
         let mut command = self.build_command();
@@ -227,9 +243,18 @@ impl ProcessBuilder {
             .stderr(Stdio::piped())
             .stdin(Stdio::null());
 
+        if self.timeout.is_some() {
+            imp::new_process_group(&mut cmd);
+        }
+
         let mut callback_error = None;
+        let mut timed_out = false;
         let status = (|| {
             let mut child = cmd.spawn()?;
+            let _group = self.timeout.map(|_| ProcessGroupGuard::new(child.id()));
+            let watchdog = self
+                .timeout
+                .map(|timeout| Watchdog::start(child.id(), timeout));
             let out = child.stdout.take().unwrap();
             let err = child.stderr.take().unwrap();
             read2(out, err, &mut |is_out, data, eof| {
@@ -270,7 +295,11 @@ impl ProcessBuilder {
                     data.drain(..idx);
                 }
             })?;
-            child.wait()
+            let status = child.wait();
+            if let Some(watchdog) = watchdog {
+                timed_out = watchdog.finish();
+            }
+            status
         })()
         .chain_err(|| process_error(&format!("could not execute process {}", self), None, None))?;
         let output = Output {
@@ -281,7 +310,19 @@ impl ProcessBuilder {
 
         {
             let to_print = if capture_output { Some(&output) } else { None };
-            if let Some(e) = callback_error {
+            if timed_out {
+                let mut e = process_error(
+                    &format!(
+                        "process timed out after {}: {}",
+                        util::elapsed(self.timeout.unwrap()),
+                        self
+                    ),
+                    Some(output.status),
+                    to_print,
+                );
+                e.timed_out = true;
+                return Err(e.into());
+            } else if let Some(e) = callback_error {
                 let cx = process_error(
                     &format!("failed to parse process output: {}", self),
                     Some(output.status),
@@ -301,6 +342,20 @@ impl ProcessBuilder {
         Ok(output)
     }
 
+    /// Starts the process in a new process group, without waiting for it, so
+    /// that it can be stopped together with the processes it starts.
+    pub fn spawn_process_group(&self) -> CargoResult<ProcessGroup> {
+        let mut command = self.build_command();
+        imp::new_process_group(&mut command);
+        let child = command
+            .spawn()
+            .chain_err(|| format!("could not execute process {}", self))?;
+        Ok(ProcessGroup {
+            guard: Some(ProcessGroupGuard::new(child.id())),
+            child,
+        })
+    }
+
     /// Converts `ProcessBuilder` into a `std::process::Command`, and handles the jobserver, if
     /// present.
     pub fn build_command(&self) -> Command {
@@ -337,6 +392,113 @@ pub fn process<T: AsRef<OsStr>>(cmd: T) -> ProcessBuilder {
         env: HashMap::new(),
         jobserver: None,
         display_env_vars: false,
+        timeout: None,
+    }
+}
+
+/// A process started in a process group of its own by
+/// `ProcessBuilder::spawn_process_group`.
+///
+/// The whole group is killed when this is dropped, or if Cargo is
+/// interrupted first, unless the process has already exited.
+pub struct ProcessGroup {
+    child: Child,
+    /// Dropped once the process has exited and been waited for, since its id
+    /// may then be reused and the group must not be killed anymore.
+    guard: Option<ProcessGroupGuard>,
+}
+
+impl ProcessGroup {
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Returns the exit status of the process if it has exited, without
+    /// waiting for it.
+    pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        let status = self.child.try_wait()?;
+        if status.is_some() {
+            self.guard.take();
+        }
+        Ok(status)
+    }
+
+    /// Kills the whole process group, and waits for the process to exit.
+    pub fn kill(&mut self) {
+        let guard = match self.guard.take() {
+            Some(guard) => guard,
+            None => return,
+        };
+        imp::kill_process_group(self.child.id());
+        // Fails if the process already exited, which is fine.
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        drop(guard);
+    }
+}
+
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+/// Kills a process group if Cargo is interrupted while the guard is alive.
+///
+/// A process in a group of its own doesn't get the signals sent to Cargo's
+/// group from the terminal, so it would be left running after a Ctrl-C.
+struct ProcessGroupGuard(u32);
+
+impl ProcessGroupGuard {
+    fn new(pid: u32) -> ProcessGroupGuard {
+        imp::register_process_group(pid);
+        ProcessGroupGuard(pid)
+    }
+}
+
+impl Drop for ProcessGroupGuard {
+    fn drop(&mut self) {
+        imp::unregister_process_group(self.0);
+    }
+}
+
+/// Kills a process, and its process group, once a timeout expires unless it
+/// is finished before.
+struct Watchdog {
+    finished: mpsc::Sender<()>,
+    thread: Option<thread::JoinHandle<bool>>,
+}
+
+impl Watchdog {
+    fn start(pid: u32, timeout: Duration) -> Watchdog {
+        let (finished, rx) = mpsc::channel();
+        let thread = thread::spawn(move || match rx.recv_timeout(timeout) {
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                imp::kill_process_group(pid);
+                true
+            }
+            _ => false,
+        });
+        Watchdog {
+            finished,
+            thread: Some(thread),
+        }
+    }
+
+    /// Stops the watchdog, returning whether the process was killed.
+    fn finish(mut self) -> bool {
+        let _ = self.finished.send(());
+        match self.thread.take() {
+            Some(thread) => thread.join().unwrap_or(false),
+            None => false,
+        }
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        // Don't kill the process after all if reading its output failed.
+        let _ = self.finished.send(());
     }
 }
 
@@ -345,6 +507,82 @@ mod imp {
     use crate::util::{process_error, ProcessBuilder};
     use crate::CargoResult;
     use std::os::unix::process::CommandExt;
+    use std::process::Command;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Once;
+
+    lazy_static::lazy_static! {
+        /// The process groups to kill if Cargo is interrupted, zero marking
+        /// a free slot. A fixed table of atomics can be read from a signal
+        /// handler, unlike anything behind a lock.
+        static ref PROCESS_GROUPS: Vec<AtomicUsize> =
+            (0..64).map(|_| AtomicUsize::new(0)).collect();
+    }
+
+    /// Makes sure the process group `pid` is killed if Cargo is killed by
+    /// SIGINT, SIGTERM or SIGHUP.
+    pub fn register_process_group(pid: u32) {
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| unsafe {
+            lazy_static::initialize(&PROCESS_GROUPS);
+            for &signal in &[libc::SIGINT, libc::SIGTERM, libc::SIGHUP] {
+                let handler = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+                // Keep ignoring signals which Cargo was started ignoring,
+                // as under `nohup`.
+                if libc::signal(signal, handler) == libc::SIG_IGN {
+                    libc::signal(signal, libc::SIG_IGN);
+                }
+            }
+        });
+        // Past the size of the table the group is just not killed.
+        for slot in PROCESS_GROUPS.iter() {
+            let swap = slot.compare_exchange(0, pid as usize, Ordering::SeqCst, Ordering::SeqCst);
+            if swap.is_ok() {
+                break;
+            }
+        }
+    }
+
+    pub fn unregister_process_group(pid: u32) {
+        for slot in PROCESS_GROUPS.iter() {
+            let swap = slot.compare_exchange(pid as usize, 0, Ordering::SeqCst, Ordering::SeqCst);
+            if swap.is_ok() {
+                break;
+            }
+        }
+    }
+
+    extern "C" fn on_signal(signal: libc::c_int) {
+        for slot in PROCESS_GROUPS.iter() {
+            let pid = slot.load(Ordering::SeqCst);
+            if pid != 0 {
+                kill_process_group(pid as u32);
+            }
+        }
+        // Die of the signal as Cargo would have without the handler.
+        unsafe {
+            libc::signal(signal, libc::SIG_DFL);
+            libc::raise(signal);
+        }
+    }
+
+    pub fn new_process_group(command: &mut Command) {
+        unsafe {
+            command.pre_exec(|| {
+                if libc::setpgid(0, 0) == 0 {
+                    Ok(())
+                } else {
+                    Err(std::io::Error::last_os_error())
+                }
+            });
+        }
+    }
+
+    pub fn kill_process_group(pid: u32) {
+        unsafe {
+            libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+        }
+    }
 
     pub fn exec_replace(process_builder: &ProcessBuilder) -> CargoResult<()> {
         let mut command = process_builder.build_command();
@@ -363,9 +601,29 @@ mod imp {
 mod imp {
     use crate::util::{process_error, ProcessBuilder};
     use crate::CargoResult;
+    use std::process::{Command, Stdio};
     use winapi::shared::minwindef::{BOOL, DWORD, FALSE, TRUE};
     use winapi::um::consoleapi::SetConsoleCtrlHandler;
 
+    pub fn new_process_group(_command: &mut Command) {}
+
+    // The processes aren't put in groups of their own, so they get the
+    // Ctrl-C along with Cargo.
+    pub fn register_process_group(_pid: u32) {}
+
+    pub fn unregister_process_group(_pid: u32) {}
+
+    pub fn kill_process_group(pid: u32) {
+        // `taskkill /T` kills the whole tree of processes started by `pid`.
+        drop(
+            Command::new("taskkill")
+                .args(&["/F", "/T", "/PID", &pid.to_string()])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status(),
+        );
+    }
+
     unsafe extern "system" fn ctrlc_handler(_: DWORD) -> BOOL {
         // Do nothing; let the child process handle it.
         TRUE
@@ -23,6 +23,8 @@ pub struct Rustc {
     pub wrapper: Option<ProcessBuilder>,
     /// Verbose version information (the output of `rustc -vV`)
     pub verbose_version: String,
+    /// The version of rustc, this comes from verbose_version.
+    pub version: semver::Version,
     /// The host triple (arch-platform-OS), this comes from verbose_version.
     pub host: String,
     cache: Mutex<Cache>,
@@ -48,6 +50,20 @@ impl Rustc {
         cmd.arg("-vV");
         let verbose_version = cache.cached_output(&cmd)?.0;
 
+        let version = {
+            let release = verbose_version
+                .lines()
+                .find(|l| l.starts_with("release: "))
+                .map(|l| &l[9..])
+                .ok_or_else(|| internal("rustc -v didn't have a line for `release:`"))?;
+            semver::Version::parse(release).map_err(|e| {
+                internal(format!(
+                    "rustc version `{}` couldn't be parsed as a semver: {}",
+                    release, e
+                ))
+            })?
+        };
+
         let host = {
             let triple = verbose_version
                 .lines()
@@ -61,6 +77,7 @@ impl Rustc {
             path,
             wrapper: wrapper.map(util::process),
             verbose_version,
+            version,
             host,
             cache: Mutex::new(cache),
         })
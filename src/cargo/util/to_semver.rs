@@ -31,3 +31,34 @@ impl<'a> ToSemver for &'a Version {
         Ok(self.clone())
     }
 }
+
+/// Parses the `rust-version` of a package, which is a `major.minor` or
+/// `major.minor.patch` version of rustc without pre-release or build metadata.
+pub fn parse_rust_version(s: &str) -> CargoResult<Version> {
+    let parts = s
+        .split('.')
+        .map(|part| part.parse::<u64>())
+        .collect::<Result<Vec<_>, _>>();
+    match parts.as_ref().map(|parts| &parts[..]) {
+        Ok(&[major, minor]) => Ok(Version::new(major, minor, 0)),
+        Ok(&[major, minor, patch]) => Ok(Version::new(major, minor, patch)),
+        _ => failure::bail!(
+            "`{}` is not a valid rust-version, expected a version like \"1.36\" or \"1.36.0\"",
+            s
+        ),
+    }
+}
+
+/// The part of `version` which semver compatible versions have in common: its
+/// leftmost non-zero component, with the ones before it. `1.2.3` is
+/// compatible with `1.4.0`, and `0.2.3` with `0.2.5`, but `0.0.3` only with
+/// itself.
+pub fn semver_compatibility(version: &Version) -> (u64, u64, u64) {
+    if version.major > 0 {
+        (version.major, 0, 0)
+    } else if version.minor > 0 {
+        (0, version.minor, 0)
+    } else {
+        (0, 0, version.patch)
+    }
+}
@@ -16,13 +16,15 @@ use url::Url;
 use crate::core::dependency::{Kind, Platform};
 use crate::core::manifest::{LibKind, ManifestMetadata, TargetSourcePath, Warnings};
 use crate::core::profiles::Profiles;
+use crate::core::resolver::ResolveBehavior;
 use crate::core::{Dependency, Manifest, PackageId, Summary, Target};
 use crate::core::{Edition, EitherManifest, Feature, Features, VirtualManifest};
-use crate::core::{GitReference, PackageIdSpec, SourceId, WorkspaceConfig, WorkspaceRootConfig};
+use crate::core::{GitReference, PackageIdSpec, PolicyLevel, SourceId, WorkspacePolicy};
+use crate::core::{WorkspaceConfig, WorkspaceRootConfig};
 use crate::sources::{CRATES_IO_INDEX, CRATES_IO_REGISTRY};
 use crate::util::errors::{CargoResult, CargoResultExt, ManifestError};
 use crate::util::paths;
-use crate::util::{self, validate_package_name, Config, ToUrl};
+use crate::util::{self, parse_rust_version, validate_package_name, Config, ToUrl};
 
 mod targets;
 use self::targets::targets;
@@ -239,9 +241,13 @@ pub struct DetailedTomlDependency {
     #[serde(rename = "default_features")]
     default_features2: Option<bool>,
     package: Option<String>,
+    public: Option<bool>,
+    /// Inherits the dependency from `[workspace.dependencies]`, see
+    /// `TomlManifest::inherit_from_workspace`.
+    workspace: Option<bool>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct TomlManifest {
     cargo_features: Option<Vec<String>>,
@@ -641,6 +647,91 @@ impl<'de> de::Deserialize<'de> for VecStringOrBool {
     }
 }
 
+/// A key of the `package` section which is either specified directly or
+/// inherited from `[workspace.package]` with `key.workspace = true`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(untagged)]
+pub enum MaybeWorkspace<T> {
+    Defined(T),
+    Workspace(TomlWorkspaceField),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TomlWorkspaceField {
+    workspace: bool,
+}
+
+impl<'de, T: de::Deserialize<'de>> de::Deserialize<'de> for MaybeWorkspace<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        // None of the inheritable keys are tables, so a table is always
+        // `{ workspace = true }`. Going through a `toml::Value` keeps the
+        // errors of `T` itself for everything else, like `version = 1`.
+        let value = toml::Value::deserialize(deserializer)?;
+        let result = match value {
+            toml::Value::Table(_) => value.try_into().map(MaybeWorkspace::Workspace),
+            _ => value.try_into().map(MaybeWorkspace::Defined),
+        };
+        result.map_err(de::Error::custom)
+    }
+}
+
+impl<T: Clone> MaybeWorkspace<T> {
+    /// Returns the value of the key `package.<key>`, which must have been
+    /// inherited from the workspace by now, see
+    /// `TomlManifest::inherit_from_workspace`.
+    fn resolved(&self, key: &str) -> CargoResult<&T> {
+        match *self {
+            MaybeWorkspace::Defined(ref value) => Ok(value),
+            MaybeWorkspace::Workspace(_) => bail!(
+                "`package.{}` is inherited from the workspace, and can't be \
+                 used before the workspace has been read",
+                key
+            ),
+        }
+    }
+
+    fn is_workspace(&self) -> bool {
+        match *self {
+            MaybeWorkspace::Defined(_) => false,
+            MaybeWorkspace::Workspace(_) => true,
+        }
+    }
+
+    /// Replaces `key.workspace = true` with `value`, the one of
+    /// `[workspace.package]` in the root manifest at `ws_manifest`.
+    fn inherit(&mut self, key: &str, value: &Option<T>, ws_manifest: &Path) -> CargoResult<()> {
+        let inherited = match *self {
+            MaybeWorkspace::Defined(_) => return Ok(()),
+            MaybeWorkspace::Workspace(TomlWorkspaceField { workspace: false }) => bail!(
+                "`workspace` of `package.{}` can only be `true`, to inherit it",
+                key
+            ),
+            MaybeWorkspace::Workspace(_) => value.clone().ok_or_else(|| {
+                failure::format_err!(
+                    "`package.{}` is inherited from the workspace, but it isn't \
+                     specified in `[workspace.package]` of `{}`",
+                    key,
+                    ws_manifest.display()
+                )
+            })?,
+        };
+        *self = MaybeWorkspace::Defined(inherited);
+        Ok(())
+    }
+}
+
+/// Returns the value of the optional key `package.<key>`, see
+/// `MaybeWorkspace::resolved`.
+fn resolved_opt<T: Clone>(value: &Option<MaybeWorkspace<T>>, key: &str) -> CargoResult<Option<T>> {
+    value
+        .as_ref()
+        .map(|v| v.resolved(key).map(T::clone))
+        .transpose()
+}
+
 /// Represents the `package`/`project` sections of a `Cargo.toml`.
 ///
 /// Note that the order of the fields matters, since this is the order they
@@ -649,16 +740,16 @@ impl<'de> de::Deserialize<'de> for VecStringOrBool {
 /// tables.
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct TomlProject {
-    edition: Option<String>,
+    edition: Option<MaybeWorkspace<String>>,
     name: String,
-    version: semver::Version,
-    authors: Option<Vec<String>>,
+    version: MaybeWorkspace<semver::Version>,
+    authors: Option<MaybeWorkspace<Vec<String>>>,
     build: Option<StringOrBool>,
     metabuild: Option<StringOrVec>,
     links: Option<String>,
     exclude: Option<Vec<String>>,
     include: Option<Vec<String>>,
-    publish: Option<VecStringOrBool>,
+    publish: Option<MaybeWorkspace<VecStringOrBool>>,
     #[serde(rename = "publish-lockfile")]
     publish_lockfile: Option<bool>,
     workspace: Option<String>,
@@ -672,32 +763,128 @@ pub struct TomlProject {
     namespaced_features: Option<bool>,
     #[serde(rename = "default-run")]
     default_run: Option<String>,
+    resolver: Option<String>,
+    #[serde(rename = "rust-version")]
+    rust_version: Option<String>,
 
     // Package metadata.
     description: Option<String>,
-    homepage: Option<String>,
-    documentation: Option<String>,
+    homepage: Option<MaybeWorkspace<String>>,
+    documentation: Option<MaybeWorkspace<String>>,
     readme: Option<String>,
-    keywords: Option<Vec<String>>,
-    categories: Option<Vec<String>>,
-    license: Option<String>,
+    keywords: Option<MaybeWorkspace<Vec<String>>>,
+    categories: Option<MaybeWorkspace<Vec<String>>>,
+    license: Option<MaybeWorkspace<String>>,
     #[serde(rename = "license-file")]
     license_file: Option<String>,
-    repository: Option<String>,
+    repository: Option<MaybeWorkspace<String>>,
     metadata: Option<toml::Value>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct TomlWorkspace {
     members: Option<Vec<String>>,
     #[serde(rename = "default-members")]
     default_members: Option<Vec<String>>,
     exclude: Option<Vec<String>>,
+    resolver: Option<String>,
+    policy: Option<TomlWorkspacePolicy>,
+    package: Option<TomlWorkspacePackage>,
+    dependencies: Option<BTreeMap<String, TomlDependency>>,
+}
+
+/// The `[workspace.package]` table, with the keys of the `package` section
+/// the members can inherit.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct TomlWorkspacePackage {
+    version: Option<semver::Version>,
+    authors: Option<Vec<String>>,
+    edition: Option<String>,
+    license: Option<String>,
+    repository: Option<String>,
+    homepage: Option<String>,
+    documentation: Option<String>,
+    keywords: Option<Vec<String>>,
+    categories: Option<Vec<String>>,
+    publish: Option<VecStringOrBool>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TomlWorkspacePolicy {
+    duplicates: Option<String>,
+    allow_duplicates: Option<Vec<String>>,
+    bans: Option<String>,
+    banned: Option<Vec<String>>,
+    allow_banned_in: Option<Vec<String>>,
 }
 
 impl TomlProject {
     pub fn to_package_id(&self, source_id: SourceId) -> CargoResult<PackageId> {
-        PackageId::new(&self.name, self.version.clone(), source_id)
+        PackageId::new(
+            &self.name,
+            self.version.resolved("version")?.clone(),
+            source_id,
+        )
+    }
+
+    /// Whether any key is inherited from `[workspace.package]`.
+    fn inherits_from_workspace(&self) -> bool {
+        self.version.is_workspace()
+            || self.authors.as_ref().map_or(false, |v| v.is_workspace())
+            || self.edition.as_ref().map_or(false, |v| v.is_workspace())
+            || self.license.as_ref().map_or(false, |v| v.is_workspace())
+            || self.repository.as_ref().map_or(false, |v| v.is_workspace())
+            || self.homepage.as_ref().map_or(false, |v| v.is_workspace())
+            || self
+                .documentation
+                .as_ref()
+                .map_or(false, |v| v.is_workspace())
+            || self.keywords.as_ref().map_or(false, |v| v.is_workspace())
+            || self.categories.as_ref().map_or(false, |v| v.is_workspace())
+            || self.publish.as_ref().map_or(false, |v| v.is_workspace())
+    }
+
+    /// Replaces the keys inherited from `[workspace.package]` of the
+    /// workspace root at `ws_root` with their values there.
+    fn inherit_from_workspace(
+        &mut self,
+        ws_root: &Path,
+        workspace: &TomlWorkspace,
+    ) -> CargoResult<()> {
+        let empty = TomlWorkspacePackage::default();
+        let ws = workspace.package.as_ref().unwrap_or(&empty);
+        let ws_manifest = ws_root.join("Cargo.toml");
+        let ws_manifest = ws_manifest.as_path();
+        self.version.inherit("version", &ws.version, ws_manifest)?;
+        if let Some(ref mut authors) = self.authors {
+            authors.inherit("authors", &ws.authors, ws_manifest)?;
+        }
+        if let Some(ref mut edition) = self.edition {
+            edition.inherit("edition", &ws.edition, ws_manifest)?;
+        }
+        if let Some(ref mut license) = self.license {
+            license.inherit("license", &ws.license, ws_manifest)?;
+        }
+        if let Some(ref mut repository) = self.repository {
+            repository.inherit("repository", &ws.repository, ws_manifest)?;
+        }
+        if let Some(ref mut homepage) = self.homepage {
+            homepage.inherit("homepage", &ws.homepage, ws_manifest)?;
+        }
+        if let Some(ref mut documentation) = self.documentation {
+            documentation.inherit("documentation", &ws.documentation, ws_manifest)?;
+        }
+        if let Some(ref mut keywords) = self.keywords {
+            keywords.inherit("keywords", &ws.keywords, ws_manifest)?;
+        }
+        if let Some(ref mut categories) = self.categories {
+            categories.inherit("categories", &ws.categories, ws_manifest)?;
+        }
+        if let Some(ref mut publish) = self.publish {
+            publish.inherit("publish", &ws.publish, ws_manifest)?;
+        }
+        Ok(())
     }
 }
 
@@ -769,6 +956,7 @@ impl TomlManifest {
                                         .or_else(|| v.build_dependencies2.as_ref()),
                                 )?,
                                 build_dependencies2: None,
+                                features: v.features.clone(),
                             },
                         ))
                     })
@@ -822,6 +1010,60 @@ impl TomlManifest {
         }
     }
 
+    /// Fills in the values `me` inherits from the root manifest of its
+    /// workspace, such as the dependencies specified with `workspace = true`.
+    /// The root is only looked up if something is inherited.
+    fn inherit_from_workspace(
+        me: &Rc<TomlManifest>,
+        package_root: &Path,
+        config: &Config,
+        features: &Features,
+    ) -> CargoResult<Rc<TomlManifest>> {
+        let mut inherited = (**me).clone();
+        let mut tables = vec![
+            &mut inherited.dependencies,
+            &mut inherited.dev_dependencies,
+            &mut inherited.dev_dependencies2,
+            &mut inherited.build_dependencies,
+            &mut inherited.build_dependencies2,
+        ];
+        for platform in inherited.target.iter_mut().flat_map(|t| t.values_mut()) {
+            tables.push(&mut platform.dependencies);
+            tables.push(&mut platform.dev_dependencies);
+            tables.push(&mut platform.dev_dependencies2);
+            tables.push(&mut platform.build_dependencies);
+            tables.push(&mut platform.build_dependencies2);
+        }
+        let deps = tables
+            .into_iter()
+            .flat_map(|table| table.iter_mut().flat_map(|t| t.iter_mut()))
+            .filter(|&(_, ref dep)| match **dep {
+                TomlDependency::Detailed(ref d) => d.workspace.is_some(),
+                TomlDependency::Simple(_) => false,
+            })
+            .collect::<Vec<_>>();
+        let project = inherited
+            .package
+            .as_mut()
+            .or(inherited.project.as_mut())
+            .filter(|p| p.inherits_from_workspace());
+        if deps.is_empty() && project.is_none() {
+            return Ok(Rc::clone(me));
+        }
+
+        features
+            .require(Feature::workspace_inheritance())
+            .chain_err(|| "inheriting values from the workspace is unstable")?;
+        let (ws_root, workspace) = find_workspace_table(me, package_root, config)?;
+        for (name, dep) in deps {
+            *dep = inherit_dependency(name, dep, package_root, &ws_root, &workspace)?;
+        }
+        if let Some(project) = project {
+            project.inherit_from_workspace(&ws_root, &workspace)?;
+        }
+        Ok(Rc::new(inherited))
+    }
+
     fn to_real_manifest(
         me: &Rc<TomlManifest>,
         source_id: SourceId,
@@ -837,6 +1079,10 @@ impl TomlManifest {
         let cargo_features = me.cargo_features.as_ref().unwrap_or(&empty);
         let features = Features::new(cargo_features, &mut warnings)?;
 
+        // The rest of the conversion, and `cargo package`, see the manifest
+        // with the values inherited from the workspace filled in.
+        let me = &TomlManifest::inherit_from_workspace(me, package_root, config, &features)?;
+
         let project = me.project.as_ref().or_else(|| me.package.as_ref());
         let project = project.ok_or_else(|| failure::format_err!("no `package` section found"))?;
 
@@ -854,6 +1100,7 @@ impl TomlManifest {
                 .require(Feature::edition())
                 .chain_err(|| "editions are unstable")?;
             edition
+                .resolved("edition")?
                 .parse()
                 .chain_err(|| "failed to parse the `edition` key")?
         } else {
@@ -978,8 +1225,45 @@ impl TomlManifest {
         if project.namespaced_features.is_some() {
             features.require(Feature::namespaced_features())?;
         }
+        let mut platform_features = Vec::new();
+        for (name, platform) in me.target.iter().flat_map(|t| t) {
+            if let Some(ref target_features) = platform.features {
+                features
+                    .require(Feature::platform_features())
+                    .chain_err(|| {
+                        format!("the `[target.'{}'.features]` table is unstable", name)
+                    })?;
+                platform_features.push((name.parse::<Platform>()?, target_features.clone()));
+            }
+        }
+        let feature_values = me
+            .features
+            .iter()
+            .chain(platform_features.iter().map(|&(_, ref f)| f))
+            .flat_map(|f| f.values())
+            .flatten();
+        for value in feature_values {
+            if value.starts_with("dep:") {
+                features
+                    .require(Feature::dep_features())
+                    .chain_err(|| format!("the `dep:` syntax of `{}` is unstable", value))?;
+            }
+            if value.contains("?/") {
+                features
+                    .require(Feature::weak_dep_features())
+                    .chain_err(|| format!("the `?/` syntax of `{}` is unstable", value))?;
+            }
+        }
 
-        let summary = Summary::new(
+        let rust_version = match project.rust_version {
+            Some(ref rust_version) => {
+                features.require(Feature::rust_version())?;
+                Some(parse_rust_version(rust_version)?)
+            }
+            None => None,
+        };
+
+        let summary = Summary::with_platform_features(
             pkgid,
             deps,
             &me.features
@@ -990,31 +1274,28 @@ impl TomlManifest {
                         .collect()
                 })
                 .unwrap_or_else(BTreeMap::new),
+            &platform_features,
             project.links.as_ref().map(|x| x.as_str()),
             project.namespaced_features.unwrap_or(false),
-        )?;
+        )?
+        .set_rust_version(rust_version);
         let metadata = ManifestMetadata {
             description: project.description.clone(),
-            homepage: project.homepage.clone(),
-            documentation: project.documentation.clone(),
+            homepage: resolved_opt(&project.homepage, "homepage")?,
+            documentation: resolved_opt(&project.documentation, "documentation")?,
             readme: project.readme.clone(),
-            authors: project.authors.clone().unwrap_or_default(),
-            license: project.license.clone(),
+            authors: resolved_opt(&project.authors, "authors")?.unwrap_or_default(),
+            license: resolved_opt(&project.license, "license")?,
             license_file: project.license_file.clone(),
-            repository: project.repository.clone(),
-            keywords: project.keywords.clone().unwrap_or_default(),
-            categories: project.categories.clone().unwrap_or_default(),
+            repository: resolved_opt(&project.repository, "repository")?,
+            keywords: resolved_opt(&project.keywords, "keywords")?.unwrap_or_default(),
+            categories: resolved_opt(&project.categories, "categories")?.unwrap_or_default(),
             badges: me.badges.clone().unwrap_or_default(),
             links: project.links.clone(),
         };
 
         let workspace_config = match (me.workspace.as_ref(), project.workspace.as_ref()) {
-            (Some(config), None) => WorkspaceConfig::Root(WorkspaceRootConfig::new(
-                package_root,
-                &config.members,
-                &config.default_members,
-                &config.exclude,
-            )),
+            (Some(config), None) => workspace_root_config(package_root, config, &features)?,
             (None, root) => WorkspaceConfig::Member {
                 root: root.cloned(),
             },
@@ -1024,8 +1305,8 @@ impl TomlManifest {
             ),
         };
         let profiles = Profiles::new(me.profile.as_ref(), config, &features, &mut warnings)?;
-        let publish = match project.publish {
-            Some(VecStringOrBool::VecString(ref vecstring)) => Some(vecstring.clone()),
+        let publish = match resolved_opt(&project.publish, "publish")? {
+            Some(VecStringOrBool::VecString(vecstring)) => Some(vecstring),
             Some(VecStringOrBool::Bool(false)) => Some(vec![]),
             None | Some(VecStringOrBool::Bool(true)) => None,
         };
@@ -1046,6 +1327,9 @@ impl TomlManifest {
             )
         }
 
+        let resolve_behavior =
+            resolve_behavior(project.resolver.as_ref(), me.workspace.as_ref(), &features)?;
+
         let custom_metadata = project.metadata.clone();
         let mut manifest = Manifest::new(
             summary,
@@ -1067,6 +1351,7 @@ impl TomlManifest {
             project.default_run.clone(),
             Rc::clone(me),
             project.metabuild.clone().map(|sov| sov.0),
+            resolve_behavior,
         );
         if project.license_file.is_some() && project.license.is_some() {
             manifest.warnings_mut().add_warning(
@@ -1156,18 +1441,21 @@ impl TomlManifest {
         };
         let profiles = Profiles::new(me.profile.as_ref(), config, &features, &mut warnings)?;
         let workspace_config = match me.workspace {
-            Some(ref config) => WorkspaceConfig::Root(WorkspaceRootConfig::new(
-                root,
-                &config.members,
-                &config.default_members,
-                &config.exclude,
-            )),
+            Some(ref config) => workspace_root_config(root, config, &features)?,
             None => {
                 bail!("virtual manifests must be configured with [workspace]");
             }
         };
+        let resolve_behavior = resolve_behavior(None, me.workspace.as_ref(), &features)?;
         Ok((
-            VirtualManifest::new(replace, patch, workspace_config, profiles, features),
+            VirtualManifest::new(
+                replace,
+                patch,
+                workspace_config,
+                profiles,
+                features,
+                resolve_behavior,
+            ),
             nested_paths,
         ))
     }
@@ -1282,6 +1570,268 @@ fn unique_build_targets(targets: &[Target], package_root: &Path) -> Result<(), S
     Ok(())
 }
 
+/// Reads the `resolver` key of `[package]` or `[workspace]`.
+fn resolve_behavior(
+    package: Option<&String>,
+    workspace: Option<&TomlWorkspace>,
+    features: &Features,
+) -> CargoResult<Option<ResolveBehavior>> {
+    let workspace = workspace.and_then(|ws| ws.resolver.as_ref());
+    let resolver = match (package, workspace) {
+        (Some(a), Some(b)) if a != b => bail!(
+            "`package.resolver` is `{}` but `workspace.resolver` is `{}`, \
+             only one of them needs to be specified",
+            a,
+            b
+        ),
+        (Some(resolver), _) | (None, Some(resolver)) => resolver,
+        (None, None) => return Ok(None),
+    };
+    features
+        .require(Feature::resolver())
+        .chain_err(|| failure::format_err!("the `resolver` manifest key is unstable"))?;
+    Ok(Some(ResolveBehavior::from_manifest(resolver)?))
+}
+
+/// Returns the directory and the `[workspace]` table of the root manifest of
+/// the workspace the package at `package_root` belongs to, looking for it
+/// the same way `Workspace` does.
+fn find_workspace_table(
+    me: &TomlManifest,
+    package_root: &Path,
+    config: &Config,
+) -> CargoResult<(PathBuf, TomlWorkspace)> {
+    if let Some(ref workspace) = me.workspace {
+        return Ok((package_root.to_path_buf(), workspace.clone()));
+    }
+    let manifest_path = package_root.join("Cargo.toml");
+    let project = me.package.as_ref().or_else(|| me.project.as_ref());
+    let mut pointer = project
+        .and_then(|p| p.workspace.as_ref())
+        .map(|root| (package_root.to_path_buf(), root.clone()));
+
+    let mut ancestors = paths::ancestors(package_root).skip(1);
+    loop {
+        if let Some((from, root)) = pointer.take() {
+            let root = paths::normalize_path(&from.join(root));
+            let manifest = read_toml_manifest(&root.join("Cargo.toml"), config)?;
+            match manifest.workspace {
+                Some(workspace) => return Ok((root, workspace)),
+                None => bail!(
+                    "the workspace root `{}` to inherit from has no `[workspace]` table",
+                    root.display()
+                ),
+            }
+        }
+
+        let path = match ancestors.next() {
+            Some(path) if !path.ends_with("target/package") => path,
+            _ => break,
+        };
+        let ances_manifest_path = path.join("Cargo.toml");
+        if ances_manifest_path.exists() {
+            let manifest = read_toml_manifest(&ances_manifest_path, config)?;
+            if let Some(workspace) = manifest.workspace {
+                let root_config = WorkspaceRootConfig::new(
+                    path,
+                    &workspace.members,
+                    &workspace.default_members,
+                    &workspace.exclude,
+                    None,
+                );
+                if !root_config.is_excluded(&manifest_path) {
+                    return Ok((path.to_path_buf(), workspace));
+                }
+            } else if let Some(root) = manifest
+                .package
+                .or(manifest.project)
+                .and_then(|p| p.workspace)
+            {
+                pointer = Some((path.to_path_buf(), root));
+                continue;
+            }
+        }
+
+        // Like `Workspace`, don't walk across `CARGO_HOME`.
+        if config.home() == path {
+            break;
+        }
+    }
+    bail!(
+        "no workspace root with a `[workspace]` table was found above `{}` \
+         to inherit from",
+        package_root.display()
+    )
+}
+
+/// Reads the manifest at `path` without converting it.
+fn read_toml_manifest(path: &Path, config: &Config) -> CargoResult<TomlManifest> {
+    let contents = paths::read(path)?;
+    let toml = parse(&contents, path, config)?;
+    Ok(toml
+        .try_into()
+        .chain_err(|| format!("failed to parse manifest at `{}`", path.display()))?)
+}
+
+/// Replaces the dependency `name`, specified with `workspace = true`, with
+/// the one in `[workspace.dependencies]` of the workspace root at `ws_root`,
+/// adding the `features`, `optional` and `public` keys of `dep`.
+fn inherit_dependency(
+    name: &str,
+    dep: &TomlDependency,
+    package_root: &Path,
+    ws_root: &Path,
+    workspace: &TomlWorkspace,
+) -> CargoResult<TomlDependency> {
+    let dep = match *dep {
+        TomlDependency::Detailed(ref d) => d,
+        TomlDependency::Simple(_) => unreachable!(),
+    };
+    if dep.workspace != Some(true) {
+        bail!(
+            "`workspace` of dependency `{}` can only be `true`, to inherit it",
+            name
+        );
+    }
+    let other_keys = [
+        (dep.version.is_some(), "version"),
+        (dep.registry.is_some(), "registry"),
+        (dep.registry_index.is_some(), "registry-index"),
+        (dep.path.is_some(), "path"),
+        (dep.git.is_some(), "git"),
+        (dep.branch.is_some(), "branch"),
+        (dep.tag.is_some(), "tag"),
+        (dep.rev.is_some(), "rev"),
+        (dep.default_features.is_some(), "default-features"),
+        (dep.default_features2.is_some(), "default_features"),
+        (dep.package.is_some(), "package"),
+    ];
+    if let Some(&(_, key)) = other_keys.iter().find(|&&(present, _)| present) {
+        bail!(
+            "dependency `{}` is inherited from the workspace, so `{}` can't be \
+             specified for it, only `features`, `optional` and `public` can",
+            name,
+            key
+        );
+    }
+
+    let inherited = workspace
+        .dependencies
+        .as_ref()
+        .and_then(|deps| deps.get(name))
+        .ok_or_else(|| {
+            failure::format_err!(
+                "dependency `{}` is inherited from the workspace, but it isn't \
+                 specified in `[workspace.dependencies]` of `{}`",
+                name,
+                ws_root.join("Cargo.toml").display()
+            )
+        })?;
+    let mut inherited = match *inherited {
+        TomlDependency::Simple(ref version) => DetailedTomlDependency {
+            version: Some(version.clone()),
+            ..Default::default()
+        },
+        TomlDependency::Detailed(ref d) => d.clone(),
+    };
+    let invalid_keys = [
+        (inherited.optional.is_some(), "optional"),
+        (inherited.public.is_some(), "public"),
+        (inherited.workspace.is_some(), "workspace"),
+    ];
+    if let Some(&(_, key)) = invalid_keys.iter().find(|&&(present, _)| present) {
+        bail!(
+            "`{}` can't be specified for `{}` in `[workspace.dependencies]`, \
+             only for the dependencies inheriting it",
+            key,
+            name
+        );
+    }
+
+    // Paths are relative to the workspace root, so they're rewritten to be
+    // relative to the package, like the ones it specifies itself.
+    if let Some(ref path) = inherited.path {
+        let path = paths::relative_path(package_root, &ws_root.join(path));
+        inherited.path = Some(path.display().to_string());
+    }
+    if let Some(ref features) = dep.features {
+        let all = inherited.features.get_or_insert_with(Vec::new);
+        for feature in features {
+            if !all.contains(feature) {
+                all.push(feature.clone());
+            }
+        }
+    }
+    inherited.optional = dep.optional;
+    inherited.public = dep.public;
+    Ok(TomlDependency::Detailed(inherited))
+}
+
+/// Converts the `[workspace]` table of a workspace root.
+fn workspace_root_config(
+    root: &Path,
+    workspace: &TomlWorkspace,
+    features: &Features,
+) -> CargoResult<WorkspaceConfig> {
+    if workspace.package.is_some() {
+        features
+            .require(Feature::workspace_inheritance())
+            .chain_err(|| failure::format_err!("the `[workspace.package]` table is unstable"))?;
+    }
+    if workspace.dependencies.is_some() {
+        features
+            .require(Feature::workspace_inheritance())
+            .chain_err(|| {
+                failure::format_err!("the `[workspace.dependencies]` table is unstable")
+            })?;
+    }
+    Ok(WorkspaceConfig::Root(WorkspaceRootConfig::new(
+        root,
+        &workspace.members,
+        &workspace.default_members,
+        &workspace.exclude,
+        workspace_policy(workspace, features)?,
+    )))
+}
+
+/// Converts the `[workspace.policy]` table of a workspace root.
+fn workspace_policy(
+    workspace: &TomlWorkspace,
+    features: &Features,
+) -> CargoResult<Option<WorkspacePolicy>> {
+    let policy = match workspace.policy {
+        Some(ref policy) => policy,
+        None => return Ok(None),
+    };
+    features
+        .require(Feature::workspace_policy())
+        .chain_err(|| failure::format_err!("the `[workspace.policy]` table is unstable"))?;
+
+    let level = |level: &Option<String>, default| match *level {
+        Some(ref level) => PolicyLevel::from_manifest(level),
+        None => Ok(default),
+    };
+    let mut banned = Vec::new();
+    for spec in policy.banned.iter().flat_map(|b| b.iter()) {
+        let mut parts = spec.splitn(2, '@');
+        let name = parts.next().unwrap().to_string();
+        let req = match parts.next() {
+            Some(req) => Some(VersionReq::parse(req).chain_err(|| {
+                failure::format_err!("invalid version requirement of banned package `{}`", spec)
+            })?),
+            None => None,
+        };
+        banned.push((name, req));
+    }
+    Ok(Some(WorkspacePolicy {
+        duplicates: level(&policy.duplicates, PolicyLevel::Allow)?,
+        allow_duplicates: policy.allow_duplicates.clone().unwrap_or_default(),
+        bans: level(&policy.bans, PolicyLevel::Deny)?,
+        banned,
+        allow_banned_in: policy.allow_banned_in.clone().unwrap_or_default(),
+    }))
+}
+
 impl TomlDependency {
     fn to_dependency(
         &self,
@@ -1307,6 +1857,16 @@ impl DetailedTomlDependency {
         cx: &mut Context<'_, '_>,
         kind: Option<Kind>,
     ) -> CargoResult<Dependency> {
+        // Inherited dependencies have been replaced already, see
+        // `TomlManifest::inherit_from_workspace`.
+        if self.workspace.is_some() {
+            bail!(
+                "dependency ({}) can't be inherited from the workspace here, \
+                 only in dependency tables",
+                name_in_toml
+            );
+        }
+
         if self.version.is_none() && self.path.is_none() && self.git.is_none() {
             let msg = format!(
                 "dependency ({}) specified without \
@@ -1461,6 +2021,22 @@ impl DetailedTomlDependency {
             cx.features.require(Feature::rename_dependency())?;
             dep.set_explicit_name_in_toml(name_in_toml);
         }
+
+        if let Some(public) = self.public {
+            cx.features.require(Feature::public_dependency())?;
+            if dep.kind() != Kind::Normal {
+                bail!(
+                    "`public` can only be specified for normal dependencies, \
+                     but `{}` is a {} dependency",
+                    name_in_toml,
+                    match dep.kind() {
+                        Kind::Development => "dev",
+                        _ => "build",
+                    }
+                );
+            }
+            dep.set_public(public);
+        }
         Ok(dep)
     }
 }
@@ -1514,7 +2090,7 @@ impl ser::Serialize for PathValue {
 }
 
 /// Corresponds to a `target` entry, but `TomlTarget` is already used.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct TomlPlatform {
     dependencies: Option<BTreeMap<String, TomlDependency>>,
     #[serde(rename = "build-dependencies")]
@@ -1525,6 +2101,7 @@ struct TomlPlatform {
     dev_dependencies: Option<BTreeMap<String, TomlDependency>>,
     #[serde(rename = "dev_dependencies")]
     dev_dependencies2: Option<BTreeMap<String, TomlDependency>>,
+    features: Option<BTreeMap<String, Vec<String>>>,
 }
 
 impl TomlTarget {
@@ -44,6 +44,12 @@ pub struct NewCrate {
     pub vers: String,
     pub deps: Vec<NewCrateDependency>,
     pub features: BTreeMap<String, Vec<String>>,
+    /// The features using the `dep:` or `dep?/feature` syntax.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub features2: BTreeMap<String, Vec<String>>,
+    /// The features only enabled on some platforms, by platform.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub target_features: BTreeMap<String, BTreeMap<String, Vec<String>>>,
     pub authors: Vec<String>,
     pub description: Option<String>,
     pub documentation: Option<String>,
@@ -58,6 +64,8 @@ pub struct NewCrate {
     pub badges: BTreeMap<String, BTreeMap<String, String>>,
     #[serde(default)]
     pub links: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rust_version: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -0,0 +1,128 @@
+use crate::support::registry::Package;
+use crate::support::{basic_manifest, project};
+
+#[test]
+fn add_newest_version_keeps_formatting() {
+    Package::new("bar", "0.1.0").publish();
+    Package::new("bar", "0.2.0").publish();
+    Package::new("bar", "0.3.0-alpha.1").publish();
+    Package::new("baz", "1.0.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+
+            # Things we depend on.
+            [dependencies]
+            baz = "1.0" # pinned on purpose
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("add -Z unstable-options bar")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[ADDING] bar 0.2.0 to dependencies
+",
+        )
+        .run();
+
+    let manifest = p.read_file("Cargo.toml");
+    assert!(manifest.contains("# Things we depend on.\n"));
+    assert!(manifest.contains("baz = \"1.0\" # pinned on purpose\n"));
+    assert!(manifest.contains("bar = \"0.2.0\""));
+    p.cargo("check").run();
+}
+
+#[test]
+fn add_with_options() {
+    Package::new("bar", "1.0.0")
+        .feature("a", &[])
+        .feature("b", &[])
+        .publish();
+
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.0.1"))
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("add -Z unstable-options bar@1.0 --dev --features a --rename baz")
+        .masquerade_as_nightly_cargo()
+        .with_stderr("[ADDING] bar 1.0 to dev-dependencies")
+        .run();
+    p.cargo("add -Z unstable-options baz --dev --features b")
+        .masquerade_as_nightly_cargo()
+        .run();
+
+    let manifest = p.read_file("Cargo.toml");
+    assert!(manifest.contains("[dev-dependencies]"));
+    assert!(manifest.contains(r#"baz = { version = "1.0", package = "bar", features = ["#));
+    assert!(manifest.contains(r#""a", "b"]"#));
+
+    p.cargo("add -Z unstable-options bar@1.0 --optional --target cfg(unix)")
+        .masquerade_as_nightly_cargo()
+        .run();
+    let manifest = p.read_file("Cargo.toml");
+    assert!(manifest.contains("[target.'cfg(unix)'.dependencies]"));
+    assert!(manifest.contains(r#"bar = { version = "1.0", optional = true }"#));
+}
+
+#[test]
+fn add_path_dependency() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.0.1"))
+        .file("src/lib.rs", "")
+        .file("libs/bar/Cargo.toml", &basic_manifest("bar", "0.5.0"))
+        .file("libs/bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("add -Z unstable-options --path libs/bar")
+        .masquerade_as_nightly_cargo()
+        .with_stderr("[ADDING] bar (path libs/bar) to dependencies")
+        .run();
+
+    assert!(p
+        .read_file("Cargo.toml")
+        .contains(r#"bar = { path = "libs/bar" }"#));
+    p.cargo("check").run();
+}
+
+#[test]
+fn invalid_edit_leaves_manifest_unchanged() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.0.1"))
+        .file("src/lib.rs", "")
+        .build();
+    let original = p.read_file("Cargo.toml");
+
+    p.cargo("add -Z unstable-options bar@1.0 --git https://example.com/bar --path bar")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr("[ERROR] `--path` and `--git` cannot be used together")
+        .run();
+    p.cargo("add -Z unstable-options bar@1.0 --dev --optional")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains("[ERROR] the edited manifest is invalid, so it was left unchanged")
+        .run();
+
+    assert_eq!(p.read_file("Cargo.toml"), original);
+}
+
+#[test]
+fn add_requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("add bar")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr("[ERROR] `cargo add` is unstable, pass `-Z unstable-options` to enable it")
+        .run();
+}
@@ -1629,3 +1629,63 @@ fn json_artifact_includes_executable_for_benchmark() {
         )
         .run();
 }
+
+#[test]
+fn bench_baselines() {
+    if !is_nightly() {
+        return;
+    }
+
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"
+            #![feature(test)]
+            #[cfg(test)]
+            extern crate test;
+
+            #[bench]
+            fn bench_sum(b: &mut test::Bencher) {
+                b.iter(|| (0..1000u64).map(test::black_box).sum::<u64>())
+            }
+        "#,
+        )
+        .build();
+
+    p.cargo("bench --save-baseline main")
+        .with_stderr_contains("[..]Saved 1 benchmark result as baseline `main`")
+        .run();
+    let baseline = p.read_file("target/bench-baselines/main.json");
+    assert!(baseline.contains("\"foo::foo bench_sum\""));
+
+    p.cargo("bench --baseline main --regression-threshold 1000")
+        .with_stderr_contains("[..]Comparing benchmarks with baseline `main`")
+        .with_stderr_contains("    foo::foo bench_sum  [..] -> [..] ns/iter [..]%")
+        .run();
+
+    p.change_file(
+        "target/bench-baselines/fast.json",
+        r#"{"benchmarks":{"foo::foo bench_sum":{"ns_per_iter":1,"deviation":0}}}"#,
+    );
+    p.cargo("bench --baseline fast")
+        .with_status(101)
+        .with_stderr_contains("[..]REGRESSED")
+        .with_stderr_contains(
+            "[ERROR] 1 benchmark regressed by more than 5% compared with baseline `fast`",
+        )
+        .run();
+
+    p.cargo("bench --baseline missing")
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] no baseline named `missing` was found, \
+             save one first with `--save-baseline missing`",
+        )
+        .run();
+    p.cargo("bench --save-baseline ../main")
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] invalid baseline name `../main`, it cannot contain path separators",
+        )
+        .run();
+}
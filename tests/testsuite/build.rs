@@ -1310,6 +1310,88 @@ failed to select a version for `bad` which could resolve this conflict",
         .run();
 }
 
+#[test]
+fn incompatible_dependencies_suggest_fixes() {
+    Package::new("bad", "1.0.0").publish();
+    Package::new("bad", "1.0.1").publish();
+    Package::new("bar", "0.1.0").dep("bad", "=1.0.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+
+            [dependencies]
+            bar = "0.1.0"
+            bad = ">=1.0.1"
+        "#,
+        )
+        .file("src/main.rs", "fn main(){}")
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains("the conflicting requirements on `bad` are:")
+        .with_stderr_contains("  `bad = \"= 1.0.0\"` required by package `bar v0.1.0`")
+        .with_stderr_contains("  `bad = \">= 1.0.1\"` required by package `foo v0.0.1 ([..])`")
+        .with_stderr_contains(
+            "help: change the requirement `bad = \">= 1.0.1\"` in the manifest of \
+             `foo v0.0.1 ([..])` to `bad = \"1.0.0\"`, which is compatible with the \
+             other requirements",
+        )
+        .with_stderr_contains(
+            "\
+help: no version of `bar` allowed by its dependents has a requirement on `bad` compatible \
+with the other requirements. A copy of `bar v0.1.0` whose requirement is changed to \
+`bad = \"1.0.1\"` can be used instead by adding it to the `[patch]` section of the \
+workspace root's `Cargo.toml`, e.g.:
+
+    [patch.crates-io]
+    bar = { path = \"../bar\" }",
+        )
+        .run();
+}
+
+#[test]
+fn incompatible_dependencies_suggest_upgrade() {
+    Package::new("bad", "1.0.0").publish();
+    Package::new("bad", "1.0.1").publish();
+    Package::new("bar", "0.1.0").dep("bad", "=1.0.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+
+            [dependencies]
+            bar = "0.1.0"
+            bad = "1.0.0"
+        "#,
+        )
+        .file("src/main.rs", "fn main(){}")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+
+    // `bar` stays locked at 0.1.0, which can't use the requested version.
+    Package::new("bar", "0.1.1").dep("bad", "1.0.1").publish();
+    p.cargo("update -p bad --precise 1.0.1")
+        .with_status(101)
+        .with_stderr_contains(
+            "help: upgrade `bar v0.1.0` to v0.1.1, whose requirement on `bad` is \
+             compatible with the other requirements, with \
+             `cargo update -p bar:0.1.0 --precise 0.1.1`",
+        )
+        .with_stderr_does_not_contain("[..][patch][..]")
+        .run();
+}
+
 #[test]
 fn compile_offline_while_transitive_dep_not_cached() {
     let baz = Package::new("baz", "1.0.0");
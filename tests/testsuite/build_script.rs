@@ -321,6 +321,7 @@ the package `a-sys` links to the native library `a`, but it conflicts with a pre
 package `foo v0.5.0 ([..])`
 
 failed to select a version for `a-sys` which could resolve this conflict
+help: only one package in the dependency graph may specify `links = \"a\"`, so that the native library is only linked once, adjust the dependencies above so that a single package links to it
 ").run();
 }
 
@@ -385,6 +386,7 @@ the package `a-sys` links to the native library `a`, but it conflicts with a pre
 package `foo v0.5.0 ([..])`
 
 failed to select a version for `a-sys` which could resolve this conflict
+help: only one package in the dependency graph may specify `links = \"a\"`, so that the native library is only linked once, adjust the dependencies above so that a single package links to it
 ").run();
 }
 
@@ -3421,6 +3423,7 @@ the package `a` links to the native library `a`, but it conflicts with a previou
 package `foo v0.5.0 ([..])`
 
 failed to select a version for `a` which could resolve this conflict
+help: only one package in the dependency graph may specify `links = \"a\"`, so that the native library is only linked once, adjust the dependencies above so that a single package links to it
 ").run();
 }
 
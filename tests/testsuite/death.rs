@@ -129,6 +129,94 @@ fn ctrl_c_kills_everyone() {
     );
 }
 
+// Test binaries run with a timeout are in process groups of their own, which
+// the Ctrl-C doesn't reach.
+#[cfg(unix)]
+#[test]
+fn ctrl_c_kills_tests_with_timeout() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let p = project()
+        .file(
+            "src/lib.rs",
+            &format!(
+                r#"
+            #[test]
+            fn hangs() {{
+                use std::io::Read;
+                let mut socket = std::net::TcpStream::connect("{}").unwrap();
+                let _ = socket.read(&mut [0; 10]);
+                panic!("that read should never return");
+            }}
+        "#,
+                addr
+            ),
+        )
+        .build();
+
+    let mut cargo = p.cargo("test --test-timeout 10m").build_command();
+    cargo
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .env("__CARGO_TEST_SETSID_PLEASE_DONT_USE_ELSEWHERE", "1");
+    let mut child = cargo.spawn().unwrap();
+
+    let mut sock = listener.accept().unwrap().0;
+    ctrl_c(&mut child);
+
+    assert!(!child.wait().unwrap().success());
+    match sock.read(&mut [0; 10]) {
+        Ok(n) => assert_eq!(n, 0),
+        Err(e) => assert_eq!(e.kind(), io::ErrorKind::ConnectionReset),
+    }
+}
+
+// The binary of `cargo run --watch` is in a process group of its own too.
+#[cfg(unix)]
+#[test]
+fn ctrl_c_kills_run_watch() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let p = project()
+        .file(
+            "src/main.rs",
+            &format!(
+                r#"
+            use std::io::Read;
+            use std::net::TcpStream;
+
+            fn main() {{
+                let mut socket = TcpStream::connect("{}").unwrap();
+                let _ = socket.read(&mut [0; 10]);
+                panic!("that read should never return");
+            }}
+        "#,
+                addr
+            ),
+        )
+        .build();
+
+    let mut cargo = p.cargo("run --watch").build_command();
+    cargo
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .env("__CARGO_TEST_SETSID_PLEASE_DONT_USE_ELSEWHERE", "1");
+    let mut child = cargo.spawn().unwrap();
+
+    let mut sock = listener.accept().unwrap().0;
+    ctrl_c(&mut child);
+
+    assert!(!child.wait().unwrap().success());
+    match sock.read(&mut [0; 10]) {
+        Ok(n) => assert_eq!(n, 0),
+        Err(e) => assert_eq!(e.kind(), io::ErrorKind::ConnectionReset),
+    }
+}
+
 #[cfg(unix)]
 fn ctrl_c(child: &mut Child) {
     let r = unsafe { libc::kill(-(child.id() as i32), libc::SIGINT) };
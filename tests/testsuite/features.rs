@@ -2,7 +2,7 @@ use std::fs::File;
 use std::io::prelude::*;
 
 use crate::support::paths::CargoPathExt;
-use crate::support::registry::Package;
+use crate::support::registry::{Dependency, Package};
 use crate::support::{basic_manifest, project};
 
 #[test]
@@ -1867,3 +1867,347 @@ fn warn_if_default_features() {
             "#.trim(),
         ).run();
 }
+
+#[test]
+fn dep_syntax_has_no_implicit_feature() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["dep-features"]
+
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = { path = "bar", optional = true }
+
+            [features]
+            feat = ["dep:bar"]
+        "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"
+            #[cfg(feature = "bar")]
+            compile_error!("`bar` is not a feature");
+
+            #[cfg(feature = "feat")]
+            extern crate bar;
+
+            fn main() {
+                #[cfg(feature = "feat")]
+                bar::bar();
+            }
+        "#,
+        )
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "pub fn bar() {}")
+        .build();
+
+    p.cargo("run --features feat")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_contains("[COMPILING] bar v0.0.1 ([..])")
+        .run();
+
+    p.cargo("build --features bar")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains(
+            "[..]Package `foo v0.0.1 ([..])` does not have feature `bar`. It has an optional \
+             dependency with that name, but that dependency is enabled with `dep:bar` in its \
+             features, so it has no implicit feature of the same name.",
+        )
+        .run();
+}
+
+#[test]
+fn dep_syntax_feature_with_same_name() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["dep-features"]
+
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = { path = "bar", optional = true }
+
+            [features]
+            bar = ["dep:bar", "bar/extra"]
+        "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"
+            #[cfg(feature = "bar")]
+            extern crate bar;
+
+            fn main() {
+                #[cfg(feature = "bar")]
+                assert!(bar::extra());
+            }
+        "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            [package]
+            name = "bar"
+            version = "0.0.1"
+
+            [features]
+            extra = []
+        "#,
+        )
+        .file(
+            "bar/src/lib.rs",
+            r#"pub fn extra() -> bool { cfg!(feature = "extra") }"#,
+        )
+        .build();
+
+    p.cargo("run --features bar")
+        .masquerade_as_nightly_cargo()
+        .run();
+}
+
+#[test]
+fn dep_syntax_unstable() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = { path = "bar", optional = true }
+
+            [features]
+            feat = ["dep:bar"]
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at [..]
+
+Caused by:
+  the `dep:` syntax of `dep:bar` is unstable
+
+Caused by:
+  feature `dep-features` is required
+
+this Cargo does not support nightly features, but if you
+switch to nightly channel you can add
+`cargo-features = [\"dep-features\"]` to enable this feature
+",
+        )
+        .run();
+}
+
+#[test]
+fn dep_syntax_requires_optional_dependency() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["dep-features", "weak-dep-features"]
+
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = { path = "bar" }
+
+            [features]
+            feat = ["dep:bar"]
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at [..]
+
+Caused by:
+  Feature `feat` includes `dep:bar`, but `bar` is not an optional dependency.
+Consider adding `optional = true` to the dependency
+",
+        )
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+        cargo-features = ["dep-features", "weak-dep-features"]
+
+        [project]
+        name = "foo"
+        version = "0.0.1"
+        authors = []
+
+        [dependencies]
+        bar = { path = "bar" }
+
+        [features]
+        feat = ["bar?/extra"]
+    "#,
+    );
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at [..]
+
+Caused by:
+  Feature `feat` includes `bar?/extra` with a `?`, but `bar` is not an optional dependency.
+A non-optional dependency is always enabled, consider removing the `?`
+",
+        )
+        .run();
+}
+
+#[test]
+fn weak_dep_feature() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["weak-dep-features"]
+
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = { path = "bar", optional = true }
+
+            [features]
+            std = ["bar?/extra"]
+        "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"
+            #[cfg(feature = "bar")]
+            extern crate bar;
+
+            fn main() {
+                #[cfg(feature = "bar")]
+                assert_eq!(bar::extra(), cfg!(feature = "std"));
+            }
+        "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            [package]
+            name = "bar"
+            version = "0.0.1"
+
+            [features]
+            extra = []
+        "#,
+        )
+        .file(
+            "bar/src/lib.rs",
+            r#"pub fn extra() -> bool { cfg!(feature = "extra") }"#,
+        )
+        .build();
+
+    // `std` alone doesn't pull in `bar`.
+    p.cargo("run --features std")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_does_not_contain("[COMPILING] bar [..]")
+        .run();
+    p.cargo("run --features bar")
+        .masquerade_as_nightly_cargo()
+        .run();
+    p.cargo("run --features std,bar")
+        .masquerade_as_nightly_cargo()
+        .run();
+}
+
+#[test]
+fn dep_syntax_in_registry_index() {
+    Package::new("inner", "1.0.0")
+        .file("src/lib.rs", "pub fn inner() {}")
+        .publish();
+    Package::new("outer", "1.0.0")
+        .add_dep(Dependency::new("inner", "1.0").optional(true))
+        .feature("on", &["dep:inner"])
+        .file(
+            "src/lib.rs",
+            r#"
+            #[cfg(feature = "on")]
+            extern crate inner;
+
+            #[cfg(feature = "on")]
+            pub fn on() { inner::inner() }
+        "#,
+        )
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            outer = { version = "1.0", features = ["on"] }
+        "#,
+        )
+        .file(
+            "src/main.rs",
+            "extern crate outer; fn main() { outer::on() }",
+        )
+        .build();
+
+    p.cargo("run").run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+        [project]
+        name = "foo"
+        version = "0.0.1"
+        authors = []
+
+        [dependencies]
+        outer = { version = "1.0", features = ["inner"] }
+    "#,
+    );
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains("[..]does not have feature `inner`[..]")
+        .run();
+}
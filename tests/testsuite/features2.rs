@@ -0,0 +1,247 @@
+use crate::support::project;
+use crate::support::registry::Package;
+
+/// Publishes `common`, whose `STD` constant tells whether its `std` feature
+/// is enabled.
+fn publish_common() {
+    Package::new("common", "1.0.0")
+        .feature("std", &[])
+        .file(
+            "src/lib.rs",
+            r#"
+            #[cfg(feature = "std")]
+            pub const STD: bool = true;
+            #[cfg(not(feature = "std"))]
+            pub const STD: bool = false;
+            "#,
+        )
+        .publish();
+}
+
+#[test]
+fn build_dep_features_not_unified() {
+    publish_common();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["resolver"]
+
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            resolver = "2"
+
+            [dependencies]
+            common = "1.0"
+
+            [build-dependencies]
+            common = { version = "1.0", features = ["std"] }
+            "#,
+        )
+        .file("build.rs", "fn main() { assert!(common::STD); }")
+        .file("src/main.rs", "fn main() { assert!(!common::STD); }")
+        .build();
+
+    p.cargo("run").masquerade_as_nightly_cargo().run();
+
+    // The features are unified without the `resolver` key.
+    p.change_file(
+        "Cargo.toml",
+        r#"
+        [package]
+        name = "foo"
+        version = "0.1.0"
+
+        [dependencies]
+        common = "1.0"
+
+        [build-dependencies]
+        common = { version = "1.0", features = ["std"] }
+        "#,
+    );
+    p.cargo("run")
+        .with_status(101)
+        .with_stderr_contains("[..]assertion failed: !common::STD[..]")
+        .run();
+}
+
+#[test]
+fn dev_dep_features_only_for_tests() {
+    publish_common();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["resolver"]
+
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            resolver = "2"
+
+            [dependencies]
+            common = "1.0"
+
+            [dev-dependencies]
+            common = { version = "1.0", features = ["std"] }
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"
+            fn main() { assert!(!common::STD); }
+
+            #[test]
+            fn std_enabled() { assert!(common::STD); }
+            "#,
+        )
+        .build();
+
+    p.cargo("run").masquerade_as_nightly_cargo().run();
+    p.cargo("test").masquerade_as_nightly_cargo().run();
+}
+
+#[test]
+fn required_features_not_unified() {
+    publish_common();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["resolver"]
+
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            resolver = "2"
+
+            [dependencies]
+            common = "1.0"
+
+            [build-dependencies]
+            common = { version = "1.0", features = ["std"] }
+
+            [[bin]]
+            name = "needs-std"
+            required-features = ["common/std"]
+            "#,
+        )
+        .file("build.rs", "fn main() {}")
+        .file("src/main.rs", "fn main() {}")
+        .file("src/bin/needs-std.rs", "fn main() {}")
+        .build();
+
+    // Only the build script gets `common/std`, so the binary is skipped.
+    p.cargo("build").masquerade_as_nightly_cargo().run();
+    assert!(!p.bin("needs-std").is_file());
+
+    p.cargo("build --bin needs-std")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+error: target `needs-std` in package `foo` requires the features: `common/std`
+Consider enabling them by passing, e.g., `--features=\"common/std\"`
+",
+        )
+        .run();
+}
+
+#[test]
+fn resolver_in_virtual_workspace() {
+    publish_common();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["resolver"]
+
+            [workspace]
+            members = ["foo"]
+            resolver = "2"
+            "#,
+        )
+        .file(
+            "foo/Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            common = "1.0"
+
+            [build-dependencies]
+            common = { version = "1.0", features = ["std"] }
+            "#,
+        )
+        .file("foo/build.rs", "fn main() { assert!(common::STD); }")
+        .file("foo/src/main.rs", "fn main() { assert!(!common::STD); }")
+        .build();
+
+    p.cargo("run").masquerade_as_nightly_cargo().run();
+}
+
+#[test]
+fn resolver_invalid_and_unstable() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            resolver = "2"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr(
+            "\
+error: failed to parse manifest at [..]
+
+Caused by:
+  the `resolver` manifest key is unstable
+
+Caused by:
+  feature `resolver` is required
+
+this Cargo does not support nightly features, but if you
+switch to nightly channel you can add
+`cargo-features = [\"resolver\"]` to enable this feature
+",
+        )
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+        cargo-features = ["resolver"]
+
+        [package]
+        name = "foo"
+        version = "0.1.0"
+        resolver = "3"
+        "#,
+    );
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+error: failed to parse manifest at [..]
+
+Caused by:
+  `resolver` setting `3` is not valid, valid options are \"1\" or \"2\"
+",
+        )
+        .run();
+}
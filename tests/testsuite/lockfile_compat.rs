@@ -506,3 +506,144 @@ error: the lock file [CWD]/Cargo.lock needs to be updated but --locked was passe
         )
         .run();
 }
+
+#[test]
+fn v2_format_opt_in_and_kept() {
+    let cksum = Package::new("bar", "0.1.0").publish();
+    Package::new("baz", "0.1.0").publish();
+    Package::new("baz", "0.2.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.1.0"
+            baz = "0.2.0"
+            old-baz = { package = "baz", version = "0.1.0" }
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile -Z lockfile-v2")
+        .masquerade_as_nightly_cargo()
+        .run();
+
+    let expected = format!(
+        r#"# This file is automatically @generated by Cargo.
+# It is not intended for manual editing.
+version = 2
+
+[[package]]
+name = "bar"
+version = "0.1.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "{}"
+
+[[package]]
+name = "baz"
+version = "0.1.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "[..]"
+
+[[package]]
+name = "baz"
+version = "0.2.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "[..]"
+
+[[package]]
+name = "foo"
+version = "0.0.1"
+dependencies = [
+ "bar",
+ "baz 0.1.0",
+ "baz 0.2.0",
+]
+"#,
+        cksum
+    );
+    let assert_lockfile = || {
+        let lock = p.read_lockfile();
+        for (l, r) in expected.lines().zip(lock.lines()) {
+            assert!(lines_match(l, r), "Lines differ:\n{}\n\n{}", l, r);
+        }
+        assert_eq!(lock.lines().count(), expected.lines().count());
+    };
+    assert_lockfile();
+
+    // The format is detected, and kept without the flag.
+    p.cargo("build").run();
+    assert_lockfile();
+    Package::new("bar", "0.1.1").publish();
+    p.cargo("update -p bar --precise 0.1.0").run();
+    assert_lockfile();
+    p.cargo("build --locked").run();
+}
+
+#[test]
+fn v1_format_is_kept() {
+    Package::new("bar", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.1.0"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+    let lock = p.read_lockfile();
+    assert!(lock.contains("\"bar 0.1.0 (registry+"));
+    assert!(lock.contains("[metadata]\n\"checksum bar 0.1.0"));
+    assert!(!lock.contains("\nchecksum = "));
+    assert!(!lock.contains("\nversion = 2"));
+
+    p.cargo("build").run();
+    assert_eq!(lock, p.read_lockfile());
+}
+
+#[test]
+fn unknown_lockfile_version() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            "Cargo.lock",
+            r#"
+version = 99
+
+[[package]]
+name = "foo"
+version = "0.0.1"
+"#,
+        )
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse lock file at: [..]Cargo.lock
+
+Caused by:
+  lock file version `99` was found, but this version of Cargo does not \
+understand this lock file, perhaps Cargo needs to be updated?
+",
+        )
+        .run();
+}
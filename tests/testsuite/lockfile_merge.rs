@@ -0,0 +1,175 @@
+use crate::support::git;
+use crate::support::registry::Package;
+use crate::support::{basic_manifest, project};
+
+#[test]
+fn merge_updates_from_both_sides() {
+    Package::new("bar", "0.1.0").publish();
+    Package::new("baz", "1.0.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+
+            [dependencies]
+            bar = "0.1"
+            baz = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+    let base = p.read_lockfile();
+
+    Package::new("bar", "0.1.1").publish();
+    Package::new("baz", "1.0.1").publish();
+    p.cargo("update -p bar").run();
+    let ours = p.read_lockfile();
+    p.change_file("Cargo.lock", &base);
+    p.cargo("update -p baz").run();
+    let theirs = p.read_lockfile();
+    p.change_file("Cargo.lock", &ours);
+
+    p.change_file("base.lock", &base);
+    p.change_file("ours.lock", &ours);
+    p.change_file("theirs.lock", &theirs);
+    p.cargo("-Z unstable-options lockfile merge base.lock ours.lock theirs.lock")
+        .masquerade_as_nightly_cargo()
+        .run();
+
+    let merged = p.read_file("ours.lock");
+    assert!(merged.contains("name = \"bar\"\nversion = \"0.1.1\""));
+    assert!(merged.contains("name = \"baz\"\nversion = \"1.0.1\""));
+    assert!(!merged.contains("version = \"0.1.0\""));
+    assert!(!merged.contains("version = \"1.0.0\""));
+
+    // The merged lock file is consistent with the manifest.
+    p.change_file("Cargo.lock", &merged);
+    p.cargo("build --locked").run();
+}
+
+#[test]
+fn merge_dependencies_added_on_both_sides() {
+    Package::new("bar", "0.1.0").publish();
+    Package::new("baz", "1.0.0").publish();
+
+    let manifest = |deps: &str| {
+        format!(
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+
+            [dependencies]
+            {}
+            "#,
+            deps
+        )
+    };
+    let p = project()
+        .file("Cargo.toml", &manifest(""))
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+    let base = p.read_lockfile();
+    p.change_file("Cargo.toml", &manifest("bar = \"0.1\""));
+    p.cargo("generate-lockfile").run();
+    let ours = p.read_lockfile();
+    p.change_file("Cargo.toml", &manifest("baz = \"1.0\""));
+    p.cargo("generate-lockfile").run();
+    let theirs = p.read_lockfile();
+
+    // Both locked versions are kept instead of resolving the dependency of
+    // one side again.
+    Package::new("bar", "0.1.1").publish();
+    Package::new("baz", "1.0.1").publish();
+    p.change_file("Cargo.toml", &manifest("bar = \"0.1\"\nbaz = \"1.0\""));
+    p.change_file("base.lock", &base);
+    p.change_file("ours.lock", &ours);
+    p.change_file("theirs.lock", &theirs);
+    p.cargo("-Z unstable-options lockfile merge base.lock ours.lock theirs.lock")
+        .masquerade_as_nightly_cargo()
+        .run();
+
+    let merged = p.read_file("ours.lock");
+    assert!(merged.contains("name = \"bar\"\nversion = \"0.1.0\""));
+    assert!(merged.contains("name = \"baz\"\nversion = \"1.0.0\""));
+}
+
+#[test]
+fn merge_conflicting_git_revisions() {
+    let git_project = git::new("dep", |project| {
+        project
+            .file("Cargo.toml", &basic_manifest("dep", "0.5.0"))
+            .file("src/lib.rs", "")
+    })
+    .unwrap();
+    let repo = git2::Repository::open(&git_project.root()).unwrap();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                dep = {{ git = '{}' }}
+                "#,
+                git_project.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+    let base = p.read_lockfile();
+
+    git_project.change_file("src/lib.rs", "pub fn one() {}");
+    git::add(&repo);
+    git::commit(&repo);
+    p.cargo("update -p dep").run();
+    let ours = p.read_lockfile();
+
+    git_project.change_file("src/lib.rs", "pub fn two() {}");
+    git::add(&repo);
+    git::commit(&repo);
+    p.cargo("update -p dep").run();
+    let theirs = p.read_lockfile();
+
+    p.change_file("base.lock", &base);
+    p.change_file("ours.lock", &ours);
+    p.change_file("theirs.lock", &theirs);
+    p.cargo("-Z unstable-options lockfile merge base.lock ours.lock theirs.lock")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to merge the lock files, the conflict must be resolved by hand
+
+Caused by:
+  `dep v0.5.0` is locked to revision `[..]` on one side and to revision `[..]` on the other side
+",
+        )
+        .run();
+    assert_eq!(p.read_file("ours.lock"), ours);
+}
+
+#[test]
+fn merge_requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("lockfile merge base.lock ours.lock theirs.lock")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr("[ERROR] `cargo lockfile` is unstable, pass `-Z unstable-options` to enable it")
+        .run();
+}
@@ -8,6 +8,7 @@
 #[macro_use]
 mod support;
 
+mod add;
 mod alt_registry;
 mod bad_config;
 mod bad_manifest_path;
@@ -37,6 +38,7 @@ mod directory;
 mod doc;
 mod edition;
 mod features;
+mod features2;
 mod fetch;
 mod fix;
 mod freshness;
@@ -49,6 +51,7 @@ mod jobserver;
 mod list_targets;
 mod local_registry;
 mod lockfile_compat;
+mod lockfile_merge;
 mod login;
 mod member_errors;
 mod metabuild;
@@ -60,19 +63,23 @@ mod overrides;
 mod package;
 mod patch;
 mod path;
+mod platform_features;
 mod plugins;
 mod proc_macro;
 mod profile_config;
 mod profile_overrides;
 mod profile_targets;
 mod profiles;
+mod pub_priv;
 mod publish;
 mod read_manifest;
 mod registry;
+mod remove;
 mod rename_deps;
 mod required_features;
 mod resolve;
 mod run;
+mod rust_version;
 mod rustc;
 mod rustc_info_cache;
 mod rustdoc;
@@ -83,10 +90,15 @@ mod shell_quoting;
 mod small_fd_limits;
 mod test;
 mod tool_paths;
+mod tree;
 mod update;
+mod upgrade;
 mod verify_project;
 mod version;
 mod warn_on_failure;
+mod watch;
+mod workspace_inheritance;
+mod workspace_policy;
 mod workspaces;
 
 #[test]
@@ -0,0 +1,160 @@
+use crate::support::project;
+
+/// Builds `foo` depending on `bar`, whose `std` feature is only enabled by
+/// default on the current platform.
+fn platform_default_project(resolver: &str) -> crate::support::Project {
+    project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                cargo-features = ["resolver"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                {}
+
+                [dependencies]
+                bar = {{ path = "bar" }}
+                "#,
+                resolver
+            ),
+        )
+        .file("src/main.rs", "fn main() { bar::std(); }")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            cargo-features = ["platform-features"]
+
+            [package]
+            name = "bar"
+            version = "0.1.0"
+
+            [features]
+            std = []
+            embedded = []
+
+            [target.'cfg(all())'.features]
+            default = ["std"]
+
+            [target.'cfg(any())'.features]
+            default = ["embedded"]
+            "#,
+        )
+        .file(
+            "bar/src/lib.rs",
+            r#"
+            #[cfg(feature = "std")]
+            pub fn std() {}
+            #[cfg(feature = "embedded")]
+            compile_error!("embedded enabled");
+            "#,
+        )
+        .build()
+}
+
+#[test]
+fn platform_default_features() {
+    let p = platform_default_project(r#"resolver = "2""#);
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[COMPILING] bar v0.1.0 ([..])
+[COMPILING] foo v0.1.0 ([..])
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
+#[test]
+fn platform_features_require_resolver() {
+    let p = platform_default_project("");
+
+    // The default resolver can't leave out the values of other platforms.
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] package `bar v0.1.0 ([CWD]/bar)` has `[target.'cfg(..)'.features]`, which are \
+only supported with `resolver = \"2\"` in the root manifest of the workspace
+",
+        )
+        .run();
+}
+
+#[test]
+fn platform_feature_not_declared() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["platform-features"]
+
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [target.'cfg(unix)'.features]
+            std = []
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  feature `std` of `[target.'cfg(unix)'.features]` is not declared in `[features]`
+",
+        )
+        .run();
+}
+
+#[test]
+fn platform_features_unstable() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [features]
+            std = []
+
+            [target.'cfg(unix)'.features]
+            default = ["std"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  the `[target.'cfg(unix)'.features]` table is unstable
+
+Caused by:
+  feature `platform-features` is required
+
+consider adding `cargo-features = [\"platform-features\"]` to the manifest
+",
+        )
+        .run();
+}
@@ -0,0 +1,226 @@
+use crate::support::registry::Package;
+use crate::support::{is_nightly, project};
+
+#[test]
+fn exported_priv_warning() {
+    if !is_nightly() {
+        return;
+    }
+    Package::new("priv_dep", "0.1.0")
+        .file("src/lib.rs", "pub struct FromPriv;")
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["public-dependency"]
+
+            [package]
+            name = "foo"
+            version = "0.0.1"
+
+            [dependencies]
+            priv_dep = "0.1.0"
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            "
+            extern crate priv_dep;
+            pub fn use_priv(_: priv_dep::FromPriv) {}
+            ",
+        )
+        .build();
+
+    p.cargo("build --message-format=short")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_contains(
+            "\
+src/lib.rs:3:13: warning: type `[..]FromPriv` from private dependency 'priv_dep' in public interface
+",
+        )
+        .run()
+}
+
+#[test]
+fn exported_pub_dep() {
+    if !is_nightly() {
+        return;
+    }
+    Package::new("pub_dep", "0.1.0")
+        .file("src/lib.rs", "pub struct FromPub;")
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["public-dependency"]
+
+            [package]
+            name = "foo"
+            version = "0.0.1"
+
+            [dependencies]
+            pub_dep = { version = "0.1.0", public = true }
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            "
+            extern crate pub_dep;
+            pub fn use_pub(_: pub_dep::FromPub) {}
+            ",
+        )
+        .build();
+
+    p.cargo("build --message-format=short")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[DOWNLOADING] crates ...
+[DOWNLOADED] pub_dep v0.1.0 ([..])
+[COMPILING] pub_dep v0.1.0
+[COMPILING] foo v0.0.1 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run()
+}
+
+#[test]
+fn requires_feature() {
+    Package::new("pub_dep", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+
+            [dependencies]
+            pub_dep = { version = "0.1.0", public = true }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  feature `public-dependency` is required
+
+consider adding `cargo-features = [\"public-dependency\"]` to the manifest
+",
+        )
+        .run()
+}
+
+#[test]
+fn pub_dev_dependency() {
+    Package::new("pub_dep", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["public-dependency"]
+
+            [package]
+            name = "foo"
+            version = "0.0.1"
+
+            [dev-dependencies]
+            pub_dep = { version = "0.1.0", public = true }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  `public` can only be specified for normal dependencies, but `pub_dep` is a dev dependency
+",
+        )
+        .run()
+}
+
+#[test]
+fn public_dependencies_are_unique() {
+    Package::new("bar", "0.1.0").publish();
+    Package::new("bar", "0.2.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["public-dependency"]
+
+            [package]
+            name = "foo"
+            version = "0.0.1"
+
+            [dependencies]
+            bar = "0.1"
+            baz = { path = "baz" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "baz/Cargo.toml",
+            r#"
+            cargo-features = ["public-dependency"]
+
+            [package]
+            name = "baz"
+            version = "0.0.1"
+
+            [dependencies]
+            bar = { version = "0.2", public = true }
+            "#,
+        )
+        .file("baz/src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+help: a package may only see one version of `bar` through its dependencies and their \
+public dependencies, make some of the dependencies above private or use a single version
+",
+        )
+        .run();
+
+    // Without the public dependency both versions can be used.
+    p.change_file(
+        "baz/Cargo.toml",
+        r#"
+        [package]
+        name = "baz"
+        version = "0.0.1"
+
+        [dependencies]
+        bar = "0.2"
+        "#,
+    );
+    p.cargo("generate-lockfile")
+        .masquerade_as_nightly_cargo()
+        .run();
+}
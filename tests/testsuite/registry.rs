@@ -1,5 +1,6 @@
 use std::fs::{self, File};
 use std::io::prelude::*;
+use std::path::PathBuf;
 
 use crate::support::cargo_process;
 use crate::support::git;
@@ -550,6 +551,81 @@ required by package `bar v0.0.1`
         .run();
 }
 
+#[test]
+fn yanked_versions_are_mentioned() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.1"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    Package::new("bar", "0.0.1").publish();
+    Package::new("bar", "0.1.0").yanked(true).publish();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+error: failed to select a version for the requirement `bar = \"^0.1\"`
+  candidate versions found which didn't match: 0.0.1
+  location searched: `[..]` index (which is replacing registry `[..]`)
+required by package `foo v0.0.1 ([..])`
+perhaps a crate was updated and forgotten to be re-vendored?
+note: version 0.1.0 of `bar` matches `bar = \"^0.1\"` but was skipped because it has been yanked
+",
+        )
+        .run();
+}
+
+#[test]
+fn pre_release_versions_are_mentioned() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.2"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    Package::new("bar", "0.1.0").publish();
+    Package::new("bar", "0.2.0-alpha.1").publish();
+    Package::new("bar", "0.2.0-beta.1").publish();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+error: failed to select a version for the requirement `bar = \"^0.2\"`
+  candidate versions found which didn't match: 0.2.0-beta.1, 0.2.0-alpha.1, 0.1.0
+  location searched: `[..]` index (which is replacing registry `[..]`)
+required by package `foo v0.0.1 ([..])`
+perhaps a crate was updated and forgotten to be re-vendored?
+note: the pre-release versions 0.2.0-beta.1, 0.2.0-alpha.1 of `bar` were skipped, \
+pre-releases are only selected by requirements naming one
+help: to use 0.2.0-beta.1, change the requirement to `bar = \"0.2.0-beta.1\"`
+",
+        )
+        .run();
+}
+
 #[test]
 fn yanks_in_lockfiles_are_ok() {
     let p = project()
@@ -590,6 +666,81 @@ required by package `foo v0.0.1 ([..])`
         .run();
 }
 
+#[test]
+fn yanks_in_lockfiles_are_reported() {
+    Package::new("bar", "0.1.0").publish();
+    Package::new("baz", "1.0.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+
+            [dependencies]
+            bar = "0.1"
+            baz = "1.0"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+
+    registry_path().join("3/b/bar").rm_rf();
+    Package::new("bar", "0.1.0").yanked(true).publish();
+    Package::new("bar", "0.1.1").publish();
+
+    p.cargo("update -p baz")
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[WARNING] package `bar v0.1.0` in Cargo.lock has been yanked from its registry, \
+`cargo update -p bar` would update it to v0.1.1
+",
+        )
+        .run();
+
+    p.cargo("fetch")
+        .with_stderr_contains(
+            "\
+[WARNING] package `bar v0.1.0` in Cargo.lock has been yanked from its registry, \
+consider updating it with `cargo update -p bar`
+",
+        )
+        .run();
+
+    // The warning isn't repeated until the index is updated again.
+    p.cargo("fetch").with_stderr("").run();
+
+    p.change_file(".cargo/config", "[registry]\nyanked = \"deny\"");
+    p.cargo("fetch")
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the lock file contains packages which have been yanked from their registries:
+
+  bar v0.1.0
+
+update them with `cargo update -p <name>`, or set the `registry.yanked` config \
+to \"warn\" to allow them
+",
+        )
+        .run();
+
+    p.cargo("update -p bar")
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[UPDATING] bar v0.1.0 -> v0.1.1
+",
+        )
+        .run();
+    p.cargo("fetch").run();
+}
+
 #[test]
 fn yanks_in_lockfiles_are_ok_for_other_update() {
     let p = project()
@@ -1977,3 +2128,127 @@ fn rename_deps_and_features() {
     p.cargo("build --features bar/foo01").run();
     p.cargo("build --features bar/another").run();
 }
+
+fn index_dir() -> PathBuf {
+    let index = paths::home().join(".cargo/registry/index");
+    index.read_dir().unwrap().next().unwrap().unwrap().path()
+}
+
+fn index_cache_dir() -> PathBuf {
+    let mut commits = index_dir()
+        .join(".cache")
+        .read_dir()
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.file_name().unwrap().to_str().unwrap().starts_with("v2-"));
+    let cache = commits.next().unwrap();
+    assert!(commits.next().is_none());
+    cache
+}
+
+#[test]
+fn index_files_are_prefetched_and_cached() {
+    Package::new("baz", "0.1.0").publish();
+    Package::new("bar", "0.1.0").dep("baz", "0.1").publish();
+    Package::new("unused", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.1"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+
+    let cache = index_cache_dir();
+    assert!(cache.join("3/b/bar").is_file());
+    assert!(cache.join("3/b/baz").is_file());
+    assert!(!cache.join("un/us/unused").exists());
+}
+
+#[test]
+fn prefetch_follows_locked_versions() {
+    Package::new("baz", "0.1.0").publish();
+    Package::new("bar", "0.1.0").dep("baz", "0.1").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.1"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+
+    Package::new("qux", "0.1.0").publish();
+    Package::new("bar", "0.1.1").dep("qux", "0.1").publish();
+    p.cargo("update -p baz").run();
+
+    let cache = index_cache_dir();
+    assert!(cache.join("3/b/baz").is_file());
+    assert!(!cache.join("3/q/qux").exists());
+}
+
+#[test]
+fn index_cache_is_used_until_the_index_changes() {
+    Package::new("bar", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.1"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+
+    // A version which is only in the cache is seen as long as the index
+    // doesn't change.
+    let cache = index_cache_dir();
+    let path = cache.join("3/b/bar");
+    let contents = fs::read_to_string(&path).unwrap();
+    let line = contents.lines().nth(1).unwrap().replace("0.1.0", "0.1.1");
+    fs::write(&path, format!("{}\n{}\n", contents.trim_end(), line)).unwrap();
+    p.cargo("generate-lockfile").run();
+    assert!(p.read_lockfile().contains("bar 0.1.1"));
+
+    // Once it does, the cache of the previous commit is removed, but
+    // anything else in the cache directory is left alone.
+    let other = index_dir().join(".cache/3/b/bar");
+    fs::create_dir_all(other.parent().unwrap()).unwrap();
+    fs::write(&other, "").unwrap();
+    Package::new("baz", "0.1.0").publish();
+    p.cargo("generate-lockfile").run();
+    assert!(p.read_lockfile().contains("bar 0.1.0"));
+    assert!(!cache.exists());
+    assert!(other.is_file());
+    assert!(index_cache_dir().join("3/b/bar").is_file());
+}
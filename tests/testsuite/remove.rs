@@ -0,0 +1,86 @@
+use crate::support::project;
+use crate::support::registry::Package;
+
+#[test]
+fn remove_dependency() {
+    Package::new("bar", "0.1.0").publish();
+    Package::new("baz", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+
+            [dependencies]
+            # Needed for parsing.
+            bar = "0.1"
+            baz = { version = "0.1", optional = true }
+
+            [features]
+            default = ["fancy"]
+            fancy = ["baz"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("remove -Z unstable-options baz")
+        .masquerade_as_nightly_cargo()
+        .with_stderr("[REMOVING] baz from dependencies")
+        .run();
+
+    let manifest = p.read_file("Cargo.toml");
+    assert!(manifest.contains("# Needed for parsing.\n"));
+    assert!(manifest.contains("bar = \"0.1\""));
+    assert!(!manifest.contains("baz"));
+    assert!(manifest.contains("fancy = []"));
+    p.cargo("check").run();
+}
+
+#[test]
+fn remove_missing_dependency() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+
+            [dependencies]
+            bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "bar/Cargo.toml",
+            &crate::support::basic_manifest("bar", "0.1.0"),
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("remove -Z unstable-options bar --dev")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr("[ERROR] the manifest has no `dev-dependencies` table")
+        .run();
+    p.cargo("remove -Z unstable-options baz")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr("[ERROR] the dependency `baz` could not be found in `dependencies`")
+        .run();
+}
+
+#[test]
+fn remove_requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("remove bar")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr("[ERROR] `cargo remove` is unstable, pass `-Z unstable-options` to enable it")
+        .run();
+}
@@ -591,6 +591,45 @@ fn minimal_version_cli() {
     assert!(lock.contains("dep 1.0.0"));
 }
 
+// Ensure that with "-Z direct-minimal-versions" only the direct dependencies
+// get their minimal version.
+#[test]
+fn direct_minimal_version_cli() {
+    Package::new("indirect", "1.0.0").publish();
+    Package::new("indirect", "1.1.0").publish();
+    Package::new("dep", "1.0.0").dep("indirect", "1.0").publish();
+    Package::new("dep", "1.1.0").dep("indirect", "1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            authors = []
+            version = "0.0.1"
+
+            [dependencies]
+            dep = "1.0"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("generate-lockfile -Zdirect-minimal-versions")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_contains(
+            "[..]Resolved direct dependencies of the workspace members to their minimal \
+             versions, and the others to their newest versions",
+        )
+        .run();
+
+    let lock = p.read_lockfile();
+
+    assert!(lock.contains("dep 1.0.0"));
+    assert!(lock.contains("indirect 1.1.0"));
+}
+
 #[test]
 fn resolving_incompat_versions() {
     let reg = registry(vec![
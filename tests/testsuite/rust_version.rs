@@ -0,0 +1,207 @@
+use crate::support::project;
+use crate::support::registry::Package;
+
+#[test]
+fn rust_version_satisfied() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["rust-version"]
+
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            rust-version = "1.0"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build").masquerade_as_nightly_cargo().run();
+}
+
+#[test]
+fn rust_version_too_new() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["rust-version"]
+
+            [package]
+            name = "foo"
+            version = "0.0.1"
+
+            [dependencies]
+            bar = { path = "bar" }
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            cargo-features = ["rust-version"]
+
+            [package]
+            name = "bar"
+            version = "0.0.1"
+            rust-version = "1.9876.0"
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] package `bar v0.0.1 ([..])` cannot be built because it requires rustc \
+1.9876.0 or newer, while the currently active rustc version is [..]
+",
+        )
+        .run();
+}
+
+#[test]
+fn rust_version_invalid() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["rust-version"]
+
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            rust-version = "1.36-nightly"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at [..]
+
+Caused by:
+  `1.36-nightly` is not a valid rust-version, expected a version like \"1.36\" or \"1.36.0\"
+",
+        )
+        .run();
+}
+
+#[test]
+fn rust_version_unstable() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            rust-version = "1.0"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at [..]
+
+Caused by:
+  feature `rust-version` is required
+
+this Cargo does not support nightly features, but if you
+switch to nightly channel you can add
+`cargo-features = [\"rust-version\"]` to enable this feature
+",
+        )
+        .run();
+}
+
+#[test]
+fn msrv_policy_prefers_compatible_versions() {
+    Package::new("bar", "1.0.0").rust_version("1.0").publish();
+    Package::new("bar", "1.1.0")
+        .rust_version("1.9876")
+        .publish();
+    Package::new("baz", "1.0.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["rust-version"]
+
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            rust-version = "1.20"
+
+            [dependencies]
+            bar = "1.0"
+            baz = "1.0"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("generate-lockfile -Z msrv-policy")
+        .masquerade_as_nightly_cargo()
+        .run();
+    let lock = p.read_lockfile();
+    assert!(lock.contains("name = \"bar\"\nversion = \"1.0.0\""));
+    assert!(lock.contains("name = \"baz\"\nversion = \"1.0.0\""));
+
+    // The newest version is still picked when nothing else matches.
+    p.change_file(
+        "Cargo.toml",
+        r#"
+        cargo-features = ["rust-version"]
+
+        [package]
+        name = "foo"
+        version = "0.0.1"
+        rust-version = "1.20"
+
+        [dependencies]
+        bar = "1.1"
+        "#,
+    );
+    p.cargo("generate-lockfile -Z msrv-policy")
+        .masquerade_as_nightly_cargo()
+        .run();
+    assert!(p
+        .read_lockfile()
+        .contains("name = \"bar\"\nversion = \"1.1.0\""));
+
+    // Without `-Z msrv-policy` the `rust-version` is ignored.
+    p.change_file(
+        "Cargo.toml",
+        r#"
+        cargo-features = ["rust-version"]
+
+        [package]
+        name = "foo"
+        version = "0.0.1"
+        rust-version = "1.20"
+
+        [dependencies]
+        bar = "1.0"
+        "#,
+    );
+    p.cargo("generate-lockfile")
+        .masquerade_as_nightly_cargo()
+        .run();
+    assert!(p
+        .read_lockfile()
+        .contains("name = \"bar\"\nversion = \"1.1.0\""));
+}
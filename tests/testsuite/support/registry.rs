@@ -137,6 +137,7 @@ pub struct Package {
     features: HashMap<String, Vec<String>>,
     local: bool,
     alternative: bool,
+    rust_version: Option<String>,
 }
 
 #[derive(Clone)]
@@ -232,6 +233,7 @@ impl Package {
             features: HashMap::new(),
             local: false,
             alternative: false,
+            rust_version: None,
         }
     }
 
@@ -335,6 +337,12 @@ impl Package {
         self
     }
 
+    /// Sets the `rust_version` of the package in the index.
+    pub fn rust_version(&mut self, rust_version: &str) -> &mut Package {
+        self.rust_version = Some(rust_version.to_string());
+        self
+    }
+
     /// Adds an entry in the `[features]` section.
     pub fn feature(&mut self, name: &str, deps: &[&str]) -> &mut Package {
         let deps = deps.iter().map(|s| s.to_string()).collect();
@@ -384,15 +392,29 @@ impl Package {
             t!(t!(File::open(&self.archive_dst())).read_to_end(&mut c));
             cksum(&c)
         };
-        let line = serde_json::json!({
+        // Like crates.io, features using the `dep:` or `dep?/feature` syntax
+        // go into `features2`.
+        let (features2, features): (HashMap<_, _>, HashMap<_, _>) =
+            self.features.iter().partition(|(_, values)| {
+                values
+                    .iter()
+                    .any(|value| value.starts_with("dep:") || value.contains("?/"))
+            });
+        let mut line = serde_json::json!({
             "name": self.name,
             "vers": self.vers,
             "deps": deps,
             "cksum": cksum,
-            "features": self.features,
+            "features": features,
             "yanked": self.yanked,
-        })
-        .to_string();
+        });
+        if !features2.is_empty() {
+            line["features2"] = serde_json::json!(features2);
+        }
+        if let Some(ref rust_version) = self.rust_version {
+            line["rust_version"] = serde_json::json!(rust_version);
+        }
+        let line = line.to_string();
 
         let file = match self.name.len() {
             1 => format!("1/{}", self.name),
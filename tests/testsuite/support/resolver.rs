@@ -113,6 +113,7 @@ pub fn resolve_with_config_raw(
         &[],
         &mut registry,
         &HashSet::new(),
+        &HashSet::new(),
         config,
         false,
         true,
@@ -3659,3 +3659,372 @@ fn test_dep_with_dev() {
         )
         .run();
 }
+
+#[test]
+fn test_report_junit() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"
+            /// ```
+            /// assert_eq!(foo::add(1, 1), 2);
+            /// ```
+            pub fn add(a: u32, b: u32) -> u32 { a + b }
+
+            #[test]
+            fn passes() {}
+
+            #[test]
+            fn fails() { panic!("oh <no>"); }
+
+            #[test]
+            #[ignore]
+            fn ignored() {}
+        "#,
+        )
+        .build();
+
+    p.cargo("test --no-fail-fast --report junit=target/junit.xml")
+        .with_status(101)
+        .with_stdout_contains("test passes ... ok")
+        .with_stdout_contains("test fails ... FAILED")
+        .run();
+
+    let xml = p.read_file("target/junit.xml");
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+    assert!(xml.contains("<testsuites name=\"cargo test\" tests=\"4\" failures=\"1\""));
+    assert!(xml.contains("<testsuite name=\"foo::foo\" tests=\"3\" failures=\"1\""));
+    assert!(xml.contains("<property name=\"binary\" value=\"doctests\"/>"));
+    assert!(xml.contains("<testcase name=\"passes\" classname=\"foo::foo\"/>"));
+    assert!(xml.contains("<testcase name=\"ignored\" classname=\"foo::foo\">\n      <skipped/>"));
+    assert!(xml.contains("<failure message=\"test failed\">thread &apos;fails&apos; panicked"));
+    assert!(xml.contains("oh &lt;no&gt;"));
+    assert!(xml.contains("<testcase name=\"src/lib.rs - add (line "));
+}
+
+#[test]
+fn test_report_junit_quiet() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"
+            #[test]
+            fn passes() {}
+
+            #[test]
+            fn fails() { panic!("oh no"); }
+
+            #[test]
+            #[ignore]
+            fn ignored() {}
+        "#,
+        )
+        .build();
+
+    p.cargo("test --lib -q --report junit=target/junit.xml")
+        .with_status(101)
+        .with_stdout_does_not_contain("test passes ... ok")
+        .run();
+
+    let xml = p.read_file("target/junit.xml");
+    assert!(xml.contains(
+        "<testsuite name=\"foo::foo\" tests=\"3\" failures=\"1\" errors=\"0\" skipped=\"1\""
+    ));
+    assert!(xml.contains("<testcase name=\"fails\" classname=\"foo::foo\">"));
+    assert!(xml.contains("<failure message=\"test failed\">thread &apos;fails&apos; panicked"));
+}
+
+#[test]
+fn test_report_bad_format() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("test --report xml=out.xml")
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] unsupported report format `xml`, the only supported format is `junit`",
+        )
+        .run();
+}
+
+#[test]
+fn test_shard() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"
+            #[test] fn t1() {}
+            #[test] fn t2() {}
+            #[test] fn t3() {}
+            #[test] fn t4() {}
+            #[test] fn t5() {}
+            #[test] fn t6() {}
+            #[test] fn t7() {}
+            #[test] fn t8() {}
+        "#,
+        )
+        .build();
+
+    let mut seen = Vec::new();
+    for shard in &["1/3", "2/3", "3/3"] {
+        let output = p
+            .cargo(&format!("test --lib --shard {}", shard))
+            .exec_with_output()
+            .unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        seen.extend(
+            stdout
+                .lines()
+                .filter(|l| l.starts_with("test t") && l.ends_with(" ... ok"))
+                .map(|l| l.to_string()),
+        );
+    }
+    seen.sort();
+    let expected = (1..=8)
+        .map(|i| format!("test t{} ... ok", i))
+        .collect::<Vec<_>>();
+    assert_eq!(seen, expected);
+
+    // The assignment is deterministic.
+    let first = p
+        .cargo("test --lib --shard 1/3")
+        .exec_with_output()
+        .unwrap();
+    let second = p
+        .cargo("test --lib --shard 1/3")
+        .exec_with_output()
+        .unwrap();
+    let tests = |out: &[u8]| {
+        String::from_utf8_lossy(out)
+            .lines()
+            .filter(|l| l.starts_with("test t"))
+            .map(|l| l.to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(tests(&first.stdout), tests(&second.stdout));
+}
+
+#[test]
+fn test_shard_invalid() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("test --shard 3/2")
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] invalid shard `3/2`, expected `K/N` where 1 <= K <= N, for example `1/4`",
+        )
+        .run();
+}
+
+#[test]
+fn test_shard_filtered() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"
+            #[test] fn a() {}
+            #[test] fn a1() {}
+            #[test] fn b() {}
+        "#,
+        )
+        .build();
+
+    p.cargo("test --lib --shard 1/1 a")
+        .with_stdout_contains("test a ... ok")
+        .with_stdout_contains("test a1 ... ok")
+        .with_stdout_contains("test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 1 filtered out")
+        .run();
+
+    p.cargo("test --lib --shard 1/1 a -- --exact")
+        .with_stdout_contains("test a ... ok")
+        .with_stdout_contains("test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 2 filtered out")
+        .run();
+}
+
+#[test]
+fn test_retries_flaky() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"
+            #[test]
+            fn flaky() {
+                let marker = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("marker");
+                if !marker.exists() {
+                    std::fs::write(&marker, "").unwrap();
+                    panic!("first attempt");
+                }
+            }
+
+            #[test]
+            fn stable() {}
+        "#,
+        )
+        .build();
+
+    p.cargo("test --lib --retries 2")
+        .with_stdout_contains("test flaky ... FAILED")
+        .with_stdout_contains("test flaky ... ok")
+        .with_stderr_contains("[RETRYING] 1 failed test of foo::foo (attempt 1 of 2)")
+        .with_stderr_contains(
+            "\
+[WARNING] 1 test failed at first but passed when retried:
+    flaky: foo::foo flaky (passed on attempt 2)",
+        )
+        .run();
+}
+
+#[test]
+fn test_retries_flaky_quiet() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"
+            #[test]
+            fn flaky() {
+                let marker = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("marker");
+                if !marker.exists() {
+                    std::fs::write(&marker, "").unwrap();
+                    panic!("first attempt");
+                }
+            }
+
+            #[test]
+            fn stable() {}
+        "#,
+        )
+        .build();
+
+    p.cargo("test --lib -q --retries 2 --report junit=target/junit.xml")
+        .with_stdout_does_not_contain("test flaky ... [..]")
+        .run();
+
+    let xml = p.read_file("target/junit.xml");
+    assert!(xml.contains("<testsuite name=\"foo::foo\" tests=\"2\" failures=\"0\""));
+    assert!(xml.contains("<flakyFailure message=\"test failed\">thread &apos;flaky&apos;"));
+}
+
+#[test]
+fn test_retries_exhausted() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"
+            #[test]
+            fn broken() { panic!("always"); }
+        "#,
+        )
+        .build();
+
+    p.cargo("test --lib --retries 1")
+        .with_status(101)
+        .with_stderr_contains("[RETRYING] 1 failed test of foo::foo (attempt 1 of 1)")
+        .with_stderr_does_not_contain("[..]flaky[..]")
+        .run();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_timeout() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"
+            #[test]
+            fn a_quick() {}
+
+            #[test]
+            fn b_hangs() {
+                std::thread::sleep(std::time::Duration::from_secs(600));
+            }
+        "#,
+        )
+        .build();
+
+    p.cargo("test --lib --test-timeout 2s -- --test-threads 1")
+        .with_status(101)
+        .with_stdout_contains("test a_quick ... ok")
+        .with_stderr_contains(
+            "\
+[ERROR] test binary `target/debug/deps/foo-[..]` of `foo::foo` timed out after [..]
+1 of 2 tests finished before it was killed
+tests still running:
+    b_hangs",
+        )
+        .with_stderr_contains("[..]process timed out after 2.00s[..]")
+        .run();
+}
+
+#[test]
+fn test_timeout_config() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+            [test]
+            timeout = "forever"
+        "#,
+        )
+        .build();
+
+    p.cargo("test")
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] invalid `test.timeout` defined in [..]config
+
+Caused by:
+  invalid test timeout `forever`, expected a duration such as `90s`, `10m` or `1h`",
+        )
+        .run();
+}
+
+#[test]
+fn test_summary() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"
+            /// ```
+            /// assert!(true);
+            /// ```
+            pub fn foo() {}
+
+            #[test]
+            fn passes() {}
+
+            #[test]
+            fn fails() { panic!(); }
+
+            #[test]
+            #[ignore]
+            fn ignored() {}
+        "#,
+        )
+        .build();
+
+    p.cargo("test --no-fail-fast --summary")
+        .with_status(101)
+        .with_stderr_contains(
+            "[SUMMARY] 2 test binaries: 2 passed; 1 failed; 1 ignored; 0 measured; finished in [..]",
+        )
+        .with_stderr_contains(
+            "    foo::foo (target/debug/deps/foo-[..])[..]FAILED 1 passed; 1 failed; 1 ignored; 0 measured; [..]",
+        )
+        .with_stderr_contains(
+            "    foo::foo (doctests)[..]ok     1 passed; 0 failed; 0 ignored; 0 measured; [..]",
+        )
+        .with_stderr_contains(
+            "\
+failures:
+    foo::foo fails",
+        )
+        .run();
+
+    p.cargo("test --lib --summary --message-format json")
+        .with_status(101)
+        .with_stdout_contains(
+            r#"{"reason":"test-summary","binaries":[{"package_id":"foo 0.0.1 ([..])","target":"foo","binary":"target/debug/deps/foo-[..]","passed":1,"failed":1,"ignored":1,"measured":0,"filtered_out":0,"duration":[..],"error":"[..]"}],"totals":{"passed":1,"failed":1,"ignored":1,"measured":0,"filtered_out":0},"duration":[..],"failures":[{"package_id":"foo 0.0.1 ([..])","target":"foo","name":"fails"}]}"#,
+        )
+        .run();
+}
@@ -0,0 +1,414 @@
+use crate::support::cross_compile;
+use crate::support::registry::Package;
+use crate::support::{project, rustc_host};
+
+#[test]
+fn tree_kinds() {
+    Package::new("b", "1.0.0").publish();
+    Package::new("a", "1.0.0").dep("b", "1.0").publish();
+    Package::new("c", "1.0.0").publish();
+    Package::new("d", "1.0.0").dep("b", "1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            a = "1.0"
+
+            [build-dependencies]
+            c = "1.0"
+
+            [dev-dependencies]
+            d = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("tree -Z unstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stdout(
+            "\
+foo v0.1.0 ([CWD])
+├── a v1.0.0
+│   └── b v1.0.0
+[build-dependencies]
+└── c v1.0.0
+[dev-dependencies]
+└── d v1.0.0
+    └── b v1.0.0
+",
+        )
+        .run();
+
+    p.cargo("tree -Z unstable-options --edges normal,dev --depth 1")
+        .masquerade_as_nightly_cargo()
+        .with_stdout(
+            "\
+foo v0.1.0 ([CWD])
+├── a v1.0.0
+[dev-dependencies]
+└── d v1.0.0
+",
+        )
+        .run();
+
+    p.cargo("tree -Z unstable-options --edges normal,test")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr("[ERROR] unknown edge kind `test`, expected one of `normal`, `build` or `dev`")
+        .run();
+}
+
+#[test]
+fn tree_repeated_subtree() {
+    Package::new("c", "1.0.0").publish();
+    Package::new("b", "1.0.0").dep("c", "1.0").publish();
+    Package::new("a", "1.0.0").dep("b", "1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            a = "1.0"
+            b = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("tree -Z unstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stdout(
+            "\
+foo v0.1.0 ([CWD])
+├── a v1.0.0
+│   └── b v1.0.0
+│       └── c v1.0.0
+└── b v1.0.0 (*)
+",
+        )
+        .run();
+}
+
+#[test]
+fn tree_invert_and_duplicates() {
+    Package::new("b", "1.0.0").publish();
+    Package::new("b", "2.0.0").publish();
+    Package::new("a", "1.0.0").dep("b", "1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            a = "1.0"
+            b = "2.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("tree -Z unstable-options --invert b:1.0.0")
+        .masquerade_as_nightly_cargo()
+        .with_stdout(
+            "\
+b v1.0.0
+└── a v1.0.0
+    └── foo v0.1.0 ([CWD])
+",
+        )
+        .run();
+
+    p.cargo("tree -Z unstable-options --duplicates")
+        .masquerade_as_nightly_cargo()
+        .with_stdout(
+            "\
+b v1.0.0
+└── a v1.0.0
+    └── foo v0.1.0 ([CWD])
+
+b v2.0.0
+└── foo v0.1.0 ([CWD])
+",
+        )
+        .run();
+}
+
+#[test]
+fn tree_target() {
+    Package::new("a", "1.0.0").publish();
+    Package::new("never", "1.0.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            a = "1.0"
+
+            [target.'cfg(foobar)'.dependencies]
+            never = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("tree -Z unstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stdout(
+            "\
+foo v0.1.0 ([CWD])
+├── a v1.0.0
+└── never v1.0.0
+",
+        )
+        .run();
+
+    p.cargo(&format!("tree -Z unstable-options --target {}", rustc_host()))
+        .masquerade_as_nightly_cargo()
+        .with_stdout(
+            "\
+foo v0.1.0 ([CWD])
+└── a v1.0.0
+",
+        )
+        .run();
+}
+
+#[test]
+fn tree_target_build_deps_use_host() {
+    if cross_compile::disabled() {
+        return;
+    }
+    Package::new("host_only", "1.0.0").publish();
+    Package::new("target_only", "1.0.0").publish();
+    Package::new("b", "1.0.0")
+        .target_dep(
+            "host_only",
+            "1.0",
+            &format!("cfg(target_arch = \"{}\")", std::env::consts::ARCH),
+        )
+        .target_dep(
+            "target_only",
+            "1.0",
+            &format!("cfg(target_arch = \"{}\")", cross_compile::alternate_arch()),
+        )
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [build-dependencies]
+            b = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo(&format!("tree -Z unstable-options --target {}", cross_compile::alternate()))
+        .masquerade_as_nightly_cargo()
+        .with_stdout(
+            "\
+foo v0.1.0 ([CWD])
+[build-dependencies]
+└── b v1.0.0
+    └── host_only v1.0.0
+",
+        )
+        .run();
+}
+
+#[test]
+fn tree_target_proc_macro_deps_use_host() {
+    if cross_compile::disabled() {
+        return;
+    }
+    Package::new("host_only", "1.0.0").publish();
+    Package::new("target_only", "1.0.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            m = { path = "m" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "m/Cargo.toml",
+            &format!(
+                r#"
+                [package]
+                name = "m"
+                version = "0.1.0"
+
+                [lib]
+                proc-macro = true
+
+                [target.'cfg(target_arch = "{}")'.dependencies]
+                host_only = "1.0"
+
+                [target.'cfg(target_arch = "{}")'.dependencies]
+                target_only = "1.0"
+                "#,
+                std::env::consts::ARCH,
+                cross_compile::alternate_arch()
+            ),
+        )
+        .file("m/src/lib.rs", "")
+        .build();
+
+    p.cargo(&format!(
+        "tree -Z unstable-options --target {}",
+        cross_compile::alternate()
+    ))
+    .masquerade_as_nightly_cargo()
+    .with_stdout(
+        "\
+foo v0.1.0 ([CWD])
+└── m v0.1.0 ([CWD]/m)
+    └── host_only v1.0.0
+",
+    )
+    .run();
+}
+
+#[test]
+fn tree_requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("tree")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr("[ERROR] `cargo tree` is unstable, pass `-Z unstable-options` to enable it")
+        .run();
+}
+
+#[test]
+fn tree_format() {
+    Package::new("a", "1.0.0")
+        .feature("x", &[])
+        .feature("y", &[])
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            license = "MIT"
+            repository = "https://example.com/foo"
+
+            [dependencies]
+            a = { version = "1.0", features = ["x"] }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("tree -Z unstable-options --format")
+        .masquerade_as_nightly_cargo()
+        .arg("{p} {l} {r} [{f}]")
+        .with_stdout(
+            "\
+foo v0.1.0 ([CWD]) MIT https://example.com/foo []
+└── a v1.0.0   [x]
+",
+        )
+        .run();
+
+    p.cargo("tree -Z unstable-options --format {x}")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] unsupported pattern `{x}` in format `{x}`, \
+             expected one of `{p}`, `{l}`, `{r}` or `{f}`",
+        )
+        .run();
+}
+
+#[test]
+fn tree_json() {
+    Package::new("a", "1.0.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            a = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("tree -Z unstable-options --message-format json")
+        .masquerade_as_nightly_cargo()
+        .with_json(
+            r#"
+            {
+                "roots": [
+                    {
+                        "id": "foo 0.1.0 (path+file:[..]foo)",
+                        "name": "foo",
+                        "version": "0.1.0",
+                        "license": null,
+                        "repository": null,
+                        "features": [],
+                        "kind": null,
+                        "repeated": false,
+                        "dependencies": [
+                            {
+                                "id": "a 1.0.0 (registry+[..])",
+                                "name": "a",
+                                "version": "1.0.0",
+                                "license": null,
+                                "repository": null,
+                                "features": [],
+                                "kind": "normal",
+                                "repeated": false,
+                                "dependencies": []
+                            }
+                        ]
+                    }
+                ]
+            }
+            "#,
+        )
+        .run();
+}
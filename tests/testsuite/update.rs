@@ -474,3 +474,140 @@ fn dry_run_update() {
     let new_lockfile = p.read_file("Cargo.lock");
     assert_eq!(old_lockfile, new_lockfile)
 }
+
+#[test]
+fn dry_run_reports_semver_changes() {
+    Package::new("bar", "0.1.0").publish();
+    Package::new("baz", "1.0.0").publish();
+    Package::new("old", "1.0.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                authors = []
+
+                [dependencies]
+                bar = ">= 0.1"
+                baz = "1.0"
+                old = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+    let old_lockfile = p.read_file("Cargo.lock");
+
+    Package::new("bar", "0.2.0").publish();
+    Package::new("baz", "1.1.0").publish();
+    Package::new("new", "1.0.0").publish();
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = ">= 0.1"
+            baz = "1.0"
+            new = "1.0"
+        "#,
+    );
+
+    p.cargo("update --dry-run")
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[UPDATING] bar v0.1.0 -> v0.2.0 (semver-breaking)
+[UPDATING] baz v1.0.0 -> v1.1.0
+[ADDING] new v1.0.0
+[REMOVING] old v1.0.0
+[WARNING] not updating lockfile due to dry run
+",
+        )
+        .run();
+    assert_eq!(old_lockfile, p.read_file("Cargo.lock"));
+
+    p.cargo("update --dry-run --message-format json")
+        .with_json(
+            r#"
+            {
+                "changes": [
+                    {
+                        "name": "bar",
+                        "source": "registry+[..]",
+                        "kind": "upgraded",
+                        "old_version": "0.1.0",
+                        "new_version": "0.2.0",
+                        "compatible": false
+                    },
+                    {
+                        "name": "baz",
+                        "source": "registry+[..]",
+                        "kind": "upgraded",
+                        "old_version": "1.0.0",
+                        "new_version": "1.1.0",
+                        "compatible": true
+                    },
+                    {
+                        "name": "new",
+                        "source": "registry+[..]",
+                        "kind": "added",
+                        "old_version": null,
+                        "new_version": "1.0.0",
+                        "compatible": null
+                    },
+                    {
+                        "name": "old",
+                        "source": "registry+[..]",
+                        "kind": "removed",
+                        "old_version": "1.0.0",
+                        "new_version": null,
+                        "compatible": null
+                    }
+                ]
+            }
+            "#,
+        )
+        .run();
+    assert_eq!(old_lockfile, p.read_file("Cargo.lock"));
+}
+
+#[test]
+fn dry_run_without_lockfile() {
+    Package::new("bar", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                authors = []
+
+                [dependencies]
+                bar = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("update --dry-run")
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[ADDING] bar v0.1.0
+[ADDING] foo v0.0.1 ([..])
+[WARNING] not updating lockfile due to dry run
+",
+        )
+        .run();
+    assert!(!p.root().join("Cargo.lock").exists());
+}
@@ -0,0 +1,95 @@
+use crate::support::registry::Package;
+use crate::support::{basic_manifest, project};
+
+#[test]
+fn upgrade_workspace() {
+    Package::new("bar", "0.1.0").publish();
+    Package::new("bar", "0.1.5").publish();
+    Package::new("bar", "0.2.3").publish();
+    Package::new("bar", "0.3.0-alpha.1").publish();
+    Package::new("baz", "1.0.0").publish();
+    Package::new("baz", "1.2.0").publish();
+    Package::new("baz", "2.0.0").publish();
+    Package::new("pinned", "1.0.0").publish();
+    Package::new("pinned", "1.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+
+            [workspace]
+            members = ["member"]
+
+            [dependencies]
+            bar = "0.1" # the parser
+            baz = { version = "1.0", default-features = false }
+            pinned = "=1.0.0"
+            member = { path = "member", version = "0.2.0" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("member/Cargo.toml", &basic_manifest("member", "0.2.1"))
+        .file("member/src/lib.rs", "")
+        .build();
+
+    p.cargo("upgrade -Z unstable-options --dry-run")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[UPGRADING] bar 0.1 -> 0.2 (dependencies of foo)
+[UPGRADING] baz 1.0 -> 2.0 (dependencies of foo)
+[UPGRADING] member 0.2.0 -> 0.2.1 (dependencies of foo)
+[WARNING] not updating the manifests due to dry run
+",
+        )
+        .run();
+
+    p.cargo("upgrade -Z unstable-options --compatible")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[UPGRADING] baz 1.0 -> 1.2 (dependencies of foo)
+[UPGRADING] member 0.2.0 -> 0.2.1 (dependencies of foo)
+",
+        )
+        .run();
+    let manifest = p.read_file("Cargo.toml");
+    assert!(manifest.contains(r#"baz = { version = "1.2", default-features = false }"#));
+    assert!(manifest.contains(r#"member = { path = "member", version = "0.2.1" }"#));
+    assert!(p
+        .read_lockfile()
+        .contains("name = \"baz\"\nversion = \"1.2.0\""));
+
+    p.cargo("upgrade -Z unstable-options bar --incompatible")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[UPGRADING] bar 0.1 -> 0.2 (dependencies of foo)
+",
+        )
+        .run();
+    let manifest = p.read_file("Cargo.toml");
+    assert!(manifest.contains("bar = \"0.2\" # the parser\n"));
+    assert!(manifest.contains("pinned = \"=1.0.0\"\n"));
+    assert!(p
+        .read_lockfile()
+        .contains("name = \"bar\"\nversion = \"0.2.3\""));
+}
+
+#[test]
+fn upgrade_requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("upgrade")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr("[ERROR] `cargo upgrade` is unstable, pass `-Z unstable-options` to enable it")
+        .run();
+}
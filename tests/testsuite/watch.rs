@@ -0,0 +1,119 @@
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Stdio};
+use std::sync::mpsc::{channel, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use crate::support::{basic_bin_manifest, basic_manifest, project, sleep_ms, Project};
+
+/// Starts `cargo <cmd> --watch`, returning the process and a channel
+/// receiving the lines it prints to stderr.
+fn spawn_watch(p: &Project, cmd: &str) -> (Child, Receiver<String>) {
+    let mut cargo = p.cargo(&format!("{} --watch", cmd)).build_command();
+    cargo.stdout(Stdio::null()).stderr(Stdio::piped());
+    let mut child = cargo.spawn().unwrap();
+    let stderr = BufReader::new(child.stderr.take().unwrap());
+    let (tx, rx) = channel();
+    thread::spawn(move || {
+        for line in stderr.lines() {
+            if tx.send(line.unwrap()).is_err() {
+                break;
+            }
+        }
+    });
+    (child, rx)
+}
+
+/// Waits for a line containing `expected`, returning the lines printed
+/// before it.
+fn wait_for(rx: &Receiver<String>, expected: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    loop {
+        match rx.recv_timeout(Duration::from_secs(60)) {
+            Ok(line) => {
+                if line.contains(expected) {
+                    return lines;
+                }
+                lines.push(line);
+            }
+            Err(e) => panic!(
+                "never saw `{}` ({}), got:\n{}",
+                expected,
+                e,
+                lines.join("\n")
+            ),
+        }
+    }
+}
+
+#[test]
+fn watch_rebuilds_path_dependency() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = { path = "bar" }
+            "#,
+        )
+        .file("src/main.rs", "fn main() { bar::bar(); }")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "pub fn bar() {}")
+        .build();
+
+    let (mut child, rx) = spawn_watch(&p, "build");
+    let lines = wait_for(&rx, "Watching");
+    assert!(lines.iter().any(|l| l.contains("Compiling bar")));
+    assert!(lines.iter().any(|l| l.contains("Compiling foo")));
+
+    // Make sure the new modification time differs on file systems with a
+    // coarse resolution.
+    sleep_ms(1000);
+    p.change_file("bar/src/lib.rs", "pub fn bar() { println!(\"bar\"); }");
+    let lines = wait_for(&rx, "Watching");
+    assert!(lines
+        .iter()
+        .any(|l| l.contains("Changed") && l.contains("lib.rs")));
+    assert!(lines.iter().any(|l| l.contains("Compiling bar")));
+
+    // Output in the target directory never triggers a rebuild.
+    sleep_ms(1000);
+    p.change_file("target/debug/touched", "");
+    sleep_ms(1000);
+    assert!(rx.try_recv().is_err());
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn watch_reports_build_errors_and_keeps_watching() {
+    let p = project()
+        .file("Cargo.toml", &basic_bin_manifest("foo"))
+        .file("src/main.rs", "fn main() { eprintln!(\"hello 1\"); }")
+        .build();
+
+    let (mut child, rx) = spawn_watch(&p, "run");
+    // The binary runs concurrently with setting up the watch.
+    let lines = wait_for(&rx, "Watching");
+    if !lines.iter().any(|l| l.contains("hello 1")) {
+        wait_for(&rx, "hello 1");
+    }
+
+    sleep_ms(1000);
+    p.change_file("src/main.rs", "fn main() { missing }");
+    let lines = wait_for(&rx, "Watching");
+    assert!(lines.iter().any(|l| l.contains("could not compile `foo`")));
+
+    sleep_ms(1000);
+    p.change_file("src/main.rs", "fn main() { eprintln!(\"hello 2\"); }");
+    wait_for(&rx, "hello 2");
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
@@ -0,0 +1,461 @@
+use std::fs::File;
+use std::io::Read;
+
+use flate2::read::GzDecoder;
+use tar::Archive;
+
+use crate::support::registry::Package;
+use crate::support::{basic_lib_manifest, project, Project};
+
+/// Returns the `Cargo.toml` which `cargo package` wrote into `krate`.
+fn packaged_manifest(p: &Project, krate: &str) -> String {
+    let f = File::open(p.root().join("target/package").join(krate)).unwrap();
+    let mut archive = Archive::new(GzDecoder::new(f));
+    for entry in archive.entries().unwrap() {
+        let mut entry = entry.unwrap();
+        if entry.path().unwrap().ends_with("Cargo.toml") {
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents).unwrap();
+            return contents;
+        }
+    }
+    panic!("no Cargo.toml in {}", krate);
+}
+
+#[test]
+fn inherit_dependencies() {
+    Package::new("dep", "0.1.0")
+        .feature("a", &[])
+        .feature("b", &[])
+        .file(
+            "src/lib.rs",
+            r#"
+            #[cfg(not(all(feature = "a", feature = "b")))]
+            compile_error!("missing features");
+            "#,
+        )
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["workspace-inheritance"]
+
+            [workspace]
+            members = ["bar"]
+
+            [workspace.dependencies]
+            dep = { version = "0.1", features = ["a"] }
+            util = { path = "util" }
+            "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            cargo-features = ["workspace-inheritance"]
+
+            [package]
+            name = "bar"
+            version = "0.1.0"
+            authors = []
+            license = "MIT"
+            description = "bar"
+
+            [dependencies]
+            dep = { workspace = true, features = ["b"] }
+            util = { workspace = true }
+            "#,
+        )
+        .file("bar/src/lib.rs", "extern crate dep; extern crate util;")
+        .file("util/Cargo.toml", &basic_lib_manifest("util"))
+        .file("util/src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[UPDATING] [..]
+[DOWNLOADING] crates ...
+[DOWNLOADED] dep v0.1.0 ([..])
+[COMPILING] [..]
+[COMPILING] [..]
+[COMPILING] bar v0.1.0 ([CWD]/bar)
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
+#[test]
+fn inherited_dependencies_are_written_out_when_packaging() {
+    Package::new("dep", "0.1.0")
+        .feature("a", &[])
+        .feature("b", &[])
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["workspace-inheritance"]
+
+            [workspace]
+            members = ["bar"]
+
+            [workspace.dependencies]
+            dep = { version = "0.1", features = ["a"], default-features = false }
+            "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            cargo-features = ["workspace-inheritance"]
+
+            [package]
+            name = "bar"
+            version = "0.1.0"
+            authors = []
+            license = "MIT"
+            description = "bar"
+
+            [dependencies]
+            dep = { workspace = true, features = ["b"], optional = true }
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("package --no-verify")
+        .cwd(p.root().join("bar"))
+        .masquerade_as_nightly_cargo()
+        .run();
+
+    let manifest = packaged_manifest(&p, "bar-0.1.0.crate");
+    assert!(
+        manifest.contains(
+            "\
+[dependencies.dep]
+version = \"0.1\"
+features = [\"a\", \"b\"]
+optional = true
+default-features = false
+"
+        ),
+        "{}",
+        manifest
+    );
+    assert!(!manifest.contains("workspace"), "{}", manifest);
+}
+
+#[test]
+fn inherited_dependency_missing() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["workspace-inheritance"]
+
+            [workspace]
+            members = ["bar"]
+
+            [workspace.dependencies]
+            "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            cargo-features = ["workspace-inheritance"]
+
+            [package]
+            name = "bar"
+            version = "0.1.0"
+
+            [dependencies]
+            dep = { workspace = true }
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/bar/Cargo.toml`
+
+Caused by:
+  dependency `dep` is inherited from the workspace, but it isn't specified in \
+`[workspace.dependencies]` of `[CWD]/Cargo.toml`
+",
+        )
+        .run();
+}
+
+#[test]
+fn inherited_dependency_with_version() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["workspace-inheritance"]
+
+            [workspace]
+            members = ["bar"]
+
+            [workspace.dependencies]
+            dep = "0.1"
+            "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            cargo-features = ["workspace-inheritance"]
+
+            [package]
+            name = "bar"
+            version = "0.1.0"
+
+            [dependencies]
+            dep = { workspace = true, version = "0.2" }
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/bar/Cargo.toml`
+
+Caused by:
+  dependency `dep` is inherited from the workspace, so `version` can't be \
+specified for it, only `features`, `optional` and `public` can
+",
+        )
+        .run();
+}
+
+#[test]
+fn workspace_inheritance_unstable() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [workspace]
+            members = ["bar"]
+            "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            [package]
+            name = "bar"
+            version = "0.1.0"
+
+            [dependencies]
+            dep = { workspace = true }
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/bar/Cargo.toml`
+
+Caused by:
+  inheriting values from the workspace is unstable
+
+Caused by:
+  feature `workspace-inheritance` is required
+
+consider adding `cargo-features = [\"workspace-inheritance\"]` to the manifest
+",
+        )
+        .run();
+}
+
+#[test]
+fn inherit_package_keys() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["workspace-inheritance"]
+
+            [workspace]
+            members = ["bar"]
+
+            [workspace.package]
+            version = "1.2.3"
+            authors = ["Ferris"]
+            license = "MIT OR Apache-2.0"
+            keywords = ["cli"]
+            "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            cargo-features = ["workspace-inheritance"]
+
+            [package]
+            name = "bar"
+            version.workspace = true
+            authors.workspace = true
+            license = { workspace = true }
+            keywords = ["parser"]
+            description = "bar"
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[COMPILING] bar v1.2.3 ([CWD]/bar)
+[FINISHED] [..]
+",
+        )
+        .run();
+
+    p.cargo("package --no-verify")
+        .cwd(p.root().join("bar"))
+        .masquerade_as_nightly_cargo()
+        .run();
+
+    let manifest = packaged_manifest(&p, "bar-1.2.3.crate");
+    assert!(
+        manifest.contains(
+            "\
+name = \"bar\"
+version = \"1.2.3\"
+authors = [\"Ferris\"]
+"
+        ),
+        "{}",
+        manifest
+    );
+    assert!(
+        manifest.contains("keywords = [\"parser\"]\n"),
+        "{}",
+        manifest
+    );
+    assert!(
+        manifest.contains("license = \"MIT OR Apache-2.0\"\n"),
+        "{}",
+        manifest
+    );
+    assert!(!manifest.contains("workspace"), "{}", manifest);
+}
+
+#[test]
+fn inherited_package_key_missing() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["workspace-inheritance"]
+
+            [workspace]
+            members = ["bar"]
+
+            [workspace.package]
+            version = "1.2.3"
+            "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            cargo-features = ["workspace-inheritance"]
+
+            [package]
+            name = "bar"
+            version.workspace = true
+            repository.workspace = true
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/bar/Cargo.toml`
+
+Caused by:
+  `package.repository` is inherited from the workspace, but it isn't \
+specified in `[workspace.package]` of `[CWD]/Cargo.toml`
+",
+        )
+        .run();
+}
+
+#[test]
+fn workspace_package_unstable() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [workspace]
+            members = ["bar"]
+
+            [workspace.package]
+            version = "1.2.3"
+            "#,
+        )
+        .file("bar/Cargo.toml", &basic_lib_manifest("bar"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  the `[workspace.package]` table is unstable
+
+Caused by:
+  feature `workspace-inheritance` is required
+
+consider adding `cargo-features = [\"workspace-inheritance\"]` to the manifest
+",
+        )
+        .run();
+}
+
+#[test]
+fn inheritable_key_with_invalid_type() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = 1
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains(
+            "  invalid type: integer `1`, expected a SemVer version as a string[..]",
+        )
+        .run();
+}
@@ -0,0 +1,181 @@
+use crate::support::project;
+use crate::support::registry::Package;
+
+#[test]
+fn duplicates_denied() {
+    Package::new("bar", "0.1.0").publish();
+    Package::new("bar", "0.2.0").publish();
+    Package::new("baz", "1.0.0").dep("bar", "0.2").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["workspace-policy"]
+
+            [package]
+            name = "foo"
+            version = "0.0.1"
+
+            [dependencies]
+            bar = "0.1"
+            baz = "1.0"
+
+            [workspace]
+
+            [workspace.policy]
+            duplicates = "deny"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[ERROR] package `bar` is used in several semver incompatible versions
+
+package `bar v0.1.0`
+    ... which is depended on by `foo v0.0.1 ([..])`
+
+package `bar v0.2.0`
+    ... which is depended on by `baz v1.0.0`
+    ... which is depended on by `foo v0.0.1 ([..])`
+",
+        )
+        .run();
+    assert!(!p.root().join("Cargo.lock").is_file());
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+        cargo-features = ["workspace-policy"]
+
+        [package]
+        name = "foo"
+        version = "0.0.1"
+
+        [dependencies]
+        bar = "0.1"
+        baz = "1.0"
+
+        [workspace]
+
+        [workspace.policy]
+        duplicates = "deny"
+        allow-duplicates = ["bar"]
+        "#,
+    );
+    p.cargo("generate-lockfile")
+        .masquerade_as_nightly_cargo()
+        .with_stderr("[UPDATING] `[..]` index")
+        .run();
+}
+
+#[test]
+fn banned_packages() {
+    Package::new("bar", "0.1.0").publish();
+    Package::new("baz", "1.0.0").dep("bar", "0.1").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["workspace-policy"]
+
+            [package]
+            name = "foo"
+            version = "0.0.1"
+
+            [dependencies]
+            baz = "1.0"
+
+            [workspace]
+
+            [workspace.policy]
+            bans = "warn"
+            banned = ["bar@0.1"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[WARNING] package `bar v0.1.0` is banned by the workspace policy
+
+package `bar v0.1.0`
+    ... which is depended on by `baz v1.0.0`
+    ... which is depended on by `foo v0.0.1 ([..])`
+",
+        )
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+        cargo-features = ["workspace-policy"]
+
+        [package]
+        name = "foo"
+        version = "0.0.1"
+
+        [dependencies]
+        baz = "1.0"
+
+        [workspace]
+
+        [workspace.policy]
+        banned = ["bar"]
+        allow-banned-in = ["baz"]
+        "#,
+    );
+    p.cargo("generate-lockfile")
+        .masquerade_as_nightly_cargo()
+        .with_stderr("[UPDATING] `[..]` index")
+        .run();
+}
+
+#[test]
+fn policy_requires_feature() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+
+            [workspace]
+
+            [workspace.policy]
+            duplicates = "deny"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  the `[workspace.policy]` table is unstable
+
+Caused by:
+  feature `workspace-policy` is required
+
+consider adding `cargo-features = [\"workspace-policy\"]` to the manifest
+",
+        )
+        .run();
+}